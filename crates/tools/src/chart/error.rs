@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ChartError {
+    #[error("无法写入文件: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("不支持的图片格式: {0}")]
+    UnsupportedFormat(String),
+    #[error("渲染图表失败: {0}")]
+    Render(String),
+}
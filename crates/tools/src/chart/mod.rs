@@ -0,0 +1,6 @@
+mod error;
+mod model;
+mod svg;
+pub use error::*;
+pub use model::*;
+pub use svg::*;
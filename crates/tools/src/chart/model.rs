@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use chrono::{Datelike, Days, NaiveDate};
+use resvg::{tiny_skia, usvg};
+
+use crate::{CHART_FONTS, ChartError, render_svg};
+
+/// Image formats a chart can be saved as.
+pub const CHART_EXTENSIONS: &[&str] = &["png", "svg"];
+/// PNG exports are rendered at this multiple of the SVG size.
+const PNG_SCALE: f32 = 2.0;
+
+pub struct ChartTool {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    Line,
+    Bar,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSeries {
+    pub name: String,
+    /// One value per chart label.
+    pub values: Vec<f64>,
+}
+
+/// Categorical chart data shared by the on-screen charts and the image
+/// export, so both show the same numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    pub title: String,
+    pub kind: ChartKind,
+    pub labels: Vec<String>,
+    pub series: Vec<ChartSeries>,
+}
+
+impl Chart {
+    pub fn new(title: impl Into<String>, kind: ChartKind, labels: Vec<String>) -> Self {
+        Self { title: title.into(), kind, labels, series: Vec::new() }
+    }
+
+    pub fn series(mut self, name: impl Into<String>, values: Vec<f64>) -> Self {
+        self.series.push(ChartSeries { name: name.into(), values });
+        self
+    }
+
+    /// The largest value over all series, at least zero.
+    pub fn max_value(&self) -> f64 {
+        self.series.iter().flat_map(|series| series.values.iter().copied()).fold(0.0, f64::max)
+    }
+}
+
+impl ChartTool {
+    /// Save `chart` as an image, picking the format from the extension.
+    pub fn export(chart: &Chart, path: impl AsRef<Path>) -> Result<(), ChartError> {
+        let path = path.as_ref();
+        let extension =
+            path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        let svg = render_svg(chart);
+        match extension.as_str() {
+            "svg" => Ok(std::fs::write(path, svg)?),
+            "png" => Self::write_png(&svg, path),
+            _ => Err(ChartError::UnsupportedFormat(extension)),
+        }
+    }
+
+    fn write_png(svg: &str, path: &Path) -> Result<(), ChartError> {
+        let mut options = usvg::Options::default();
+        let fonts = options.fontdb_mut();
+        fonts.load_system_fonts();
+        // `sans-serif` resolves to Arial by default, and text in a missing
+        // font is silently dropped, so point it at a font that exists.
+        let has_family = |name: &str| {
+            fonts.faces().any(|face| face.families.iter().any(|(family, _)| family == name))
+        };
+        let fallback =
+            CHART_FONTS.iter().map(|name| name.to_string()).find(|name| has_family(name));
+        if let Some(family) = fallback
+            .or_else(|| fonts.faces().find_map(|face| Some(face.families.first()?.0.clone())))
+        {
+            fonts.set_sans_serif_family(family);
+        }
+        let tree = usvg::Tree::from_str(svg, &options)
+            .map_err(|err| ChartError::Render(err.to_string()))?;
+
+        let size = tree
+            .size()
+            .to_int_size()
+            .scale_by(PNG_SCALE)
+            .ok_or_else(|| ChartError::Render("图片尺寸无效".to_string()))?;
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .ok_or_else(|| ChartError::Render("图片尺寸无效".to_string()))?;
+        pixmap.fill(tiny_skia::Color::WHITE);
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(PNG_SCALE, PNG_SCALE),
+            &mut pixmap.as_mut(),
+        );
+        pixmap.save_png(path).map_err(|err| ChartError::Render(err.to_string()))
+    }
+}
+
+/// The Monday of the week containing `date`.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+/// The Mondays of every week overlapping `start..=end`.
+pub fn week_starts(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut weeks = Vec::new();
+    let mut week = week_start(start);
+    while week <= end {
+        weeks.push(week);
+        week = week + Days::new(7);
+    }
+    weeks
+}
+
+/// Axis label of the week starting on `week`.
+pub fn week_label(week: NaiveDate) -> String {
+    week.format("%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn week_start_is_the_monday() {
+        // 2026-01-05 is a Monday.
+        assert_eq!(week_start(date(2026, 1, 5)), date(2026, 1, 5));
+        assert_eq!(week_start(date(2026, 1, 7)), date(2026, 1, 5));
+        assert_eq!(week_start(date(2026, 1, 11)), date(2026, 1, 5));
+        assert_eq!(week_start(date(2026, 1, 1)), date(2025, 12, 29));
+    }
+
+    #[test]
+    fn week_starts_cover_partial_weeks() {
+        let weeks = week_starts(date(2026, 1, 1), date(2026, 1, 12));
+        assert_eq!(weeks, vec![date(2025, 12, 29), date(2026, 1, 5), date(2026, 1, 12)]);
+        assert_eq!(week_starts(date(2026, 1, 7), date(2026, 1, 7)), vec![date(2026, 1, 5)]);
+        assert!(week_starts(date(2026, 1, 12), date(2026, 1, 1)).is_empty());
+    }
+
+    #[test]
+    fn max_value_is_never_negative() {
+        let chart = Chart::new("", ChartKind::Bar, vec!["a".into(), "b".into()])
+            .series("x", vec![-3.0, 2.0])
+            .series("y", vec![5.0, 1.0]);
+        assert_eq!(chart.max_value(), 5.0);
+        let chart = Chart::new("", ChartKind::Bar, Vec::new()).series("x", vec![-1.0]);
+        assert_eq!(chart.max_value(), 0.0);
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt::Write as _;
+
+use crate::{Chart, ChartKind};
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 360.0;
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 24.0;
+const MARGIN_TOP: f64 = 48.0;
+const MARGIN_BOTTOM: f64 = 64.0;
+/// Horizontal grid lines, not counting the axis.
+const GRID_LINES: usize = 4;
+/// At most this many x labels are drawn, the rest are skipped.
+const MAX_X_LABELS: usize = 12;
+/// Series colors as RGB, in order.
+const CHART_COLORS: &[u32] = &[0x3b82f6, 0xef4444, 0x22c55e, 0xf59e0b, 0x8b5cf6];
+/// Preferred fonts, CJK-capable ones first. The SVG lists them before the
+/// generic `sans-serif`.
+pub(crate) const CHART_FONTS: &[&str] =
+    &["Microsoft YaHei", "PingFang SC", "Noto Sans CJK SC", "WenQuanYi Micro Hei", "DejaVu Sans"];
+
+/// Draw `chart` as a standalone SVG document with a title, y grid, x labels
+/// and a legend.
+pub(crate) fn render_svg(chart: &Chart) -> String {
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let bottom = MARGIN_TOP + plot_height;
+    let max = nice_max(chart.max_value());
+    let band = plot_width / chart.labels.len().max(1) as f64;
+    let y = |value: f64| bottom - value / max * plot_height;
+
+    let mut svg = String::new();
+    _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="{}, sans-serif" font-size="12">"##,
+        CHART_FONTS.join(", ")
+    );
+    _ = write!(svg, r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+    _ = write!(
+        svg,
+        r##"<text x="{}" y="28" text-anchor="middle" font-size="16" font-weight="bold" fill="#111827">{}</text>"##,
+        WIDTH / 2.0,
+        escape(&chart.title)
+    );
+
+    for line in 0..=GRID_LINES {
+        let value = max / GRID_LINES as f64 * line as f64;
+        let y = y(value);
+        _ = write!(
+            svg,
+            r##"<line x1="{MARGIN_LEFT}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{}"/>"##,
+            MARGIN_LEFT + plot_width,
+            if line == 0 { "#9ca3af" } else { "#e5e7eb" }
+        );
+        _ = write!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="end" fill="#6b7280">{}</text>"##,
+            MARGIN_LEFT - 8.0,
+            y + 4.0,
+            format_value(value)
+        );
+    }
+
+    let label_step = chart.labels.len().div_ceil(MAX_X_LABELS).max(1);
+    for (index, label) in chart.labels.iter().enumerate().step_by(label_step) {
+        _ = write!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="#6b7280">{}</text>"##,
+            MARGIN_LEFT + band * (index as f64 + 0.5),
+            bottom + 18.0,
+            escape(label)
+        );
+    }
+
+    let series_count = chart.series.len().max(1) as f64;
+    for (index, series) in chart.series.iter().enumerate() {
+        let color = series_color(index);
+        match chart.kind {
+            ChartKind::Bar => {
+                let bar_width = band * 0.7 / series_count;
+                for (slot, value) in series.values.iter().enumerate() {
+                    let x = MARGIN_LEFT + band * (slot as f64 + 0.15) + bar_width * index as f64;
+                    _ = write!(
+                        svg,
+                        r##"<rect x="{x:.1}" y="{:.1}" width="{bar_width:.1}" height="{:.1}" fill="#{color:06x}"/>"##,
+                        y(*value),
+                        bottom - y(*value)
+                    );
+                }
+            },
+            ChartKind::Line => {
+                let points: Vec<(f64, f64)> = series
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, value)| (MARGIN_LEFT + band * (slot as f64 + 0.5), y(*value)))
+                    .collect();
+                let path: Vec<String> =
+                    points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+                _ = write!(
+                    svg,
+                    r##"<polyline points="{}" fill="none" stroke="#{color:06x}" stroke-width="2"/>"##,
+                    path.join(" ")
+                );
+                for (x, y) in points {
+                    _ = write!(
+                        svg,
+                        r##"<circle cx="{x:.1}" cy="{y:.1}" r="3" fill="#{color:06x}"/>"##
+                    );
+                }
+            },
+        }
+    }
+
+    // 图例
+    let mut x = MARGIN_LEFT;
+    for (index, series) in chart.series.iter().enumerate() {
+        let color = series_color(index);
+        let legend_y = HEIGHT - 20.0;
+        _ = write!(
+            svg,
+            r##"<rect x="{x:.1}" y="{:.1}" width="12" height="12" fill="#{color:06x}"/>"##,
+            legend_y - 10.0
+        );
+        _ = write!(
+            svg,
+            r##"<text x="{:.1}" y="{legend_y:.1}" fill="#374151">{}</text>"##,
+            x + 18.0,
+            escape(&series.name)
+        );
+        x += 18.0 + series.name.chars().count() as f64 * 12.0 + 24.0;
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// The RGB color of the `index`th series, shared with the on-screen charts.
+pub fn series_color(index: usize) -> u32 {
+    CHART_COLORS[index % CHART_COLORS.len()]
+}
+
+/// Round the axis maximum up so the grid step is 1, 2 or 5 times a power of
+/// ten, and the grid labels come out as round numbers.
+fn nice_max(max: f64) -> f64 {
+    if max <= 0.0 {
+        return GRID_LINES as f64;
+    }
+    let magnitude = 10f64.powf((max / GRID_LINES as f64).log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| step * GRID_LINES as f64 >= max)
+        .unwrap_or(10.0 * magnitude);
+    step * GRID_LINES as f64
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{}", value as i64) } else { format!("{:.1}", value) }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use resvg::usvg;
+
+    use super::*;
+    use crate::{ChartError, ChartTool};
+
+    fn chart(kind: ChartKind) -> Chart {
+        let labels = ["03-01", "03-02", "03-03"].map(str::to_string).into();
+        Chart::new("需求燃尽图 <本周> & 下周", kind, labels)
+            .series("剩余需求", vec![3.0, 2.0, 0.0])
+            .series("理想进度", vec![3.0, 1.5, 0.0])
+    }
+
+    #[test]
+    fn nice_max_rounds_the_grid_step() {
+        assert_eq!(nice_max(0.0), GRID_LINES as f64);
+        assert_eq!(nice_max(-3.0), GRID_LINES as f64);
+        // Exact powers of ten keep a tight axis.
+        assert_eq!(nice_max(1.0), 2.0);
+        assert_eq!(nice_max(10.0), 20.0);
+        assert_eq!(nice_max(100.0), 200.0);
+        // Already a round grid.
+        assert_eq!(nice_max(4.0), 4.0);
+        assert_eq!(nice_max(40.0), 40.0);
+        assert_eq!(nice_max(8.0), 8.0);
+        assert_eq!(nice_max(41.0), 80.0);
+        assert_eq!(nice_max(3.0), 4.0);
+        for max in [0.3, 1.0, 7.0, 13.0, 99.0, 101.0, 12345.0] {
+            let nice = nice_max(max);
+            assert!(nice >= max && nice <= max * 8.0, "{max} → {nice}");
+        }
+    }
+
+    #[test]
+    fn rendered_svg_parses() {
+        for kind in [ChartKind::Line, ChartKind::Bar] {
+            let svg = render_svg(&chart(kind));
+            let tree = usvg::Tree::from_str(&svg, &usvg::Options::default()).unwrap();
+            assert_eq!((tree.size().width(), tree.size().height()), (WIDTH as f32, HEIGHT as f32));
+        }
+        let empty = Chart::new("空图表", ChartKind::Bar, Vec::new());
+        usvg::Tree::from_str(&render_svg(&empty), &usvg::Options::default()).unwrap();
+    }
+
+    #[test]
+    fn export_writes_png_and_svg() {
+        let dir = std::env::temp_dir().join(format!("tasks-mine-chart-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        ChartTool::export(&chart(ChartKind::Line), dir.join("burndown.png")).unwrap();
+        let png = std::fs::read(dir.join("burndown.png")).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        ChartTool::export(&chart(ChartKind::Bar), dir.join("burndown.svg")).unwrap();
+        let svg = std::fs::read_to_string(dir.join("burndown.svg")).unwrap();
+        assert!(svg.contains("需求燃尽图 &lt;本周&gt; &amp; 下周"));
+
+        let err = ChartTool::export(&chart(ChartKind::Bar), dir.join("burndown.gif")).unwrap_err();
+        assert!(matches!(err, ChartError::UnsupportedFormat(ext) if ext == "gif"));
+    }
+}
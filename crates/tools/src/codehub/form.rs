@@ -0,0 +1,220 @@
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::{DraftCheck, FormError, MergeRequest, MrStatus, TransitionError, status_choices};
+
+/// The editable fields of an MR, as entered in the MR form. Line counts are
+/// kept as typed so they can be reported when they don't parse.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MrDraft {
+    /// Left empty for a new MR to get a local id.
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub repository: String,
+    pub add_lines: String,
+    pub del_lines: String,
+    pub status: Option<MrStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MrFormError {
+    #[error("请填写{0}")]
+    Required(&'static str),
+    #[error("{0}应为非负整数")]
+    InvalidNumber(&'static str),
+    #[error("MR {0} 已存在")]
+    DuplicateId(String),
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+}
+
+impl FormError for MrFormError {
+    fn required(label: &'static str) -> Self {
+        Self::Required(label)
+    }
+
+    fn duplicate_id(id: String) -> Self {
+        Self::DuplicateId(id)
+    }
+
+    fn field(&self) -> &'static str {
+        match self {
+            Self::Required(field) | Self::InvalidNumber(field) => field,
+            Self::DuplicateId(_) => "ID",
+            Self::Transition(_) => "状态",
+        }
+    }
+}
+
+impl MrDraft {
+    /// A blank draft for a new MR, which always starts open.
+    pub fn new() -> Self {
+        Self {
+            status: Some(MrStatus::Open),
+            add_lines: "0".into(),
+            del_lines: "0".into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn edit(mr: &MergeRequest) -> Self {
+        Self {
+            id: mr.id.clone(),
+            title: mr.title.clone(),
+            author: mr.author.clone(),
+            repository: mr.repository.clone(),
+            add_lines: mr.add_lines.to_string(),
+            del_lines: mr.del_lines.to_string(),
+            status: Some(mr.status),
+        }
+    }
+
+    /// The statuses the form offers: only "open" for a new MR, the current
+    /// status and its legal successors for an existing one.
+    pub fn statuses(existing: Option<&MergeRequest>) -> Vec<MrStatus> {
+        status_choices(existing.map(|mr| mr.status), MrStatus::Open)
+    }
+
+    /// Check the draft and build the MR to save, like
+    /// [`IssueDraft::validate`](crate::IssueDraft::validate).
+    pub fn validate(
+        &self,
+        existing: Option<&MergeRequest>,
+        id_taken: impl Fn(&str) -> bool,
+    ) -> Result<MergeRequest, Vec<MrFormError>> {
+        let mut check = DraftCheck::new();
+        let title = check.text("标题", &self.title);
+        let author = check.text("作者", &self.author);
+        let mut lines = |field: &'static str, value: &str| match value.trim() {
+            "" => 0,
+            value => value.parse::<i32>().ok().filter(|lines| *lines >= 0).unwrap_or_else(|| {
+                check.push(MrFormError::InvalidNumber(field));
+                0
+            }),
+        };
+        let add_lines = lines("新增行数", &self.add_lines);
+        let del_lines = lines("删除行数", &self.del_lines);
+        check.required("状态", self.status);
+
+        let now = Utc::now();
+        let mut mr = match existing {
+            Some(mr) => mr.clone(),
+            None => MergeRequest {
+                id: check.new_id(&self.id, id_taken),
+                title: String::new(),
+                author: String::new(),
+                created_at: now,
+                add_lines: 0,
+                del_lines: 0,
+                status: MrStatus::Open,
+                updated_at: None,
+                repository: String::new(),
+                merged_at: None,
+            },
+        };
+        if let Some(status) = self.status
+            && let Err(err) = mr.transition(status)
+        {
+            check.push(err);
+        }
+        check.finish()?;
+
+        mr.title = title.to_string();
+        mr.author = author.to_string();
+        mr.repository = self.repository.trim().to_string();
+        mr.add_lines = add_lines;
+        mr.del_lines = del_lines;
+        mr.updated_at = Some(now);
+        Ok(mr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LOCAL_ID_PREFIX;
+
+    fn draft(id: &str) -> MrDraft {
+        MrDraft {
+            id: id.to_string(),
+            title: "修复登录白屏".to_string(),
+            author: "张三".to_string(),
+            repository: " web ".to_string(),
+            add_lines: "120".to_string(),
+            del_lines: " 30 ".to_string(),
+            ..MrDraft::new()
+        }
+    }
+
+    #[test]
+    fn missing_fields_are_all_reported() {
+        let draft =
+            MrDraft { title: String::new(), author: " ".to_string(), status: None, ..draft("") };
+        let errors = draft.validate(None, |_| false).unwrap_err();
+        assert_eq!(errors, [
+            MrFormError::Required("标题"),
+            MrFormError::Required("作者"),
+            MrFormError::Required("状态"),
+        ]);
+    }
+
+    #[test]
+    fn line_counts_must_be_non_negative_integers() {
+        let invalid =
+            MrDraft { add_lines: "-1".to_string(), del_lines: "十".to_string(), ..draft("") };
+        let errors = invalid.validate(None, |_| false).unwrap_err();
+        assert_eq!(errors, [
+            MrFormError::InvalidNumber("新增行数"),
+            MrFormError::InvalidNumber("删除行数"),
+        ]);
+        assert_eq!(errors[0].field(), "新增行数");
+
+        let blank = MrDraft { add_lines: " ".to_string(), ..draft("MR-1") };
+        let mr = blank.validate(None, |_| false).unwrap();
+        assert_eq!((mr.add_lines, mr.del_lines), (0, 30));
+        assert_eq!(mr.repository, "web");
+        assert_eq!(mr.status, MrStatus::Open);
+    }
+
+    #[test]
+    fn statuses_offer_only_legal_successors() {
+        assert_eq!(MrDraft::statuses(None), [MrStatus::Open]);
+
+        let mr = draft("MR-1").validate(None, |_| false).unwrap();
+        assert_eq!(
+            MrDraft::statuses(Some(&mr)),
+            [MrStatus::Open, MrStatus::Merged, MrStatus::Closed]
+        );
+
+        let merging = MrDraft { status: Some(MrStatus::Merged), ..MrDraft::edit(&mr) };
+        let merged = merging.validate(Some(&mr), |_| true).unwrap();
+        assert_eq!(merged.status, MrStatus::Merged);
+        assert!(merged.merged_at.is_some());
+
+        let reopening = MrDraft { status: Some(MrStatus::Open), ..MrDraft::edit(&merged) };
+        let errors = reopening.validate(Some(&merged), |_| true).unwrap_err();
+        assert!(matches!(errors[..], [MrFormError::Transition(_)]), "{:?}", errors);
+        assert_eq!(errors[0].field(), "状态");
+    }
+
+    #[test]
+    fn new_mr_without_an_id_gets_a_local_one() {
+        let mr = draft(" ").validate(None, |_| false).unwrap();
+        assert!(mr.id.starts_with(LOCAL_ID_PREFIX), "{}", mr.id);
+    }
+
+    #[test]
+    fn taken_id_is_refused_for_a_new_mr_only() {
+        let errors = draft("MR-1").validate(None, |id| id == "MR-1").unwrap_err();
+        assert_eq!(errors, [MrFormError::DuplicateId("MR-1".to_string())]);
+        assert_eq!(errors[0].field(), "ID");
+
+        let existing = draft("MR-1").validate(None, |_| false).unwrap();
+        let edited =
+            MrDraft { title: "修复登录页白屏".to_string(), ..MrDraft::edit(&existing) };
+        let mr = edited.validate(Some(&existing), |id| id == "MR-1").unwrap();
+        assert_eq!((mr.id.as_str(), mr.title.as_str()), ("MR-1", "修复登录页白屏"));
+        assert_eq!(mr.created_at, existing.created_at);
+    }
+}
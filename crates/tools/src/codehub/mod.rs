@@ -1,2 +1,6 @@
+mod form;
 mod model;
+mod workload;
+pub use form::*;
 pub use model::*;
+pub use workload::*;
@@ -4,15 +4,83 @@ impl CodeHubTool {
         Self {}
     }
 }
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{Coded, Status, TransitionError, impl_coded};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeRequest {
     pub id: String,
     pub title: String,
     pub author: String,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
     pub add_lines: i32,
     pub del_lines: i32,
-    pub status: String,
+    pub status: MrStatus,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub repository: String,
+    #[serde(default)]
+    pub merged_at: Option<DateTime<Utc>>,
+}
+
+impl MergeRequest {
+    /// Move the MR to `next`, stamping `merged_at` when it is merged and
+    /// clearing it again if a closed MR is reopened.
+    pub fn transition(&mut self, next: MrStatus) -> Result<(), TransitionError> {
+        self.status = self.status.transition_to(next)?;
+        self.updated_at = Some(Utc::now());
+        match next {
+            MrStatus::Merged => {
+                self.merged_at.get_or_insert_with(Utc::now);
+            },
+            MrStatus::Open => self.merged_at = None,
+            MrStatus::Closed => {},
+        }
+        Ok(())
+    }
+
+    /// Time from creation to merge, for merged MRs.
+    pub fn time_to_merge(&self) -> Option<Duration> {
+        let merged_at = self.merged_at.filter(|_| self.status == MrStatus::Merged)?;
+        Some((merged_at - self.created_at).max(Duration::zero()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MrStatus {
+    Open,
+    Merged,
+    Closed,
+}
+
+impl Coded for MrStatus {
+    const ALL: &'static [Self] = &[Self::Open, Self::Merged, Self::Closed];
+    const KIND: &'static str = "MR 状态";
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Merged => "merged",
+            Self::Closed => "closed",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Open => "待合入",
+            Self::Merged => "已合入",
+            Self::Closed => "已关闭",
+        }
+    }
+}
+
+impl Status for MrStatus {
+    fn can_transition_to(&self, next: Self) -> bool {
+        use MrStatus::*;
+        matches!((self, next), (Open, Merged | Closed) | (Closed, Open))
+    }
 }
+impl_coded!(MrStatus);
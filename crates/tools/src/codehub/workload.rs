@@ -0,0 +1,217 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use chrono::{Duration, Local, NaiveDate};
+
+use crate::{
+    Chart, ChartKind, CodeHubTool, MergeRequest, MrStatus, week_label, week_start, week_starts,
+};
+
+/// MR throughput of one group (an author, a repository, a week, or all of
+/// them) within a period.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workload {
+    pub mr_count: usize,
+    pub add_lines: i64,
+    pub del_lines: i64,
+    pub merged: usize,
+    pub open: usize,
+    /// Median creation-to-merge time of the merged MRs, if any were merged.
+    pub median_time_to_merge: Option<Duration>,
+}
+
+impl Workload {
+    pub fn new(mrs: &[&MergeRequest]) -> Self {
+        let mut merge_times: Vec<Duration> =
+            mrs.iter().filter_map(|mr| mr.time_to_merge()).collect();
+        merge_times.sort();
+        let median_time_to_merge = match merge_times.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(merge_times[len / 2]),
+            len => Some((merge_times[len / 2 - 1] + merge_times[len / 2]) / 2),
+        };
+
+        Self {
+            mr_count: mrs.len(),
+            add_lines: mrs.iter().map(|mr| mr.add_lines as i64).sum(),
+            del_lines: mrs.iter().map(|mr| mr.del_lines as i64).sum(),
+            merged: mrs.iter().filter(|mr| mr.status == MrStatus::Merged).count(),
+            open: mrs.iter().filter(|mr| mr.status == MrStatus::Open).count(),
+            median_time_to_merge,
+        }
+    }
+}
+
+/// Workload of the MRs created in `start..=end` (local dates), broken down
+/// per author, per repository and per week.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkloadReport {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub total: Workload,
+    /// Sorted by MR count, busiest first.
+    pub by_author: Vec<(String, Workload)>,
+    /// Sorted by MR count, busiest first. MRs without a repository are
+    /// grouped under an empty name.
+    pub by_repository: Vec<(String, Workload)>,
+    /// Keyed by the Monday starting each week. Every week overlapping the
+    /// period is listed, including weeks without MRs.
+    pub by_week: Vec<(NaiveDate, Workload)>,
+}
+
+impl WorkloadReport {
+    /// Lines added and deleted per week, as a bar chart.
+    pub fn lines_chart(&self) -> Chart {
+        let labels = self.by_week.iter().map(|(week, _)| week_label(*week)).collect();
+        let column = |value: fn(&Workload) -> i64| {
+            self.by_week.iter().map(|(_, workload)| value(workload) as f64).collect()
+        };
+        Chart::new("MR 代码行数（按周）", ChartKind::Bar, labels)
+            .series("新增行数", column(|workload| workload.add_lines))
+            .series("删除行数", column(|workload| workload.del_lines))
+    }
+}
+
+impl CodeHubTool {
+    pub fn workload(mrs: &[&MergeRequest], start: NaiveDate, end: NaiveDate) -> WorkloadReport {
+        let in_period: Vec<&MergeRequest> = mrs
+            .iter()
+            .copied()
+            .filter(|mr| {
+                let date = mr.created_at.with_timezone(&Local).date_naive();
+                date >= start && date <= end
+            })
+            .collect();
+
+        let mut weeks: BTreeMap<NaiveDate, Vec<&MergeRequest>> =
+            week_starts(start, end).into_iter().map(|week| (week, Vec::new())).collect();
+        for mr in &in_period {
+            let date = mr.created_at.with_timezone(&Local).date_naive();
+            weeks.entry(week_start(date)).or_default().push(mr);
+        }
+
+        WorkloadReport {
+            start,
+            end,
+            total: Workload::new(&in_period),
+            by_author: group_by(&in_period, |mr| mr.author.clone()),
+            by_repository: group_by(&in_period, |mr| mr.repository.clone()),
+            by_week: weeks.into_iter().map(|(week, mrs)| (week, Workload::new(&mrs))).collect(),
+        }
+    }
+}
+
+fn group_by(
+    mrs: &[&MergeRequest],
+    key: impl Fn(&MergeRequest) -> String,
+) -> Vec<(String, Workload)> {
+    let mut groups: BTreeMap<String, Vec<&MergeRequest>> = BTreeMap::new();
+    for mr in mrs {
+        groups.entry(key(mr)).or_default().push(mr);
+    }
+    let mut workloads: Vec<(String, Workload)> =
+        groups.into_iter().map(|(key, mrs)| (key, Workload::new(&mrs))).collect();
+    workloads.sort_by_key(|(_, workload)| Reverse(workload.mr_count));
+    workloads
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone as _, Utc};
+
+    use super::*;
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Local.with_ymd_and_hms(2026, month, day, hour, minute, 0).unwrap().to_utc()
+    }
+
+    fn mr(
+        id: &str,
+        author: &str,
+        repository: &str,
+        created_at: DateTime<Utc>,
+        merged_after_hours: Option<i64>,
+    ) -> MergeRequest {
+        MergeRequest {
+            id: id.to_string(),
+            title: id.to_string(),
+            author: author.to_string(),
+            created_at,
+            add_lines: 10,
+            del_lines: 2,
+            status: if merged_after_hours.is_some() { MrStatus::Merged } else { MrStatus::Open },
+            updated_at: None,
+            repository: repository.to_string(),
+            merged_at: merged_after_hours.map(|hours| created_at + Duration::hours(hours)),
+        }
+    }
+
+    fn merged_after(hours: &[Option<i64>]) -> Workload {
+        let mrs: Vec<MergeRequest> = hours
+            .iter()
+            .enumerate()
+            .map(|(ix, hours)| mr(&format!("MR-{}", ix), "alice", "core", at(3, 2, 9, 0), *hours))
+            .collect();
+        Workload::new(&mrs.iter().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn median_of_an_odd_count_is_the_middle_merge_time() {
+        let workload = merged_after(&[Some(10), None, Some(1), Some(3)]);
+        assert_eq!(workload.median_time_to_merge, Some(Duration::hours(3)));
+        assert_eq!((workload.mr_count, workload.merged, workload.open), (4, 3, 1));
+        assert_eq!((workload.add_lines, workload.del_lines), (40, 8));
+    }
+
+    #[test]
+    fn median_of_an_even_count_averages_the_middle_two() {
+        let workload = merged_after(&[Some(8), Some(1), None, Some(2), Some(30)]);
+        assert_eq!(workload.median_time_to_merge, Some(Duration::hours(5)));
+    }
+
+    #[test]
+    fn unmerged_mrs_have_no_median() {
+        let mut closed = mr("MR-9", "alice", "core", at(3, 2, 9, 0), Some(4));
+        closed.status = MrStatus::Closed;
+        let open = mr("MR-10", "alice", "core", at(3, 2, 9, 0), None);
+        let workload = Workload::new(&[&closed, &open]);
+        assert_eq!(workload.median_time_to_merge, None);
+        assert_eq!((workload.merged, workload.open), (0, 1));
+        assert_eq!(Workload::new(&[]), Workload::default());
+    }
+
+    #[test]
+    fn workload_buckets_the_period_by_author_repository_and_week() {
+        // 2026-03-02 and every seventh day after it is a Monday.
+        let mrs = [
+            mr("before", "carol", "ui", at(3, 3, 23, 59), Some(1)),
+            mr("first-day", "alice", "core", at(3, 4, 0, 0), Some(2)),
+            mr("second-week", "alice", "core", at(3, 10, 12, 0), Some(6)),
+            mr("no-repo", "bob", "", at(3, 11, 12, 0), None),
+            mr("last-day", "alice", "ui", at(3, 23, 23, 59), None),
+            mr("after", "carol", "ui", at(3, 24, 0, 0), None),
+        ];
+        let mrs: Vec<&MergeRequest> = mrs.iter().collect();
+        let report = CodeHubTool::workload(
+            &mrs,
+            NaiveDate::from_ymd_opt(2026, 3, 4).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 23).unwrap(),
+        );
+
+        assert_eq!(report.total.mr_count, 4);
+        assert_eq!(report.total.median_time_to_merge, Some(Duration::hours(4)));
+
+        let counts = |groups: &[(String, Workload)]| -> Vec<(String, usize)> {
+            groups.iter().map(|(key, workload)| (key.clone(), workload.mr_count)).collect()
+        };
+        assert_eq!(counts(&report.by_author), [("alice".to_string(), 3), ("bob".to_string(), 1)]);
+        assert_eq!(
+            counts(&report.by_repository),
+            [("core".to_string(), 2), (String::new(), 1), ("ui".to_string(), 1),]
+        );
+
+        let weeks: Vec<(NaiveDate, usize)> =
+            report.by_week.iter().map(|(week, workload)| (*week, workload.mr_count)).collect();
+        let monday = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        assert_eq!(weeks, [(monday(2), 1), (monday(9), 2), (monday(16), 0), (monday(23), 1)]);
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Issue, IssueStatus, Record as _, Severity};
+
+/// Criteria combined with AND; an empty criterion matches every issue.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IssueFilter {
+    pub statuses: Vec<IssueStatus>,
+    pub severities: Vec<Severity>,
+    /// Case-insensitive substring of the assignee.
+    pub assignee: String,
+    /// Inclusive local date range of the submission.
+    pub created: Option<(NaiveDate, NaiveDate)>,
+    /// Inclusive local date range of the resolve time; unresolved issues
+    /// never match.
+    pub resolved: Option<(NaiveDate, NaiveDate)>,
+    /// Free text matched like the search box, against id, title, assignee
+    /// and status.
+    pub text: String,
+}
+
+impl IssueFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches(&self, issue: &Issue) -> bool {
+        let in_range = |date: DateTime<Utc>, (start, end): (NaiveDate, NaiveDate)| {
+            let date = date.with_timezone(&Local).date_naive();
+            date >= start && date <= end
+        };
+
+        (self.statuses.is_empty() || self.statuses.contains(&issue.status))
+            && (self.severities.is_empty() || self.severities.contains(&issue.severity))
+            && issue.assignee.to_lowercase().contains(&self.assignee.trim().to_lowercase())
+            && self.created.is_none_or(|range| in_range(issue.created_at, range))
+            && self
+                .resolved
+                .is_none_or(|range| issue.resolved_at.is_some_and(|date| in_range(date, range)))
+            && issue.matches(&self.text)
+    }
+
+    /// Add `value` to `values` if missing, remove it otherwise.
+    pub fn toggle<T: PartialEq>(values: &mut Vec<T>, value: T) {
+        match values.iter().position(|existing| *existing == value) {
+            Some(index) => {
+                values.remove(index);
+            },
+            None => values.push(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone as _;
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    /// Noon local time, so the local date is `day` in every time zone.
+    fn noon(day: u32) -> DateTime<Utc> {
+        Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap().to_utc()
+    }
+
+    fn issue() -> Issue {
+        Issue {
+            id: "DTS-1".to_string(),
+            title: "登录页面白屏".to_string(),
+            severity: Severity::Serious,
+            status: IssueStatus::Closed,
+            created_at: noon(5),
+            resolved_at: Some(noon(10)),
+            assignee: "Zhang San".to_string(),
+            updated_at: None,
+            reopen_count: 0,
+        }
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert!(IssueFilter::default().is_empty());
+        assert!(IssueFilter::default().matches(&issue()));
+    }
+
+    #[test]
+    fn statuses_and_severities_match_any_listed() {
+        let filter = IssueFilter {
+            statuses: vec![IssueStatus::Submitted, IssueStatus::Closed],
+            ..Default::default()
+        };
+        assert!(filter.matches(&issue()));
+        let filter = IssueFilter { statuses: vec![IssueStatus::Fixing], ..Default::default() };
+        assert!(!filter.matches(&issue()));
+
+        let filter = IssueFilter { severities: vec![Severity::Serious], ..Default::default() };
+        assert!(filter.matches(&issue()));
+        let filter = IssueFilter { severities: vec![Severity::Fatal], ..Default::default() };
+        assert!(!filter.matches(&issue()));
+    }
+
+    #[test]
+    fn assignee_is_a_case_insensitive_substring() {
+        for assignee in ["zhang", " SAN ", "Zhang San"] {
+            let filter = IssueFilter { assignee: assignee.to_string(), ..Default::default() };
+            assert!(filter.matches(&issue()), "{assignee}");
+        }
+        let filter = IssueFilter { assignee: "li".to_string(), ..Default::default() };
+        assert!(!filter.matches(&issue()));
+    }
+
+    #[test]
+    fn date_ranges_are_inclusive_local_days() {
+        let created = |range| IssueFilter { created: Some(range), ..Default::default() };
+        assert!(created((day(5), day(5))).matches(&issue()));
+        assert!(created((day(1), day(5))).matches(&issue()));
+        assert!(!created((day(6), day(9))).matches(&issue()));
+
+        let resolved = |range| IssueFilter { resolved: Some(range), ..Default::default() };
+        assert!(resolved((day(10), day(12))).matches(&issue()));
+        assert!(!resolved((day(1), day(9))).matches(&issue()));
+        let unresolved = Issue { resolved_at: None, ..issue() };
+        assert!(!resolved((day(1), day(31))).matches(&unresolved));
+    }
+
+    #[test]
+    fn text_matches_like_the_search_box() {
+        for text in ["dts-1", "白屏", "关闭"] {
+            let filter = IssueFilter { text: text.to_string(), ..Default::default() };
+            assert!(filter.matches(&issue()), "{text}");
+        }
+        let filter = IssueFilter { text: "超时".to_string(), ..Default::default() };
+        assert!(!filter.matches(&issue()));
+    }
+
+    #[test]
+    fn criteria_are_combined_with_and() {
+        let filter = IssueFilter {
+            statuses: vec![IssueStatus::Closed],
+            assignee: "li".to_string(),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&issue()));
+    }
+
+    #[test]
+    fn toggle_adds_and_removes() {
+        let mut statuses = vec![IssueStatus::Submitted];
+        IssueFilter::toggle(&mut statuses, IssueStatus::Fixing);
+        assert_eq!(statuses, [IssueStatus::Submitted, IssueStatus::Fixing]);
+        IssueFilter::toggle(&mut statuses, IssueStatus::Submitted);
+        assert_eq!(statuses, [IssueStatus::Fixing]);
+    }
+}
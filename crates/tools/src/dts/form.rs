@@ -0,0 +1,202 @@
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::{DraftCheck, FormError, Issue, IssueStatus, Severity, TransitionError, status_choices};
+
+/// The editable fields of an issue, as entered in the issue form.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssueDraft {
+    /// Left empty for a new issue to get a local id.
+    pub id: String,
+    pub title: String,
+    pub severity: Option<Severity>,
+    pub status: Option<IssueStatus>,
+    pub assignee: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IssueFormError {
+    #[error("请填写{0}")]
+    Required(&'static str),
+    #[error("问题单 {0} 已存在")]
+    DuplicateId(String),
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+}
+
+impl FormError for IssueFormError {
+    fn required(label: &'static str) -> Self {
+        Self::Required(label)
+    }
+
+    fn duplicate_id(id: String) -> Self {
+        Self::DuplicateId(id)
+    }
+
+    fn field(&self) -> &'static str {
+        match self {
+            Self::Required(field) => field,
+            Self::DuplicateId(_) => "ID",
+            Self::Transition(_) => "状态",
+        }
+    }
+}
+
+impl IssueDraft {
+    /// A blank draft for a new issue, which always starts as submitted.
+    pub fn new() -> Self {
+        Self { status: Some(IssueStatus::Submitted), ..Default::default() }
+    }
+
+    pub fn edit(issue: &Issue) -> Self {
+        Self {
+            id: issue.id.clone(),
+            title: issue.title.clone(),
+            severity: Some(issue.severity),
+            status: Some(issue.status),
+            assignee: issue.assignee.clone(),
+        }
+    }
+
+    /// The statuses the form offers: only "submitted" for a new issue, the
+    /// current status and its legal successors for an existing one.
+    pub fn statuses(existing: Option<&Issue>) -> Vec<IssueStatus> {
+        status_choices(existing.map(|issue| issue.status), IssueStatus::Submitted)
+    }
+
+    /// Check the draft and build the issue to save. `existing` is the issue
+    /// being edited, `None` when creating, and `id_taken` tells whether a new
+    /// id is already used. Every problem found is returned, not just the
+    /// first.
+    pub fn validate(
+        &self,
+        existing: Option<&Issue>,
+        id_taken: impl Fn(&str) -> bool,
+    ) -> Result<Issue, Vec<IssueFormError>> {
+        let mut check = DraftCheck::new();
+        let title = check.text("标题", &self.title);
+        check.required("严重程度", self.severity);
+        check.required("状态", self.status);
+        let assignee = check.text("责任人", &self.assignee);
+
+        let now = Utc::now();
+        let mut issue = match existing {
+            Some(issue) => issue.clone(),
+            None => Issue {
+                id: check.new_id(&self.id, id_taken),
+                title: String::new(),
+                severity: Severity::Normal,
+                status: IssueStatus::Submitted,
+                created_at: now,
+                resolved_at: None,
+                assignee: String::new(),
+                updated_at: None,
+                reopen_count: 0,
+            },
+        };
+        if let Some(status) = self.status
+            && let Err(err) = issue.transition(status)
+        {
+            check.push(err);
+        }
+        check.finish()?;
+
+        issue.title = title.to_string();
+        issue.assignee = assignee.to_string();
+        issue.severity = self.severity.unwrap_or(issue.severity);
+        issue.updated_at = Some(now);
+        Ok(issue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LOCAL_ID_PREFIX;
+
+    fn draft(id: &str) -> IssueDraft {
+        IssueDraft {
+            id: id.to_string(),
+            title: "登录页面白屏".to_string(),
+            severity: Some(Severity::Serious),
+            assignee: "张三".to_string(),
+            ..IssueDraft::new()
+        }
+    }
+
+    #[test]
+    fn missing_fields_are_all_reported() {
+        let draft =
+            IssueDraft { title: " ".to_string(), severity: None, status: None, ..draft("") };
+        let errors = draft.validate(None, |_| false).unwrap_err();
+        assert_eq!(errors, [
+            IssueFormError::Required("标题"),
+            IssueFormError::Required("严重程度"),
+            IssueFormError::Required("状态"),
+        ]);
+        assert_eq!(errors[1].field(), "严重程度");
+    }
+
+    #[test]
+    fn fields_are_trimmed_and_the_severity_kept() {
+        let draft = IssueDraft {
+            title: " 登录页面白屏 ".to_string(),
+            severity: Some(Severity::Fatal),
+            assignee: " 张三 ".to_string(),
+            ..draft("DTS-1")
+        };
+        let issue = draft.validate(None, |_| false).unwrap();
+        assert_eq!((issue.title.as_str(), issue.assignee.as_str()), ("登录页面白屏", "张三"));
+        assert_eq!((issue.severity, issue.status), (Severity::Fatal, IssueStatus::Submitted));
+        assert_eq!(issue.updated_at, Some(issue.created_at));
+    }
+
+    #[test]
+    fn statuses_offer_only_legal_successors() {
+        assert_eq!(IssueDraft::statuses(None), [IssueStatus::Submitted]);
+
+        let issue = draft("DTS-1").validate(None, |_| false).unwrap();
+        assert_eq!(IssueDraft::statuses(Some(&issue)), [
+            IssueStatus::Submitted,
+            IssueStatus::Fixing,
+            IssueStatus::Regression,
+            IssueStatus::Suspended,
+        ]);
+    }
+
+    #[test]
+    fn illegal_status_change_is_refused() {
+        let issue = draft("DTS-1").validate(None, |_| false).unwrap();
+        let closing = IssueDraft { status: Some(IssueStatus::Closed), ..IssueDraft::edit(&issue) };
+        let errors = closing.validate(Some(&issue), |_| true).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], IssueFormError::Transition(_)), "{:?}", errors);
+        assert_eq!(errors[0].field(), "状态");
+
+        let fixing = IssueDraft { status: Some(IssueStatus::Fixing), ..IssueDraft::edit(&issue) };
+        let fixed = fixing.validate(Some(&issue), |_| true).unwrap();
+        assert_eq!(fixed.status, IssueStatus::Fixing);
+        assert_eq!(fixed.created_at, issue.created_at);
+    }
+
+    #[test]
+    fn new_issue_without_an_id_gets_a_local_one() {
+        let issue = draft("  ").validate(None, |_| false).unwrap();
+        assert!(issue.id.starts_with(LOCAL_ID_PREFIX), "{}", issue.id);
+    }
+
+    #[test]
+    fn taken_id_is_refused_for_a_new_issue_only() {
+        let errors = draft("DTS-1").validate(None, |id| id == "DTS-1").unwrap_err();
+        assert_eq!(errors, [IssueFormError::DuplicateId("DTS-1".to_string())]);
+        assert_eq!(errors[0].field(), "ID");
+
+        let existing = draft("DTS-1").validate(None, |_| false).unwrap();
+        let edited = IssueDraft {
+            title: "登录页面偶现白屏".to_string(),
+            ..IssueDraft::edit(&existing)
+        };
+        let issue = edited.validate(Some(&existing), |id| id == "DTS-1").unwrap();
+        assert_eq!((issue.id.as_str(), issue.title.as_str()), ("DTS-1", "登录页面偶现白屏"));
+    }
+}
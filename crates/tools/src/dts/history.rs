@@ -0,0 +1,443 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+
+use crate::{Issue, IssueStatus, MergeRequest, Requirement, Store, Transaction};
+
+/// Actor recorded for status changes pulled in by a sync.
+pub const SYNC_ACTOR: &str = "同步";
+
+/// Actor recorded for status changes made by an Excel import.
+pub const IMPORT_ACTOR: &str = "导入";
+
+/// One status change of an issue. `from` is `None` for the status the issue
+/// was first seen with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueEvent {
+    pub issue_id: String,
+    pub from: Option<IssueStatus>,
+    pub to: IssueStatus,
+    pub actor: String,
+    pub at: DateTime<Utc>,
+}
+
+/// A local note on an issue. Comments are never pushed to DTS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueComment {
+    pub id: i64,
+    pub issue_id: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    MergeRequest,
+    Requirement,
+}
+
+impl LinkKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MergeRequest => "merge_request",
+            Self::Requirement => "requirement",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MergeRequest => "MR",
+            Self::Requirement => "需求",
+        }
+    }
+}
+
+/// A record linked to an issue. `record` is `None` when the linked id is not
+/// in the store (yet), and `manual` tells a link added by hand from one found
+/// by the issue id appearing in an MR title.
+#[derive(Debug, Clone)]
+pub struct Linked<T> {
+    pub id: String,
+    pub record: Option<T>,
+    pub manual: bool,
+}
+
+/// Everything the issue detail sheet shows.
+#[derive(Debug, Clone)]
+pub struct IssueDetail {
+    pub issue: Issue,
+    pub history: Vec<IssueEvent>,
+    pub comments: Vec<IssueComment>,
+    pub merge_requests: Vec<Linked<MergeRequest>>,
+    pub requirements: Vec<Linked<Requirement>>,
+}
+
+fn insert_event(conn: &Connection, event: &IssueEvent) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO issue_history (issue_id, from_status, to_status, actor, at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![event.issue_id, event.from, event.to, event.actor, event.at],
+    )?;
+    Ok(())
+}
+
+impl Issue {
+    /// Record the status a synced issue arrived with, or its change since
+    /// the local copy.
+    pub(crate) fn record_merge(&self, previous: Option<&Self>, tx: &Transaction) -> Result<()> {
+        self.record_change(previous, SYNC_ACTOR, tx)
+    }
+
+    /// Record the status `self` was written with over `previous`, `None` for
+    /// a new issue, as made by `actor`.
+    pub(crate) fn record_change(
+        &self,
+        previous: Option<&Self>,
+        actor: &str,
+        tx: &Transaction,
+    ) -> Result<()> {
+        let from = previous.map(|previous| previous.status);
+        if from == Some(self.status) {
+            return Ok(());
+        }
+        let at = match previous {
+            None => self.created_at,
+            Some(_) => self.updated_at.unwrap_or_else(Utc::now),
+        };
+        insert_event(tx.conn(), &IssueEvent {
+            issue_id: self.id.clone(),
+            from,
+            to: self.status,
+            actor: actor.to_string(),
+            at,
+        })?;
+        Ok(())
+    }
+}
+
+impl Store {
+    /// Save `issue` written over `previous`, `None` for a new issue, and
+    /// record its status change, if any, as made by `actor`.
+    pub fn save_issue(&self, issue: &Issue, previous: Option<&Issue>, actor: &str) -> Result<()> {
+        self.transaction(|tx| {
+            tx.upsert(issue)?;
+            issue.record_change(previous, actor, tx)
+        })
+    }
+
+    /// Status changes of an issue, oldest first.
+    pub fn issue_history(&self, issue_id: &str) -> Result<Vec<IssueEvent>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT from_status, to_status, actor, at FROM issue_history
+             WHERE issue_id = ?1 ORDER BY at, rowid",
+        )?;
+        let events = stmt
+            .query_map([issue_id], |row| {
+                Ok(IssueEvent {
+                    issue_id: issue_id.to_string(),
+                    from: row.get(0)?,
+                    to: row.get(1)?,
+                    actor: row.get(2)?,
+                    at: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    /// Comments on an issue, oldest first.
+    pub fn issue_comments(&self, issue_id: &str) -> Result<Vec<IssueComment>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, author, body, created_at FROM issue_comments
+             WHERE issue_id = ?1 ORDER BY created_at, id",
+        )?;
+        let comments = stmt
+            .query_map([issue_id], |row| {
+                Ok(IssueComment {
+                    id: row.get(0)?,
+                    issue_id: issue_id.to_string(),
+                    author: row.get(1)?,
+                    body: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(comments)
+    }
+
+    pub fn add_issue_comment(
+        &self,
+        issue_id: &str,
+        author: &str,
+        body: &str,
+    ) -> Result<IssueComment> {
+        let created_at = Utc::now();
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO issue_comments (issue_id, author, body, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![issue_id, author, body, created_at],
+        )?;
+        Ok(IssueComment {
+            id: conn.last_insert_rowid(),
+            issue_id: issue_id.to_string(),
+            author: author.to_string(),
+            body: body.to_string(),
+            created_at,
+        })
+    }
+
+    pub fn delete_issue_comment(&self, comment_id: i64) -> Result<bool> {
+        let deleted =
+            self.conn().execute("DELETE FROM issue_comments WHERE id = ?1", [comment_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Link `target_id` to the issue. Linking twice is a no-op.
+    pub fn link_issue(&self, issue_id: &str, kind: LinkKind, target_id: &str) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR IGNORE INTO issue_links (issue_id, kind, target_id) VALUES (?1, ?2, ?3)",
+            params![issue_id, kind.code(), target_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn unlink_issue(&self, issue_id: &str, kind: LinkKind, target_id: &str) -> Result<bool> {
+        let deleted = self.conn().execute(
+            "DELETE FROM issue_links WHERE issue_id = ?1 AND kind = ?2 AND target_id = ?3",
+            params![issue_id, kind.code(), target_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn issue_links(&self, issue_id: &str, kind: LinkKind) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT target_id FROM issue_links WHERE issue_id = ?1 AND kind = ?2 ORDER BY rowid",
+        )?;
+        let ids = stmt
+            .query_map(params![issue_id, kind.code()], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    /// The issue with its history, comments and linked records, `None` if
+    /// no issue has that id.
+    ///
+    /// Besides manual links, an MR whose title mentions the issue id counts
+    /// as linked, the usual convention for fix MRs.
+    pub fn issue_detail(&self, issue_id: &str) -> Result<Option<IssueDetail>> {
+        let Some(issue) = self.issues().get(issue_id)? else {
+            return Ok(None);
+        };
+
+        let mut merge_requests = self
+            .issue_links(issue_id, LinkKind::MergeRequest)?
+            .into_iter()
+            .map(|id| Ok(Linked { record: self.merge_requests().get(&id)?, id, manual: true }))
+            .collect::<Result<Vec<_>>>()?;
+        for mr in self.merge_requests().all()? {
+            if mentions(&mr.title, issue_id) && !merge_requests.iter().any(|link| link.id == mr.id)
+            {
+                merge_requests.push(Linked { id: mr.id.clone(), record: Some(mr), manual: false });
+            }
+        }
+
+        let requirements = self
+            .issue_links(issue_id, LinkKind::Requirement)?
+            .into_iter()
+            .map(|id| Ok(Linked { record: self.requirements().get(&id)?, id, manual: true }))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(IssueDetail {
+            history: self.issue_history(issue_id)?,
+            comments: self.issue_comments(issue_id)?,
+            merge_requests,
+            requirements,
+            issue,
+        }))
+    }
+}
+
+/// Whether `text` contains `id` as a whole token, so "DTS-1" is not found in
+/// "DTS-12" or "XDTS-1". Only ASCII letters and digits continue a token, as
+/// titles often put Chinese text right next to the id.
+fn mentions(text: &str, id: &str) -> bool {
+    if id.is_empty() {
+        return false;
+    }
+    text.match_indices(id).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + id.len()..].chars().next();
+        let continues = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+        !continues(before) && !continues(after)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone as _;
+
+    use super::*;
+    use crate::{MrStatus, Severity};
+
+    #[test]
+    fn mentions_matches_whole_ids_only() {
+        assert!(mentions("修复 DTS-1 白屏", "DTS-1"));
+        assert!(mentions("[DTS-1]修复白屏", "DTS-1"));
+        assert!(mentions("修复DTS-1白屏", "DTS-1"));
+        assert!(mentions("DTS-12, DTS-1", "DTS-1"));
+        assert!(!mentions("修复 DTS-12 白屏", "DTS-1"));
+        assert!(!mentions("XDTS-1", "DTS-1"));
+        assert!(!mentions("任意标题", ""));
+    }
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn issue(id: &str, status: IssueStatus) -> Issue {
+        Issue {
+            id: id.to_string(),
+            title: "登录页面白屏".to_string(),
+            severity: Severity::Serious,
+            status,
+            created_at: at(1, 9),
+            resolved_at: None,
+            assignee: "张三".to_string(),
+            updated_at: Some(at(1, 9)),
+            reopen_count: 0,
+        }
+    }
+
+    fn mr(id: &str, title: &str) -> MergeRequest {
+        MergeRequest {
+            id: id.to_string(),
+            title: title.to_string(),
+            author: "李四".to_string(),
+            created_at: at(2, 10),
+            add_lines: 10,
+            del_lines: 2,
+            status: MrStatus::Open,
+            updated_at: None,
+            repository: "web".to_string(),
+            merged_at: None,
+        }
+    }
+
+    #[test]
+    fn save_issue_records_creation_and_status_changes() {
+        let store = Store::open_in_memory().unwrap();
+        let mut created = issue("DTS-1", IssueStatus::Submitted);
+        // Edited after creation, the creation event still happens when it was created.
+        created.updated_at = Some(at(2, 9));
+        store.save_issue(&created, None, "张三").unwrap();
+
+        let mut fixing = created.clone();
+        fixing.transition(IssueStatus::Fixing).unwrap();
+        fixing.updated_at = Some(at(3, 9));
+        store.save_issue(&fixing, Some(&created), "李四").unwrap();
+
+        let mut renamed = fixing.clone();
+        renamed.title = "登录页面偶现白屏".to_string();
+        store.save_issue(&renamed, Some(&fixing), "李四").unwrap();
+
+        let history = store.issue_history("DTS-1").unwrap();
+        assert_eq!(history, [
+            IssueEvent {
+                issue_id: "DTS-1".to_string(),
+                from: None,
+                to: IssueStatus::Submitted,
+                actor: "张三".to_string(),
+                at: at(1, 9),
+            },
+            IssueEvent {
+                issue_id: "DTS-1".to_string(),
+                from: Some(IssueStatus::Submitted),
+                to: IssueStatus::Fixing,
+                actor: "李四".to_string(),
+                at: at(3, 9),
+            },
+        ]);
+        assert_eq!(store.issues().get("DTS-1").unwrap().unwrap().title, "登录页面偶现白屏");
+        assert!(store.issue_history("DTS-2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn comments_are_added_in_order_and_deleted() {
+        let store = Store::open_in_memory().unwrap();
+        store.save_issue(&issue("DTS-1", IssueStatus::Submitted), None, "张三").unwrap();
+
+        let first = store.add_issue_comment("DTS-1", "张三", "复现步骤见附件").unwrap();
+        let second = store.add_issue_comment("DTS-1", "李四", "已定位").unwrap();
+        assert_eq!(store.issue_comments("DTS-1").unwrap(), [first.clone(), second.clone()]);
+
+        assert!(store.delete_issue_comment(first.id).unwrap());
+        assert!(!store.delete_issue_comment(first.id).unwrap());
+        assert_eq!(store.issue_comments("DTS-1").unwrap(), [second]);
+    }
+
+    #[test]
+    fn links_are_added_once_and_removed() {
+        let store = Store::open_in_memory().unwrap();
+        store.save_issue(&issue("DTS-1", IssueStatus::Submitted), None, "张三").unwrap();
+
+        store.link_issue("DTS-1", LinkKind::Requirement, "REQ-1").unwrap();
+        store.link_issue("DTS-1", LinkKind::Requirement, "REQ-1").unwrap();
+        store.link_issue("DTS-1", LinkKind::MergeRequest, "MR-1").unwrap();
+        assert_eq!(store.issue_links("DTS-1", LinkKind::Requirement).unwrap(), ["REQ-1"]);
+        assert_eq!(store.issue_links("DTS-1", LinkKind::MergeRequest).unwrap(), ["MR-1"]);
+
+        assert!(store.unlink_issue("DTS-1", LinkKind::Requirement, "REQ-1").unwrap());
+        assert!(!store.unlink_issue("DTS-1", LinkKind::Requirement, "REQ-1").unwrap());
+        assert!(store.issue_links("DTS-1", LinkKind::Requirement).unwrap().is_empty());
+    }
+
+    #[test]
+    fn issue_detail_collects_manual_and_mentioned_links() {
+        let store = Store::open_in_memory().unwrap();
+        assert!(store.issue_detail("DTS-1").unwrap().is_none());
+
+        store.save_issue(&issue("DTS-1", IssueStatus::Submitted), None, "张三").unwrap();
+        store.add_issue_comment("DTS-1", "张三", "复现步骤见附件").unwrap();
+        store
+            .merge_requests()
+            .upsert_all(&[
+                mr("MR-1", "重构登录"),
+                mr("MR-2", "[DTS-1] 修复白屏"),
+                mr("MR-3", "修复 DTS-12"),
+            ])
+            .unwrap();
+        store.link_issue("DTS-1", LinkKind::MergeRequest, "MR-1").unwrap();
+        store.link_issue("DTS-1", LinkKind::MergeRequest, "MR-2").unwrap();
+        store.link_issue("DTS-1", LinkKind::MergeRequest, "MR-9").unwrap();
+        store.link_issue("DTS-1", LinkKind::Requirement, "REQ-1").unwrap();
+
+        let detail = store.issue_detail("DTS-1").unwrap().unwrap();
+        assert_eq!(detail.issue.id, "DTS-1");
+        assert_eq!(detail.history.len(), 1);
+        assert_eq!(detail.comments.len(), 1);
+        let mrs: Vec<_> = detail
+            .merge_requests
+            .iter()
+            .map(|link| (link.id.as_str(), link.record.is_some(), link.manual))
+            .collect();
+        // MR-2 is both linked and mentioned, and is listed once as a manual link.
+        assert_eq!(mrs, [("MR-1", true, true), ("MR-2", true, true), ("MR-9", false, true)]);
+        let requirements: Vec<_> = detail
+            .requirements
+            .iter()
+            .map(|link| (link.id.as_str(), link.record.is_some()))
+            .collect();
+        assert_eq!(requirements, [("REQ-1", false)]);
+
+        store.unlink_issue("DTS-1", LinkKind::MergeRequest, "MR-2").unwrap();
+        let detail = store.issue_detail("DTS-1").unwrap().unwrap();
+        let mentioned: Vec<_> =
+            detail.merge_requests.iter().filter(|link| !link.manual).map(|link| &link.id).collect();
+        assert_eq!(mentioned, ["MR-2"]);
+    }
+}
@@ -1,2 +1,9 @@
+mod filter;
+mod form;
+mod history;
 mod model;
+mod stats;
+pub use filter::*;
+pub use form::*;
+pub use history::*;
 pub use model::*;
@@ -1,6 +1,231 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Coded, Status, TransitionError, impl_coded};
+
 pub struct DtsTool {}
 impl DtsTool {
     fn new() -> Self {
         Self {}
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub status: IssueStatus,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub assignee: String,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// How often a fix failed regression or a closed issue resurfaced.
+    #[serde(default)]
+    pub reopen_count: u32,
+}
+
+impl Issue {
+    /// Move the issue to `next`, keeping `resolved_at` in step: it is set when
+    /// a fix enters regression and cleared again when the issue is reopened.
+    pub fn transition(&mut self, next: IssueStatus) -> Result<(), TransitionError> {
+        let previous = self.status;
+        self.status = previous.transition_to(next)?;
+        if next == IssueStatus::Fixing
+            && matches!(previous, IssueStatus::Regression | IssueStatus::Closed)
+        {
+            self.reopen_count += 1;
+        }
+        self.updated_at = Some(Utc::now());
+        match next {
+            IssueStatus::Regression | IssueStatus::Closed => {
+                self.resolved_at.get_or_insert_with(Utc::now);
+            },
+            IssueStatus::Fixing => self.resolved_at = None,
+            IssueStatus::Submitted | IssueStatus::Suspended => {},
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IssueStatus {
+    Submitted,
+    Fixing,
+    Regression,
+    Closed,
+    Suspended,
+}
+
+impl Coded for IssueStatus {
+    const ALL: &'static [Self] =
+        &[Self::Submitted, Self::Fixing, Self::Regression, Self::Closed, Self::Suspended];
+    const KIND: &'static str = "问题单状态";
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Submitted => "submitted",
+            Self::Fixing => "fixing",
+            Self::Regression => "regression",
+            Self::Closed => "closed",
+            Self::Suspended => "suspended",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Submitted => "提交",
+            Self::Fixing => "修改中",
+            Self::Regression => "回归",
+            Self::Closed => "关闭",
+            Self::Suspended => "挂起",
+        }
+    }
+}
+
+impl Status for IssueStatus {
+    /// An issue only closes after its fix passed regression; a failed
+    /// regression or a closed issue that resurfaces goes back to fixing.
+    fn can_transition_to(&self, next: Self) -> bool {
+        use IssueStatus::*;
+        matches!(
+            (self, next),
+            (Submitted, Fixing | Regression | Suspended)
+                | (Fixing, Regression | Suspended)
+                | (Regression, Closed | Fixing)
+                | (Closed, Fixing)
+                | (Suspended, Submitted | Fixing)
+        )
+    }
+}
+impl_coded!(IssueStatus);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Fatal,
+    Serious,
+    Normal,
+    Minor,
+    Suggestion,
+}
+
+impl Coded for Severity {
+    const ALL: &'static [Self] =
+        &[Self::Fatal, Self::Serious, Self::Normal, Self::Minor, Self::Suggestion];
+    const KIND: &'static str = "严重程度";
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Fatal => "fatal",
+            Self::Serious => "serious",
+            Self::Normal => "normal",
+            Self::Minor => "minor",
+            Self::Suggestion => "suggestion",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Fatal => "致命",
+            Self::Serious => "严重",
+            Self::Normal => "一般",
+            Self::Minor => "轻微",
+            Self::Suggestion => "提示",
+        }
+    }
+}
+impl_coded!(Severity);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnknownStatus;
+
+    fn issue(status: IssueStatus) -> Issue {
+        Issue {
+            id: "DTS001".to_string(),
+            title: "登录页面显示异常".to_string(),
+            severity: Severity::Serious,
+            status,
+            created_at: Utc::now(),
+            resolved_at: None,
+            assignee: "张三".to_string(),
+            updated_at: None,
+            reopen_count: 0,
+        }
+    }
+
+    #[test]
+    fn issue_closes_only_through_regression() {
+        assert!(!IssueStatus::Submitted.can_transition_to(IssueStatus::Closed));
+        assert!(!IssueStatus::Fixing.can_transition_to(IssueStatus::Closed));
+        assert!(IssueStatus::Regression.can_transition_to(IssueStatus::Closed));
+        assert_eq!(
+            IssueStatus::Submitted.next_states(),
+            [IssueStatus::Fixing, IssueStatus::Regression, IssueStatus::Suspended]
+        );
+    }
+
+    #[test]
+    fn refused_transition_leaves_issue_untouched() {
+        let mut issue = issue(IssueStatus::Submitted);
+        let err = issue.transition(IssueStatus::Closed).unwrap_err();
+        assert_eq!(err.to_string(), "问题单状态不允许从「提交」变更为「关闭」");
+        assert_eq!(issue.status, IssueStatus::Submitted);
+        assert_eq!(issue.updated_at, None);
+        assert_eq!(issue.resolved_at, None);
+    }
+
+    #[test]
+    fn resolved_at_follows_regression_and_reopening() {
+        let mut issue = issue(IssueStatus::Fixing);
+        issue.transition(IssueStatus::Regression).unwrap();
+        let resolved_at = issue.resolved_at.expect("set on regression");
+        issue.transition(IssueStatus::Closed).unwrap();
+        assert_eq!(issue.resolved_at, Some(resolved_at));
+
+        issue.transition(IssueStatus::Fixing).unwrap();
+        assert_eq!(issue.resolved_at, None);
+        assert!(issue.updated_at.is_some());
+    }
+
+    #[test]
+    fn reopen_count_counts_failed_regressions_and_resurfaced_issues() {
+        let mut issue = issue(IssueStatus::Submitted);
+        issue.transition(IssueStatus::Fixing).unwrap();
+        issue.transition(IssueStatus::Regression).unwrap();
+        assert_eq!(issue.reopen_count, 0);
+
+        issue.transition(IssueStatus::Fixing).unwrap();
+        issue.transition(IssueStatus::Regression).unwrap();
+        issue.transition(IssueStatus::Closed).unwrap();
+        issue.transition(IssueStatus::Fixing).unwrap();
+        assert_eq!(issue.reopen_count, 2);
+
+        // Staying in the same state is not a reopen.
+        issue.transition(IssueStatus::Fixing).unwrap();
+        assert_eq!(issue.reopen_count, 2);
+    }
+
+    #[test]
+    fn severity_round_trips_through_its_code() {
+        for &severity in Severity::ALL {
+            let json = serde_json::to_string(&severity).unwrap();
+            assert_eq!(json, format!("\"{}\"", severity.code()));
+            assert_eq!(serde_json::from_str::<Severity>(&json).unwrap(), severity);
+        }
+        assert_eq!("严重".parse::<Severity>(), Ok(Severity::Serious));
+        assert_eq!("FATAL".parse::<Severity>(), Ok(Severity::Fatal));
+    }
+
+    #[test]
+    fn unknown_codes_are_rejected() {
+        assert_eq!(
+            "blocker".parse::<Severity>(),
+            Err(UnknownStatus { kind: "严重程度", value: "blocker".to_string() })
+        );
+        let err = serde_json::from_str::<IssueStatus>("\"reopened\"").unwrap_err();
+        assert_eq!(err.to_string(), "未知的问题单状态: reopened");
+    }
+}
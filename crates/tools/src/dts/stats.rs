@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+
+use crate::{
+    Chart, ChartKind, Coded as _, DtsTool, Issue, IssueStatus, Severity, week_label, week_start,
+    week_starts,
+};
+
+/// Age buckets of open issues: label and the age in days the bucket starts at.
+pub const AGING_BUCKETS: &[(&str, i64)] =
+    &[("0-3 天", 0), ("3-7 天", 3), ("7-30 天", 7), (">30 天", 30)];
+
+/// Mean time to resolve the issues of one severity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeverityMttr {
+    pub severity: Severity,
+    /// Issues of this severity that have a resolve time.
+    pub resolved: usize,
+    /// Mean time from submission until the fix entered regression.
+    pub mean: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueStats {
+    pub total: usize,
+    /// Issues whose fix reached regression at least once.
+    pub regressed: usize,
+    /// Regressed issues that were reopened at least once.
+    pub reopened: usize,
+    /// One entry per severity, most severe first.
+    pub mttr: Vec<SeverityMttr>,
+    /// Open issue count per [`AGING_BUCKETS`] entry.
+    pub aging: Vec<usize>,
+}
+
+impl IssueStats {
+    /// Share of issues whose fix reached regression, in percent.
+    pub fn regression_rate(&self) -> f64 {
+        percent_of(self.regressed, self.total)
+    }
+
+    /// Share of regressed issues that were reopened, in percent.
+    pub fn reopen_rate(&self) -> f64 {
+        percent_of(self.reopened, self.regressed)
+    }
+
+    /// Open issues per age bucket, as a bar chart.
+    pub fn aging_chart(&self) -> Chart {
+        let labels = AGING_BUCKETS.iter().map(|(label, _)| label.to_string()).collect();
+        Chart::new("未关闭问题单账龄", ChartKind::Bar, labels)
+            .series("问题单数", self.aging.iter().map(|count| *count as f64).collect())
+    }
+}
+
+fn percent_of(part: usize, total: usize) -> f64 {
+    if total > 0 { part as f64 / total as f64 * 100.0 } else { 0.0 }
+}
+
+impl DtsTool {
+    /// Regression and reopen rates, time to resolve per severity, and the
+    /// age of the issues still open at `now`.
+    pub fn stats(issues: &[&Issue], now: DateTime<Utc>) -> IssueStats {
+        let regressed = issues
+            .iter()
+            .filter(|issue| issue.resolved_at.is_some() || issue.reopen_count > 0)
+            .count();
+        let reopened = issues.iter().filter(|issue| issue.reopen_count > 0).count();
+
+        let mttr = Severity::ALL
+            .iter()
+            .map(|severity| {
+                let durations: Vec<Duration> = issues
+                    .iter()
+                    .filter(|issue| issue.severity == *severity)
+                    .filter_map(|issue| Some(issue.resolved_at? - issue.created_at))
+                    .collect();
+                let mean = (!durations.is_empty())
+                    .then(|| durations.iter().copied().sum::<Duration>() / durations.len() as i32);
+                SeverityMttr { severity: *severity, resolved: durations.len(), mean }
+            })
+            .collect();
+
+        let mut aging = vec![0; AGING_BUCKETS.len()];
+        for issue in issues.iter().filter(|issue| issue.status != IssueStatus::Closed) {
+            let age = (now - issue.created_at).num_days();
+            let bucket = AGING_BUCKETS.iter().rposition(|(_, from)| age >= *from).unwrap_or(0);
+            aging[bucket] += 1;
+        }
+
+        IssueStats { total: issues.len(), regressed, reopened, mttr, aging }
+    }
+
+    /// Issues submitted and issues entering regression per week of
+    /// `start..=end` (local dates), as a line chart.
+    pub fn weekly_trend(issues: &[&Issue], start: NaiveDate, end: NaiveDate) -> Chart {
+        let weeks = week_starts(start, end);
+        let count_per_week = |date: fn(&Issue) -> Option<DateTime<Utc>>| {
+            let mut counts: BTreeMap<NaiveDate, f64> =
+                weeks.iter().map(|week| (*week, 0.0)).collect();
+            for issue in issues {
+                let Some(date) = date(issue).map(|date| date.with_timezone(&Local).date_naive())
+                else {
+                    continue;
+                };
+                if date >= start && date <= end {
+                    *counts.entry(week_start(date)).or_default() += 1.0;
+                }
+            }
+            counts.into_values().collect()
+        };
+
+        let labels = weeks.iter().map(|week| week_label(*week)).collect();
+        Chart::new("问题单趋势（按周）", ChartKind::Line, labels)
+            .series("提交", count_per_week(|issue| Some(issue.created_at)))
+            .series("回归", count_per_week(|issue| issue.resolved_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone as _;
+
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, day, hour, 0, 0).unwrap()
+    }
+
+    fn issue(severity: Severity, status: IssueStatus, created: u32) -> Issue {
+        Issue {
+            id: format!("DTS-{}", created),
+            title: String::new(),
+            severity,
+            status,
+            created_at: at(created, 0),
+            resolved_at: None,
+            assignee: String::new(),
+            updated_at: None,
+            reopen_count: 0,
+        }
+    }
+
+    #[test]
+    fn rates_count_regressed_and_reopened_issues() {
+        let open = issue(Severity::Normal, IssueStatus::Submitted, 1);
+        let resolved = Issue {
+            resolved_at: Some(at(3, 0)),
+            ..issue(Severity::Normal, IssueStatus::Closed, 1)
+        };
+        // Reopened after regression, back to fixing without a resolve time.
+        let reopened =
+            Issue { reopen_count: 2, ..issue(Severity::Serious, IssueStatus::Fixing, 2) };
+        let stats = DtsTool::stats(&[&open, &resolved, &reopened, &open], at(10, 0));
+
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.regressed, 2);
+        assert_eq!(stats.reopened, 1);
+        assert_eq!(stats.regression_rate(), 50.0);
+        assert_eq!(stats.reopen_rate(), 50.0);
+    }
+
+    #[test]
+    fn rates_are_zero_without_issues() {
+        let stats = DtsTool::stats(&[], at(10, 0));
+        assert_eq!(stats.regression_rate(), 0.0);
+        assert_eq!(stats.reopen_rate(), 0.0);
+        assert!(stats.mttr.iter().all(|mttr| mttr.mean.is_none()));
+    }
+
+    #[test]
+    fn mttr_averages_per_severity() {
+        let fast = Issue {
+            resolved_at: Some(at(1, 6)),
+            ..issue(Severity::Fatal, IssueStatus::Regression, 1)
+        };
+        let slow =
+            Issue { resolved_at: Some(at(2, 0)), ..issue(Severity::Fatal, IssueStatus::Closed, 1) };
+        let unresolved = issue(Severity::Fatal, IssueStatus::Fixing, 1);
+        let stats = DtsTool::stats(&[&fast, &slow, &unresolved], at(10, 0));
+
+        assert_eq!(stats.mttr.len(), Severity::ALL.len());
+        let fatal = stats.mttr.iter().find(|mttr| mttr.severity == Severity::Fatal).unwrap();
+        assert_eq!(fatal.resolved, 2);
+        assert_eq!(fatal.mean, Some(Duration::hours(15)));
+        let minor = stats.mttr.iter().find(|mttr| mttr.severity == Severity::Minor).unwrap();
+        assert_eq!((minor.resolved, minor.mean), (0, None));
+    }
+
+    #[test]
+    fn aging_skips_closed_issues() {
+        let issues = [
+            issue(Severity::Normal, IssueStatus::Submitted, 20),
+            issue(Severity::Normal, IssueStatus::Fixing, 16),
+            issue(Severity::Normal, IssueStatus::Suspended, 10),
+            issue(Severity::Normal, IssueStatus::Closed, 1),
+        ];
+        let issues: Vec<&Issue> = issues.iter().collect();
+        let stats = DtsTool::stats(&issues, at(21, 12));
+        // Ages 1, 5 and 11 days.
+        assert_eq!(stats.aging, vec![1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn weekly_trend_buckets_by_monday() {
+        let first = issue(Severity::Normal, IssueStatus::Submitted, 6);
+        let second = Issue {
+            resolved_at: Some(at(13, 12)),
+            ..issue(Severity::Normal, IssueStatus::Regression, 7)
+        };
+        let outside = issue(Severity::Normal, IssueStatus::Submitted, 28);
+        let start = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 1, 18).unwrap();
+        let chart = DtsTool::weekly_trend(&[&first, &second, &outside], start, end);
+
+        assert_eq!(chart.labels, vec!["01-05", "01-12"]);
+        assert_eq!(chart.series[0].values, vec![2.0, 0.0]);
+        assert_eq!(chart.series[1].values, vec![0.0, 1.0]);
+    }
+}
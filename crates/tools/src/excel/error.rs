@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExcelError {
+    #[error("无法读取文件: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("不支持的文件格式: {0}")]
+    UnsupportedFormat(String),
+    #[error("工作表 {0} 不存在")]
+    SheetNotFound(String),
+    #[error("解析 Excel 失败: {0}")]
+    Parse(#[from] calamine::Error),
+    #[error("写入 Excel 失败: {0}")]
+    Write(#[from] rust_xlsxwriter::XlsxError),
+    #[error("该文件基于旧版{name}模板 (v{version})，列可能已变化，请重新下载模板")]
+    OutdatedTemplate { name: &'static str, version: u32 },
+    #[error("该文件不是{name}导入模板（模板类型: {kind}）")]
+    WrongTemplate { name: &'static str, kind: String },
+}
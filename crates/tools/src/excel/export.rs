@@ -0,0 +1,407 @@
+use std::{collections::HashSet, fmt, path::Path};
+
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use rust_xlsxwriter::{Format, FormatAlign, Workbook as XlsxWorkbook, Worksheet};
+
+use crate::{
+    CellValue, Coded as _, DtsTool, ExcelError, ExcelTool, Issue, IssueStatus, MergeRequest,
+    Requirement, RequirementStatus, TestCase,
+};
+
+const MIN_COLUMN_WIDTH: usize = 8;
+const MAX_COLUMN_WIDTH: usize = 60;
+
+/// A statistic as shown on a view's stat card.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatValue {
+    Count(usize),
+    /// Shown with an explicit sign, e.g. line changes.
+    Signed(i64),
+    /// Already scaled to 0–100.
+    Percent(f64),
+}
+
+impl fmt::Display for StatValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count(value) => write!(f, "{}", value),
+            Self::Signed(value) => write!(f, "{:+}", value),
+            Self::Percent(value) => write!(f, "{:.1}%", value),
+        }
+    }
+}
+
+/// A record type that can be written to an export sheet.
+pub trait Exportable {
+    const SHEET_NAME: &'static str;
+    const HEADERS: &'static [&'static str];
+
+    /// One cell per header.
+    fn row(&self) -> Vec<CellValue>;
+
+    /// The statistics shown on the view's stat cards, in display order.
+    fn summary(records: &[&Self]) -> Vec<(&'static str, StatValue)>;
+}
+
+impl ExcelTool {
+    /// Write `records` to an .xlsx file: a data sheet with a frozen, filterable
+    /// header row, followed by a summary sheet with the view's statistics.
+    pub fn export<T: Exportable>(path: impl AsRef<Path>, records: &[&T]) -> Result<(), ExcelError> {
+        export_workbook(records)?.save(path.as_ref())?;
+        Ok(())
+    }
+}
+
+fn export_workbook<T: Exportable>(records: &[&T]) -> Result<XlsxWorkbook, ExcelError> {
+    let mut workbook = XlsxWorkbook::new();
+    let header = Format::new().set_bold().set_background_color("#E5E7EB");
+
+    let sheet = workbook.add_worksheet().set_name(T::SHEET_NAME)?;
+    let rows: Vec<Vec<CellValue>> = records.iter().map(|record| record.row()).collect();
+    write_table(sheet, &header, T::HEADERS, &rows)?;
+
+    let summary = workbook.add_worksheet().set_name("统计")?;
+    write_summary(summary, &header, &T::summary(records))?;
+    Ok(workbook)
+}
+
+pub(crate) fn write_table(
+    sheet: &mut Worksheet,
+    header: &Format,
+    headers: &[&str],
+    rows: &[Vec<CellValue>],
+) -> Result<(), ExcelError> {
+    let date = Format::new().set_num_format("yyyy-mm-dd").set_align(FormatAlign::Left);
+    let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm").set_align(FormatAlign::Left);
+
+    let mut widths: Vec<usize> = headers.iter().map(|text| display_width(text)).collect();
+    for (column, text) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, column as u16, *text, header)?;
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let row_num = index as u32 + 1;
+        for (column, cell) in row.iter().enumerate() {
+            let col_num = column as u16;
+            match cell {
+                CellValue::Empty => {},
+                CellValue::Bool(value) => _ = sheet.write_boolean(row_num, col_num, *value)?,
+                CellValue::Int(value) => _ = sheet.write_number(row_num, col_num, *value as f64)?,
+                CellValue::Float(value) => _ = sheet.write_number(row_num, col_num, *value)?,
+                CellValue::Text(value) | CellValue::Error(value) => {
+                    _ = sheet.write_string(row_num, col_num, value)?
+                },
+                CellValue::DateTime(value) if value.time() == NaiveTime::MIN => {
+                    _ = sheet.write_datetime_with_format(row_num, col_num, value, &date)?
+                },
+                CellValue::DateTime(value) => {
+                    _ = sheet.write_datetime_with_format(row_num, col_num, value, &datetime)?
+                },
+            }
+            if let Some(width) = widths.get_mut(column) {
+                *width = (*width).max(display_width(&cell.to_string()));
+            }
+        }
+    }
+
+    for (column, width) in widths.into_iter().enumerate() {
+        let width = (width + 2).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+        sheet.set_column_width(column as u16, width as f64)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    if !headers.is_empty() {
+        sheet.autofilter(0, 0, rows.len() as u32, headers.len() as u16 - 1)?;
+    }
+    Ok(())
+}
+
+fn write_summary(
+    sheet: &mut Worksheet,
+    header: &Format,
+    stats: &[(&'static str, StatValue)],
+) -> Result<(), ExcelError> {
+    let count = Format::new().set_num_format("0").set_align(FormatAlign::Left);
+    let signed = Format::new().set_num_format("+0;-0;0").set_align(FormatAlign::Left);
+    // Stored as a fraction so it stays numeric in Excel.
+    let percent = Format::new().set_num_format("0.0%").set_align(FormatAlign::Left);
+    let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm").set_align(FormatAlign::Left);
+
+    sheet.write_string_with_format(0, 0, "指标", header)?;
+    sheet.write_string_with_format(0, 1, "数值", header)?;
+    for (index, (label, value)) in stats.iter().enumerate() {
+        let row = index as u32 + 1;
+        sheet.write_string(row, 0, *label)?;
+        match value {
+            StatValue::Count(value) => {
+                sheet.write_number_with_format(row, 1, *value as f64, &count)?
+            },
+            StatValue::Signed(value) => {
+                sheet.write_number_with_format(row, 1, *value as f64, &signed)?
+            },
+            StatValue::Percent(value) => {
+                sheet.write_number_with_format(row, 1, value / 100.0, &percent)?
+            },
+        };
+    }
+    let row = stats.len() as u32 + 1;
+    sheet.write_string(row, 0, "导出时间")?;
+    sheet.write_datetime_with_format(row, 1, Local::now().naive_local(), &datetime)?;
+
+    sheet.set_column_width(0, 16)?;
+    sheet.set_column_width(1, 20)?;
+    Ok(())
+}
+
+/// Column width in Excel character units; CJK characters take two.
+fn display_width(text: &str) -> usize {
+    text.chars().map(|ch| if ch.is_ascii() { 1 } else { 2 }).sum()
+}
+
+fn local_datetime(value: DateTime<Utc>) -> CellValue {
+    CellValue::DateTime(value.with_timezone(&Local).naive_local())
+}
+
+fn local_date(value: DateTime<Utc>) -> CellValue {
+    CellValue::DateTime(value.with_timezone(&Local).date_naive().and_time(NaiveTime::MIN))
+}
+
+impl Exportable for MergeRequest {
+    const HEADERS: &'static [&'static str] =
+        &["ID", "标题", "作者", "仓库", "创建时间", "新增行数", "删除行数", "状态", "合入时间"];
+    const SHEET_NAME: &'static str = "MR";
+
+    fn row(&self) -> Vec<CellValue> {
+        vec![
+            CellValue::Text(self.id.clone()),
+            CellValue::Text(self.title.clone()),
+            CellValue::Text(self.author.clone()),
+            CellValue::Text(self.repository.clone()),
+            local_datetime(self.created_at),
+            CellValue::Int(self.add_lines as i64),
+            CellValue::Int(self.del_lines as i64),
+            CellValue::Text(self.status.label().to_string()),
+            self.merged_at.map(local_datetime).unwrap_or(CellValue::Empty),
+        ]
+    }
+
+    fn summary(records: &[&Self]) -> Vec<(&'static str, StatValue)> {
+        let additions: i64 = records.iter().map(|mr| mr.add_lines as i64).sum();
+        let deletions: i64 = records.iter().map(|mr| mr.del_lines as i64).sum();
+        vec![
+            ("MR 数量", StatValue::Count(records.len())),
+            ("新增行数", StatValue::Signed(additions)),
+            ("删除行数", StatValue::Signed(-deletions)),
+            ("净变化", StatValue::Signed(additions - deletions)),
+        ]
+    }
+}
+
+impl Exportable for Issue {
+    const HEADERS: &'static [&'static str] =
+        &["ID", "标题", "严重程度", "状态", "责任人", "创建时间", "解决时间", "重开次数"];
+    const SHEET_NAME: &'static str = "问题单";
+
+    fn row(&self) -> Vec<CellValue> {
+        vec![
+            CellValue::Text(self.id.clone()),
+            CellValue::Text(self.title.clone()),
+            CellValue::Text(self.severity.label().to_string()),
+            CellValue::Text(self.status.label().to_string()),
+            CellValue::Text(self.assignee.clone()),
+            local_datetime(self.created_at),
+            self.resolved_at.map(local_datetime).unwrap_or(CellValue::Empty),
+            CellValue::Int(self.reopen_count as i64),
+        ]
+    }
+
+    fn summary(records: &[&Self]) -> Vec<(&'static str, StatValue)> {
+        let submitted = records.iter().filter(|i| i.status == IssueStatus::Submitted).count();
+        let stats = DtsTool::stats(records, Utc::now());
+        vec![
+            ("总计问题单", StatValue::Count(records.len())),
+            ("已提交", StatValue::Count(submitted)),
+            ("已回归", StatValue::Count(stats.regressed)),
+            ("回归率", StatValue::Percent(stats.regression_rate())),
+            ("重开率", StatValue::Percent(stats.reopen_rate())),
+        ]
+    }
+}
+
+impl Exportable for Requirement {
+    const HEADERS: &'static [&'static str] =
+        &["ID", "标题", "版本", "测试周期（天）", "开始日期", "结束日期", "状态", "负责人"];
+    const SHEET_NAME: &'static str = "需求";
+
+    fn row(&self) -> Vec<CellValue> {
+        vec![
+            CellValue::Text(self.id.clone()),
+            CellValue::Text(self.title.clone()),
+            CellValue::Text(self.version.clone()),
+            CellValue::Int(self.test_cycle_days),
+            local_date(self.start_date),
+            local_date(self.end_date),
+            CellValue::Text(self.status.label().to_string()),
+            CellValue::Text(self.owner.clone()),
+        ]
+    }
+
+    fn summary(records: &[&Self]) -> Vec<(&'static str, StatValue)> {
+        let count = |status| records.iter().filter(|r| r.status == status).count();
+        vec![
+            ("总需求", StatValue::Count(records.len())),
+            ("进行中", StatValue::Count(count(RequirementStatus::InProgress))),
+            ("已完成", StatValue::Count(count(RequirementStatus::Done))),
+            ("延期", StatValue::Count(count(RequirementStatus::Delayed))),
+        ]
+    }
+}
+
+impl Exportable for TestCase {
+    const HEADERS: &'static [&'static str] = &["ID", "用例名称", "状态", "错误信息"];
+    const SHEET_NAME: &'static str = "失败用例";
+
+    fn row(&self) -> Vec<CellValue> {
+        vec![
+            CellValue::Text(self.id.clone()),
+            CellValue::Text(self.name.clone()),
+            CellValue::Text(self.status.clone()),
+            self.error_msg.clone().map(CellValue::Text).unwrap_or(CellValue::Empty),
+        ]
+    }
+
+    fn summary(records: &[&Self]) -> Vec<(&'static str, StatValue)> {
+        let errors: HashSet<&str> =
+            records.iter().filter_map(|case| case.error_msg.as_deref()).collect();
+        vec![
+            ("失败用例数", StatValue::Count(records.len())),
+            ("不同错误数", StatValue::Count(errors.len())),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use calamine::{Reader as _, Xlsx};
+    use chrono::{Local, NaiveDate, TimeZone as _};
+
+    use super::*;
+    use crate::{MrStatus, Severity, Sheet};
+
+    fn local(day: u32, hour: u32) -> DateTime<Utc> {
+        Local.with_ymd_and_hms(2024, 3, day, hour, 30, 0).unwrap().to_utc()
+    }
+
+    fn day(day: u32) -> CellValue {
+        CellValue::DateTime(NaiveDate::from_ymd_opt(2024, 3, day).unwrap().into())
+    }
+
+    fn at(day: u32, hour: u32) -> CellValue {
+        CellValue::DateTime(local(day, hour).with_timezone(&Local).naive_local())
+    }
+
+    /// Export `records` in memory and read both sheets back.
+    fn round_trip<T: Exportable>(records: &[&T]) -> (Sheet, Sheet) {
+        let buffer = export_workbook(records).unwrap().save_to_buffer().unwrap();
+        let mut workbook = Xlsx::new(Cursor::new(buffer)).unwrap();
+        assert_eq!(workbook.sheet_names(), [T::SHEET_NAME, "统计"]);
+        let mut read = |name| Sheet::from_range(name, &workbook.worksheet_range(name).unwrap());
+        (read(T::SHEET_NAME), read("统计"))
+    }
+
+    fn stat(summary: &Sheet, label: &str) -> CellValue {
+        let row = summary.rows.iter().find(|row| row[0] == CellValue::Text(label.to_string()));
+        row.unwrap_or_else(|| panic!("no {label} in the summary"))[1].clone()
+    }
+
+    #[test]
+    fn requirements_round_trip_with_a_numeric_test_cycle() {
+        let req = Requirement {
+            id: "REQ-1".to_string(),
+            title: "单点登录".to_string(),
+            version: "v1.2.0".to_string(),
+            test_cycle_days: 5,
+            start_date: local(1, 0),
+            end_date: local(5, 0),
+            status: RequirementStatus::Done,
+            owner: "王五".to_string(),
+            updated_at: None,
+        };
+        let (sheet, summary) = round_trip(&[&req]);
+
+        assert_eq!(sheet.headers, Requirement::HEADERS);
+        assert_eq!(sheet.rows, [vec![
+            CellValue::Text("REQ-1".to_string()),
+            CellValue::Text("单点登录".to_string()),
+            CellValue::Text("v1.2.0".to_string()),
+            CellValue::Float(5.0),
+            day(1),
+            day(5),
+            CellValue::Text("已完成".to_string()),
+            CellValue::Text("王五".to_string()),
+        ]]);
+        assert_eq!(stat(&summary, "已完成"), CellValue::Float(1.0));
+    }
+
+    #[test]
+    fn merge_requests_round_trip() {
+        let mr = MergeRequest {
+            id: "MR-1".to_string(),
+            title: "修复登录".to_string(),
+            author: "张三".to_string(),
+            created_at: local(1, 9),
+            add_lines: 120,
+            del_lines: 30,
+            status: MrStatus::Merged,
+            updated_at: None,
+            repository: "web".to_string(),
+            merged_at: Some(local(2, 10)),
+        };
+        let (sheet, summary) = round_trip(&[&mr]);
+
+        assert_eq!(sheet.headers, MergeRequest::HEADERS);
+        assert_eq!(sheet.rows, [vec![
+            CellValue::Text("MR-1".to_string()),
+            CellValue::Text("修复登录".to_string()),
+            CellValue::Text("张三".to_string()),
+            CellValue::Text("web".to_string()),
+            at(1, 9),
+            CellValue::Float(120.0),
+            CellValue::Float(30.0),
+            CellValue::Text("已合入".to_string()),
+            at(2, 10),
+        ]]);
+        assert_eq!(stat(&summary, "净变化"), CellValue::Float(90.0));
+    }
+
+    #[test]
+    fn issues_and_failed_cases_round_trip_with_empty_cells() {
+        let issue = Issue {
+            id: "DTS-1".to_string(),
+            title: "登录页面白屏".to_string(),
+            severity: Severity::Serious,
+            status: IssueStatus::Submitted,
+            created_at: local(1, 9),
+            resolved_at: None,
+            assignee: "张三".to_string(),
+            updated_at: None,
+            reopen_count: 0,
+        };
+        let (sheet, summary) = round_trip(&[&issue]);
+        assert_eq!(sheet.headers, Issue::HEADERS);
+        assert_eq!(sheet.rows[0][5], at(1, 9));
+        assert_eq!(sheet.rows[0][6], CellValue::Empty);
+        assert_eq!(stat(&summary, "已提交"), CellValue::Float(1.0));
+
+        let case = TestCase {
+            id: "TC-1".to_string(),
+            name: "登录成功".to_string(),
+            status: "失败".to_string(),
+            error_msg: None,
+        };
+        let (sheet, summary) = round_trip(&[&case]);
+        assert_eq!(sheet.rows, [case.row()]);
+        assert_eq!(stat(&summary, "不同错误数"), CellValue::Float(0.0));
+    }
+}
@@ -0,0 +1,730 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone as _, Utc};
+use rust_xlsxwriter::{Format, Workbook as XlsxWorkbook};
+
+use crate::{
+    CellValue, Coded, EnvStatus, ExcelError, IMPORT_ACTOR, Issue, IssueStatus, MergeRequest,
+    MrStatus, Record, Requirement, RequirementStatus, Severity, Sheet, Status as _, Store, Table,
+    Transaction, VirtualEnv, write_table,
+};
+
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%Y%m%d"];
+const DATETIME_FORMATS: &[&str] =
+    &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Number,
+    Date,
+    /// One of the values returned by [`Importable::choices`].
+    Choice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportField {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub required: bool,
+    pub kind: FieldKind,
+}
+
+impl ImportField {
+    const fn number(self) -> Self {
+        Self { kind: FieldKind::Number, ..self }
+    }
+
+    const fn date(self) -> Self {
+        Self { kind: FieldKind::Date, ..self }
+    }
+
+    const fn choice(self) -> Self {
+        Self { kind: FieldKind::Choice, ..self }
+    }
+}
+
+const fn field(name: &'static str, label: &'static str, required: bool) -> ImportField {
+    ImportField { name, label, required, kind: FieldKind::Text }
+}
+
+/// A record type that can be bulk loaded from a spreadsheet.
+pub trait Importable: Table + Record + Sized {
+    const IMPORT_FIELDS: &'static [ImportField];
+
+    /// Build a record from one row. Problems are reported through `row`;
+    /// the returned record is discarded if there were any.
+    fn read(row: &mut RowReader<'_>) -> Self;
+
+    /// Apply an imported row onto `existing`, the stored record with the same
+    /// id. Refused status transitions are reported as the error.
+    fn update(existing: &Self, imported: Self) -> Result<Self, String>;
+
+    /// Called in the commit transaction after `self` was written over
+    /// `previous`, the stored record it replaced, if any.
+    fn imported(&self, _previous: Option<&Self>, _tx: &Transaction) -> Result<()> {
+        Ok(())
+    }
+
+    /// The accepted values of a [`FieldKind::Choice`] field.
+    fn choices(_field: &str) -> Vec<&'static str> {
+        Vec::new()
+    }
+}
+
+fn labels<S: Coded>() -> Vec<&'static str> {
+    S::ALL.iter().map(|status| status.label()).collect()
+}
+
+/// Which sheet column feeds each of a record type's [`ImportField`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    columns: Vec<Option<usize>>,
+}
+
+impl ColumnMapping {
+    /// Map every field whose name or label matches a header. A trailing `*`
+    /// on the header, used to mark required columns, is ignored.
+    pub fn guess(fields: &[ImportField], headers: &[String]) -> Self {
+        let normalize = |text: &str| text.trim().trim_end_matches('*').trim().to_lowercase();
+        let headers: Vec<String> = headers.iter().map(|header| normalize(header)).collect();
+        let columns = fields
+            .iter()
+            .map(|field| {
+                headers.iter().position(|header| {
+                    *header == normalize(field.label) || *header == normalize(field.name)
+                })
+            })
+            .collect();
+        Self { columns }
+    }
+
+    pub fn column(&self, field: usize) -> Option<usize> {
+        self.columns.get(field).copied().flatten()
+    }
+
+    pub fn set(&mut self, field: usize, column: Option<usize>) {
+        if let Some(slot) = self.columns.get_mut(field) {
+            *slot = column;
+        }
+    }
+
+    /// Labels of required fields that have no column yet.
+    pub fn missing_required(&self, fields: &[ImportField]) -> Vec<&'static str> {
+        fields
+            .iter()
+            .enumerate()
+            .filter(|(index, field)| field.required && self.column(*index).is_none())
+            .map(|(_, field)| field.label)
+            .collect()
+    }
+}
+
+/// The mapped cells of one sheet row, handed to [`Importable::read`].
+pub struct RowReader<'a> {
+    fields: &'static [ImportField],
+    cells: Vec<&'a CellValue>,
+    errors: Vec<String>,
+}
+
+impl<'a> RowReader<'a> {
+    fn new(fields: &'static [ImportField], row: &'a [CellValue], mapping: &ColumnMapping) -> Self {
+        let cells = (0..fields.len())
+            .map(|index| {
+                mapping
+                    .column(index)
+                    .and_then(|column| row.get(column))
+                    .unwrap_or(&CellValue::Empty)
+            })
+            .collect();
+        Self { fields, cells, errors: Vec::new() }
+    }
+
+    /// The field's cell, `None` if it is empty. Empty required fields, and
+    /// names that are not import fields, are reported.
+    fn cell(&mut self, name: &str) -> Option<(&'static str, &'a CellValue)> {
+        let Some(index) = self.fields.iter().position(|field| field.name == name) else {
+            self.error(format!("未知的导入字段: {}", name));
+            return None;
+        };
+        let field = self.fields[index];
+        let cell = self.cells[index];
+        if !cell.is_empty() {
+            return Some((field.label, cell));
+        }
+        if field.required {
+            self.error(format!("{}不能为空", field.label));
+        }
+        None
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into());
+    }
+
+    pub fn text(&mut self, name: &str) -> String {
+        self.cell(name).map(|(_, cell)| cell.to_string().trim().to_string()).unwrap_or_default()
+    }
+
+    pub fn parse<T: FromStr>(&mut self, name: &str) -> Option<T> {
+        let (label, cell) = self.cell(name)?;
+        let text = cell.to_string();
+        let value = text.trim().parse().ok();
+        if value.is_none() {
+            self.error(format!("{}无法识别: {}", label, text.trim()));
+        }
+        value
+    }
+
+    /// Accepts real date cells as well as text in common date formats,
+    /// interpreted in local time.
+    pub fn datetime(&mut self, name: &str) -> Option<DateTime<Utc>> {
+        let (label, cell) = self.cell(name)?;
+        let naive = match cell {
+            CellValue::DateTime(value) => Some(*value),
+            CellValue::Text(text) => parse_datetime(text.trim()),
+            _ => None,
+        };
+        let value = naive
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+            .map(|local| local.with_timezone(&Utc));
+        if value.is_none() {
+            self.error(format!("{}格式错误: {}", label, cell));
+        }
+        value
+    }
+}
+
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportRow<T> {
+    /// The 1-based spreadsheet row number.
+    pub row: usize,
+    pub cells: Vec<CellValue>,
+    pub result: Result<T, Vec<String>>,
+    /// The stored record with the same id, which importing the row would
+    /// overwrite.
+    pub existing: Option<T>,
+}
+
+impl<T> ImportRow<T> {
+    /// A valid row whose id is already in the store.
+    pub fn is_conflict(&self) -> bool {
+        self.result.is_ok() && self.existing.is_some()
+    }
+}
+
+/// Every non-blank row of a sheet, validated against a column mapping.
+#[derive(Debug, Clone)]
+pub struct ImportPreview<T> {
+    pub headers: Vec<String>,
+    pub rows: Vec<ImportRow<T>>,
+}
+
+impl<T: Importable> ImportPreview<T> {
+    /// Validate the rows of `sheet` and look up which ids `store` already
+    /// has.
+    pub fn new(sheet: &Sheet, mapping: &ColumnMapping, store: &Store) -> Result<Self> {
+        let mut stored: HashMap<String, T> = store
+            .repository::<T>()
+            .all()?
+            .into_iter()
+            .map(|record| (record.id().to_string(), record))
+            .collect();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let rows = sheet
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, cells)| !cells.iter().all(CellValue::is_empty))
+            .map(|(index, cells)| {
+                let row = sheet.first_row + index;
+                let mut reader = RowReader::new(T::IMPORT_FIELDS, cells, mapping);
+                let record = T::read(&mut reader);
+                if !record.id().is_empty() {
+                    if let Some(first) = seen.get(record.id()) {
+                        reader.error(format!("ID 与第 {} 行重复", first));
+                    } else {
+                        seen.insert(record.id().to_string(), row);
+                    }
+                }
+                let existing = stored.remove(record.id());
+                let result = match &existing {
+                    _ if !reader.errors.is_empty() => Err(reader.errors),
+                    Some(existing) => T::update(existing, record).map_err(|err| vec![err]),
+                    None => Ok(record),
+                };
+                ImportRow { row, cells: cells.clone(), result, existing }
+            })
+            .collect();
+        Ok(Self { headers: sheet.headers.clone(), rows })
+    }
+
+    pub fn valid(&self) -> impl Iterator<Item = &T> {
+        self.rows.iter().filter_map(|row| row.result.as_ref().ok())
+    }
+
+    pub fn rejected(&self) -> impl Iterator<Item = &ImportRow<T>> {
+        self.rows.iter().filter(|row| row.result.is_err())
+    }
+
+    /// Valid rows that would overwrite a stored record.
+    pub fn conflicts(&self) -> impl Iterator<Item = &ImportRow<T>> {
+        self.rows.iter().filter(|row| row.is_conflict())
+    }
+
+    /// How many rows [`commit`](Self::commit) would write.
+    pub fn importable(&self, overwrite: bool) -> usize {
+        self.valid().count() - if overwrite { 0 } else { self.conflicts().count() }
+    }
+
+    /// Write all valid rows in one transaction, so a failure imports nothing.
+    /// Rows whose id is already stored are skipped unless `overwrite` is set.
+    pub fn commit(&self, store: &Store, overwrite: bool) -> Result<usize> {
+        store.transaction(|tx| {
+            let mut count = 0;
+            for row in &self.rows {
+                let Ok(record) = &row.result else {
+                    continue;
+                };
+                if row.existing.is_some() && !overwrite {
+                    continue;
+                }
+                tx.upsert(record)?;
+                record.imported(row.existing.as_ref(), tx)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    /// The rejected rows as they were in the sheet, plus the reasons.
+    pub fn write_error_report(&self, path: impl AsRef<Path>) -> Result<(), ExcelError> {
+        let mut workbook = XlsxWorkbook::new();
+        let header = Format::new().set_bold().set_background_color("#FEE2E2");
+
+        let mut headers: Vec<&str> = vec!["行号", "错误原因"];
+        headers.extend(self.headers.iter().map(String::as_str));
+        let rows: Vec<Vec<CellValue>> = self
+            .rejected()
+            .map(|row| {
+                let errors = row.result.as_ref().err().map(|errors| errors.join("；"));
+                let mut cells = vec![
+                    CellValue::Int(row.row as i64),
+                    CellValue::Text(errors.unwrap_or_default()),
+                ];
+                cells.extend(row.cells.iter().cloned());
+                cells
+            })
+            .collect();
+
+        let sheet = workbook.add_worksheet().set_name("导入错误")?;
+        write_table(sheet, &header, &headers, &rows)?;
+        workbook.save(path.as_ref())?;
+        Ok(())
+    }
+}
+
+impl Importable for Requirement {
+    const IMPORT_FIELDS: &'static [ImportField] = &[
+        field("id", "ID", true),
+        field("title", "标题", true),
+        field("version", "版本", false),
+        field("start_date", "开始日期", true).date(),
+        field("end_date", "结束日期", true).date(),
+        field("status", "状态", false).choice(),
+        field("owner", "负责人", false),
+    ];
+
+    fn choices(field: &str) -> Vec<&'static str> {
+        match field {
+            "status" => labels::<RequirementStatus>(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn read(row: &mut RowReader<'_>) -> Self {
+        let start_date = row.datetime("start_date");
+        let end_date = row.datetime("end_date");
+        if let (Some(start), Some(end)) = (start_date, end_date)
+            && end < start
+        {
+            row.error("结束日期早于开始日期");
+        }
+        let mut req = Self {
+            id: row.text("id"),
+            title: row.text("title"),
+            version: row.text("version"),
+            test_cycle_days: 0,
+            start_date: Default::default(),
+            end_date: Default::default(),
+            status: row.parse("status").unwrap_or(RequirementStatus::NotStarted),
+            owner: row.text("owner"),
+            updated_at: Some(Utc::now()),
+        };
+        req.set_schedule(start_date.unwrap_or_default(), end_date.unwrap_or_default());
+        req
+    }
+
+    /// Only finishing or reopening is checked against the state machine, the
+    /// other statuses follow the dates when loading.
+    fn update(existing: &Self, imported: Self) -> Result<Self, String> {
+        let done = RequirementStatus::Done;
+        let status = if existing.status == done || imported.status == done {
+            existing.status.transition_to(imported.status).map_err(|err| err.to_string())?
+        } else {
+            imported.status
+        };
+        Ok(Self { status, ..imported })
+    }
+}
+
+impl Importable for Issue {
+    const IMPORT_FIELDS: &'static [ImportField] = &[
+        field("id", "ID", true),
+        field("title", "标题", true),
+        field("severity", "严重程度", true).choice(),
+        field("status", "状态", false).choice(),
+        field("assignee", "责任人", false),
+        field("created_at", "创建时间", false).date(),
+        field("resolved_at", "解决时间", false).date(),
+        field("reopen_count", "重开次数", false).number(),
+    ];
+
+    fn choices(field: &str) -> Vec<&'static str> {
+        match field {
+            "severity" => labels::<Severity>(),
+            "status" => labels::<IssueStatus>(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn read(row: &mut RowReader<'_>) -> Self {
+        let status = row.parse("status").unwrap_or(IssueStatus::Submitted);
+        let resolved_at = row.datetime("resolved_at");
+        if resolved_at.is_some() && !matches!(status, IssueStatus::Regression | IssueStatus::Closed)
+        {
+            row.error(format!("状态为{}的问题单不应有解决时间", status.label()));
+        }
+        Self {
+            id: row.text("id"),
+            title: row.text("title"),
+            severity: row.parse("severity").unwrap_or(Severity::Normal),
+            status,
+            created_at: row.datetime("created_at").unwrap_or_else(Utc::now),
+            resolved_at,
+            assignee: row.text("assignee"),
+            updated_at: Some(Utc::now()),
+            reopen_count: row.parse("reopen_count").unwrap_or_default(),
+        }
+    }
+
+    /// Status changes go through [`Issue::transition`], which keeps the
+    /// reopen count and resolution time in step; the other fields are taken
+    /// from the sheet.
+    fn update(existing: &Self, imported: Self) -> Result<Self, String> {
+        let mut issue = existing.clone();
+        issue.transition(imported.status).map_err(|err| err.to_string())?;
+        issue.title = imported.title;
+        issue.severity = imported.severity;
+        issue.assignee = imported.assignee;
+        if imported.resolved_at.is_some() {
+            issue.resolved_at = imported.resolved_at;
+        }
+        Ok(issue)
+    }
+
+    fn imported(&self, previous: Option<&Self>, tx: &Transaction) -> Result<()> {
+        self.record_change(previous, IMPORT_ACTOR, tx)
+    }
+}
+
+impl Importable for MergeRequest {
+    const IMPORT_FIELDS: &'static [ImportField] = &[
+        field("id", "ID", true),
+        field("title", "标题", true),
+        field("author", "作者", true),
+        field("repository", "仓库", false),
+        field("created_at", "创建时间", false).date(),
+        field("add_lines", "新增行数", false).number(),
+        field("del_lines", "删除行数", false).number(),
+        field("status", "状态", false).choice(),
+        field("merged_at", "合入时间", false).date(),
+    ];
+
+    fn read(row: &mut RowReader<'_>) -> Self {
+        Self {
+            id: row.text("id"),
+            title: row.text("title"),
+            author: row.text("author"),
+            created_at: row.datetime("created_at").unwrap_or_else(Utc::now),
+            add_lines: row.parse("add_lines").unwrap_or_default(),
+            del_lines: row.parse("del_lines").unwrap_or_default(),
+            status: row.parse("status").unwrap_or(MrStatus::Open),
+            updated_at: Some(Utc::now()),
+            repository: row.text("repository"),
+            merged_at: row.datetime("merged_at"),
+        }
+    }
+
+    fn update(existing: &Self, imported: Self) -> Result<Self, String> {
+        let mut mr = existing.clone();
+        mr.transition(imported.status).map_err(|err| err.to_string())?;
+        Ok(Self {
+            status: mr.status,
+            merged_at: imported.merged_at.or(mr.merged_at),
+            updated_at: mr.updated_at,
+            ..imported
+        })
+    }
+
+    fn choices(field: &str) -> Vec<&'static str> {
+        match field {
+            "status" => labels::<MrStatus>(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Importable for VirtualEnv {
+    const IMPORT_FIELDS: &'static [ImportField] = &[
+        field("id", "ID", true),
+        field("name", "名称", true),
+        field("status", "状态", false).choice(),
+        field("owner", "占用人", false),
+    ];
+
+    fn read(row: &mut RowReader<'_>) -> Self {
+        let owner = row.text("owner");
+        Self {
+            id: row.text("id"),
+            name: row.text("name"),
+            status: row.parse("status").unwrap_or(EnvStatus::Idle),
+            owner: (!owner.is_empty()).then_some(owner),
+            lease_until: None,
+            reason: None,
+        }
+    }
+
+    /// The current lease is kept, the sheet has no columns for it. Holders
+    /// only change through reservations, which keep the lease history, so a
+    /// held or deploying environment can't get another holder or status here.
+    fn update(existing: &Self, imported: Self) -> Result<Self, String> {
+        let held = existing.status != EnvStatus::Idle || existing.lease_until.is_some();
+        if held && (imported.status, &imported.owner) != (existing.status, &existing.owner) {
+            return Err(match existing.status {
+                EnvStatus::Deploying => "环境正在部署，不能修改状态或占用人".to_string(),
+                _ => format!(
+                    "环境已被 {} 占用，不能修改状态或占用人",
+                    existing.owner.as_deref().unwrap_or("未知用户")
+                ),
+            });
+        }
+        let status =
+            existing.status.transition_to(imported.status).map_err(|err| err.to_string())?;
+        Ok(Self {
+            status,
+            lease_until: existing.lease_until,
+            reason: existing.reason.clone(),
+            ..imported
+        })
+    }
+
+    fn choices(field: &str) -> Vec<&'static str> {
+        match field {
+            "status" => labels::<EnvStatus>(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    const HEADERS: &[&str] = &["ID *", "标题", "严重程度", "状态", "解决时间"];
+
+    fn sheet(rows: &[[&str; 5]]) -> Sheet {
+        sheet_of("问题单", HEADERS, rows)
+    }
+
+    fn sheet_of<const N: usize>(name: &str, headers: &[&str], rows: &[[&str; N]]) -> Sheet {
+        Sheet {
+            name: name.to_string(),
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            column_types: Vec::new(),
+            rows: rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|cell| match *cell {
+                            "" => CellValue::Empty,
+                            text => CellValue::Text(text.to_string()),
+                        })
+                        .collect()
+                })
+                .collect(),
+            first_row: 2,
+        }
+    }
+
+    fn preview(store: &Store, rows: &[[&str; 5]]) -> ImportPreview<Issue> {
+        let sheet = sheet(rows);
+        let mapping = ColumnMapping::guess(Issue::IMPORT_FIELDS, &sheet.headers);
+        ImportPreview::new(&sheet, &mapping, store).unwrap()
+    }
+
+    fn errors(preview: &ImportPreview<Issue>) -> Vec<(usize, Vec<String>)> {
+        preview
+            .rejected()
+            .map(|row| (row.row, row.result.as_ref().err().cloned().unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn guess_matches_labels_and_names_ignoring_required_marks() {
+        let headers = ["ID *".to_string(), " title ".to_string(), "备注".to_string()];
+        let mapping = ColumnMapping::guess(Issue::IMPORT_FIELDS, &headers);
+        assert_eq!(mapping.column(0), Some(0));
+        assert_eq!(mapping.column(1), Some(1));
+        assert_eq!(mapping.column(2), None);
+        assert_eq!(mapping.missing_required(Issue::IMPORT_FIELDS), ["严重程度"]);
+    }
+
+    #[test]
+    fn invalid_rows_are_rejected_with_every_reason() {
+        let store = Store::open_in_memory().unwrap();
+        let preview = preview(&store, &[
+            ["DTS001", "登录页面显示异常", "严重", "提交", ""],
+            ["DTS002", "", "超级严重", "提交", "2024-03-05"],
+            ["", "", "", "", ""],
+        ]);
+        assert_eq!(preview.rows.len(), 2);
+        assert_eq!(preview.valid().count(), 1);
+        assert_eq!(errors(&preview), [(3, vec![
+            "状态为提交的问题单不应有解决时间".to_string(),
+            "标题不能为空".to_string(),
+            "严重程度无法识别: 超级严重".to_string(),
+        ])]);
+    }
+
+    #[test]
+    fn repeated_ids_are_rejected() {
+        let store = Store::open_in_memory().unwrap();
+        let preview = preview(&store, &[
+            ["DTS001", "登录页面显示异常", "严重", "提交", ""],
+            ["DTS001", "数据库连接超时", "一般", "提交", ""],
+        ]);
+        assert_eq!(errors(&preview), [(3, vec!["ID 与第 2 行重复".to_string()])]);
+    }
+
+    #[test]
+    fn stored_ids_are_only_overwritten_on_request() {
+        let store = Store::open_in_memory().unwrap();
+        preview(&store, &[["DTS001", "登录页面显示异常", "严重", "提交", ""]])
+            .commit(&store, false)
+            .unwrap();
+
+        let preview = preview(&store, &[
+            ["DTS001", "登录页面显示异常", "严重", "修改中", ""],
+            ["DTS002", "数据库连接超时", "一般", "提交", ""],
+        ]);
+        assert_eq!(preview.conflicts().map(|row| row.row).collect::<Vec<_>>(), [2]);
+        assert_eq!(preview.importable(false), 1);
+        assert_eq!(preview.commit(&store, false).unwrap(), 1);
+        assert_eq!(store.issues().get("DTS001").unwrap().unwrap().status, IssueStatus::Submitted);
+
+        assert_eq!(preview.commit(&store, true).unwrap(), 2);
+        assert_eq!(store.issues().get("DTS001").unwrap().unwrap().status, IssueStatus::Fixing);
+        let history = store.issue_history("DTS001").unwrap();
+        let last = history.last().unwrap();
+        assert_eq!((last.from, last.to), (Some(IssueStatus::Submitted), IssueStatus::Fixing));
+        assert_eq!(last.actor, IMPORT_ACTOR);
+    }
+
+    #[test]
+    fn refused_status_change_of_a_stored_issue_is_rejected() {
+        let store = Store::open_in_memory().unwrap();
+        preview(&store, &[["DTS001", "登录页面显示异常", "严重", "提交", ""]])
+            .commit(&store, false)
+            .unwrap();
+
+        let preview = preview(&store, &[["DTS001", "登录页面显示异常", "严重", "关闭", ""]]);
+        assert_eq!(preview.conflicts().count(), 0);
+        assert_eq!(errors(&preview), [(2, vec![
+            "问题单状态不允许从「提交」变更为「关闭」".to_string()
+        ])]);
+    }
+
+    #[test]
+    fn failed_commit_writes_nothing() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .conn()
+            .execute_batch(
+                "CREATE TRIGGER refuse_dts002 BEFORE INSERT ON issues WHEN NEW.id = 'DTS002'
+                 BEGIN SELECT RAISE(ABORT, 'refused'); END",
+            )
+            .unwrap();
+
+        let preview = preview(&store, &[
+            ["DTS001", "登录页面显示异常", "严重", "提交", ""],
+            ["DTS002", "数据库连接超时", "一般", "提交", ""],
+        ]);
+        assert!(preview.commit(&store, false).is_err());
+        assert_eq!(store.issues().count().unwrap(), 0);
+        assert!(store.issue_history("DTS001").unwrap().is_empty());
+    }
+
+    #[test]
+    fn import_does_not_change_the_holder_of_a_leased_env() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .virtual_envs()
+            .upsert(&VirtualEnv {
+                id: "ENV-01".to_string(),
+                name: "测试环境 01".to_string(),
+                status: EnvStatus::Idle,
+                owner: None,
+                lease_until: None,
+                reason: None,
+            })
+            .unwrap();
+        let now = Utc::now();
+        store.reserve_env("ENV-01", "张三", "回归", TimeDelta::hours(2), now).unwrap();
+
+        let import = |rows: &[[&str; 4]]| {
+            let sheet = sheet_of("虚拟环境", &["ID *", "名称 *", "状态", "占用人"], rows);
+            let mapping = ColumnMapping::guess(VirtualEnv::IMPORT_FIELDS, &sheet.headers);
+            ImportPreview::<VirtualEnv>::new(&sheet, &mapping, &store).unwrap()
+        };
+        for row in [["ENV-01", "测试环境 01", "空闲", ""], ["ENV-01", "测试环境 01", "占用", "李四"]] {
+            let preview = import(&[row]);
+            let errors: Vec<Vec<String>> =
+                preview.rejected().map(|row| row.result.clone().unwrap_err()).collect();
+            assert_eq!(errors, [vec!["环境已被 张三 占用，不能修改状态或占用人".to_string()]]);
+        }
+
+        let renamed = import(&[["ENV-01", "回归环境", "占用", "张三"]]);
+        assert_eq!(renamed.commit(&store, true).unwrap(), 1);
+        let stored = store.virtual_envs().get("ENV-01").unwrap().unwrap();
+        assert_eq!(stored.name, "回归环境");
+        assert_eq!((stored.owner.as_deref(), stored.reason.as_deref()), (Some("张三"), Some("回归")));
+        assert_eq!(stored.lease_until, Some(now + TimeDelta::hours(2)));
+        assert_eq!(store.env_reservations("ENV-01").unwrap().len(), 1);
+    }
+}
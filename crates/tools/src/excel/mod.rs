@@ -1,2 +1,10 @@
+mod error;
+mod export;
+mod import;
 mod model;
+mod template;
+pub use error::*;
+pub use export::*;
+pub use import::*;
 pub use model::*;
+pub use template::*;
@@ -1,6 +1,377 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use calamine::{Data, Range, Reader, open_workbook_auto};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{ExcelError, TEMPLATE_META_SHEET, TemplateInfo, read_template_info};
+
+/// File extensions `ExcelTool` can open.
+pub const EXCEL_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
 pub struct ExcelTool {}
 impl ExcelTool {
     fn new() -> Self {
         Self {}
     }
+
+    /// Read the file's metadata and sheet names, without loading any sheet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Workbook, ExcelError> {
+        let path = path.as_ref();
+        let extension =
+            path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        if !EXCEL_EXTENSIONS.contains(&extension.as_str()) {
+            return Err(ExcelError::UnsupportedFormat(path.display().to_string()));
+        }
+
+        let metadata = std::fs::metadata(path)?;
+        let mut sheets = open_workbook_auto(path)?;
+        let template = read_template_info(&mut sheets);
+        let sheet_names =
+            sheets.sheet_names().into_iter().filter(|name| name != TEMPLATE_META_SHEET).collect();
+        Ok(Workbook {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified().ok().map(DateTime::<Local>::from),
+            sheet_names,
+            template,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Workbook {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<DateTime<Local>>,
+    /// Sheet names, without the hidden template metadata sheet.
+    pub sheet_names: Vec<String>,
+    pub template: Option<TemplateInfo>,
+}
+
+impl Workbook {
+    pub fn file_name(&self) -> String {
+        self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    pub fn file_type(&self) -> String {
+        self.path.extension().map(|ext| ext.to_string_lossy().to_uppercase()).unwrap_or_default()
+    }
+
+    /// Load one sheet. The file is reopened so a `Workbook` stays cheap to
+    /// clone and send to a background thread.
+    pub fn read_sheet(&self, name: &str) -> Result<Sheet, ExcelError> {
+        if !self.sheet_names.iter().any(|sheet| sheet == name) {
+            return Err(ExcelError::SheetNotFound(name.to_string()));
+        }
+        let range = open_workbook_auto(&self.path)?.worksheet_range(name)?;
+        Ok(Sheet::from_range(name, &range))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    DateTime(NaiveDateTime),
+    Error(String),
+}
+
+impl CellValue {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Empty => true,
+            Self::Text(text) => text.trim().is_empty(),
+            _ => false,
+        }
+    }
+
+    pub fn column_type(&self) -> ColumnType {
+        match self {
+            Self::Empty | Self::Error(_) => ColumnType::Empty,
+            Self::Text(_) if self.is_empty() => ColumnType::Empty,
+            Self::Bool(_) => ColumnType::Bool,
+            Self::Int(_) | Self::Float(_) => ColumnType::Number,
+            Self::Text(_) => ColumnType::Text,
+            Self::DateTime(_) => ColumnType::DateTime,
+        }
+    }
+}
+
+impl From<&Data> for CellValue {
+    fn from(data: &Data) -> Self {
+        match data {
+            Data::Empty => Self::Empty,
+            Data::Bool(value) => Self::Bool(*value),
+            Data::Int(value) => Self::Int(*value),
+            Data::Float(value) => Self::Float(*value),
+            Data::String(value) | Data::DurationIso(value) => Self::Text(value.clone()),
+            Data::DateTime(value) => match value.as_datetime() {
+                Some(datetime) if !value.is_duration() => Self::DateTime(datetime),
+                _ => Self::Float(value.as_f64()),
+            },
+            // `%.f` also accepts whole seconds, so times with and without
+            // fractional seconds both parse.
+            Data::DateTimeIso(value) => {
+                NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                            .map(|date| date.and_time(NaiveTime::MIN))
+                    })
+                    .map(Self::DateTime)
+                    .unwrap_or_else(|_| Self::Text(value.clone()))
+            },
+            Data::Error(err) => Self::Error(err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => Ok(()),
+            Self::Bool(value) => write!(f, "{}", if *value { "TRUE" } else { "FALSE" }),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Text(value) => f.write_str(value),
+            Self::DateTime(value) if value.time() == NaiveTime::MIN => {
+                write!(f, "{}", value.format("%Y-%m-%d"))
+            },
+            Self::DateTime(value) => write!(f, "{}", value.format("%Y-%m-%d %H:%M:%S")),
+            Self::Error(value) => write!(f, "#{}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Empty,
+    Bool,
+    Number,
+    Text,
+    DateTime,
+    Mixed,
+}
+
+impl ColumnType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Empty => "空",
+            Self::Bool => "布尔",
+            Self::Number => "数字",
+            Self::Text => "文本",
+            Self::DateTime => "日期",
+            Self::Mixed => "混合",
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Empty, other) | (other, Self::Empty) => other,
+            (left, right) if left == right => left,
+            _ => Self::Mixed,
+        }
+    }
+}
+
+/// A worksheet with its header row split off from the data rows.
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    pub name: String,
+    /// Header texts, or column letters when the sheet has no header row.
+    pub headers: Vec<String>,
+    pub column_types: Vec<ColumnType>,
+    pub rows: Vec<Vec<CellValue>>,
+    /// The 1-based spreadsheet row number of `rows[0]`.
+    pub first_row: usize,
+}
+
+impl Sheet {
+    pub(crate) fn from_range(name: &str, range: &Range<Data>) -> Self {
+        let (first_row, first_column) =
+            range.start().map(|(row, column)| (row as usize, column as usize)).unwrap_or_default();
+        let width = range.width();
+        let mut rows: Vec<Vec<CellValue>> =
+            range.rows().map(|row| row.iter().map(CellValue::from).collect()).collect();
+        let blank = rows.iter().take_while(|row| row.iter().all(CellValue::is_empty)).count();
+        rows.drain(..blank);
+
+        // A leading row of text followed by at least one more row is taken
+        // as the header.
+        let has_header = rows.len() > 1
+            && rows[0].iter().any(|cell| !cell.is_empty())
+            && rows[0].iter().all(|cell| cell.is_empty() || matches!(cell, CellValue::Text(_)));
+        let header_row = if has_header { Some(rows.remove(0)) } else { None };
+
+        let headers = (0..width)
+            .map(|index| {
+                header_row
+                    .as_ref()
+                    .and_then(|row| row.get(index))
+                    .filter(|cell| !cell.is_empty())
+                    .map(|cell| cell.to_string().trim().to_string())
+                    .unwrap_or_else(|| column_name(first_column + index))
+            })
+            .collect();
+
+        let column_types = (0..width)
+            .map(|index| {
+                rows.iter()
+                    .filter_map(|row| row.get(index))
+                    .fold(ColumnType::Empty, |ty, cell| ty.merge(cell.column_type()))
+            })
+            .collect();
+
+        let first_row = first_row + blank + usize::from(has_header) + 1;
+        Self { name: name.to_string(), headers, column_types, rows, first_row }
+    }
+}
+
+/// Spreadsheet column letters for a zero-based index: 0 → A, 26 → AA.
+pub fn column_name(index: usize) -> String {
+    let mut name = String::new();
+    let mut index = index + 1;
+    while index > 0 {
+        let rem = (index - 1) % 26;
+        name.insert(0, (b'A' + rem as u8) as char);
+        index = (index - 1) / 26;
+    }
+    name
+}
+
+pub fn format_file_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", size) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
+/// A file that was opened in the Excel view, kept as a recent-files entry.
+/// `id` is the file path and `created_at` the file's modification time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcelData {
+    pub id: String,
+    pub name: String,
+    pub file_type: String,
+    pub size: String,
+    pub created_at: String,
+    pub description: String,
+}
+
+impl From<&Workbook> for ExcelData {
+    fn from(workbook: &Workbook) -> Self {
+        Self {
+            id: workbook.path.display().to_string(),
+            name: workbook.file_name(),
+            file_type: workbook.file_type(),
+            size: format_file_size(workbook.size),
+            created_at: workbook
+                .modified
+                .map(|modified| modified.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+            description: workbook.sheet_names.join("、"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Data {
+        Data::String(value.to_string())
+    }
+
+    fn range(start: (u32, u32), rows: &[&[Data]]) -> Range<Data> {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(1) as u32;
+        let end = (start.0 + rows.len() as u32 - 1, start.1 + width - 1);
+        let mut range = Range::new(start, end);
+        for (row, cells) in rows.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate() {
+                range.set_value((start.0 + row as u32, start.1 + column as u32), cell.clone());
+            }
+        }
+        range
+    }
+
+    #[test]
+    fn column_names_continue_past_z() {
+        let names: Vec<String> = [0, 25, 26, 51, 52, 701, 702, 16383].map(column_name).into();
+        assert_eq!(names, ["A", "Z", "AA", "AZ", "BA", "ZZ", "AAA", "XFD"]);
+    }
+
+    #[test]
+    fn column_types_merge_to_mixed_only_on_a_conflict() {
+        use ColumnType::*;
+        for ty in [Empty, Bool, Number, Text, DateTime, Mixed] {
+            assert_eq!(Empty.merge(ty), ty);
+            assert_eq!(ty.merge(Empty), ty);
+            assert_eq!(ty.merge(ty), ty);
+        }
+        assert_eq!(Number.merge(Text), Mixed);
+        assert_eq!(DateTime.merge(Number), Mixed);
+        assert_eq!(Mixed.merge(Bool), Mixed);
+    }
+
+    #[test]
+    fn from_range_splits_off_the_header_and_infers_column_types() {
+        let date = Data::DateTimeIso("2024-03-05T08:30:00".to_string());
+        let cells = range((2, 0), &[
+            &[Data::Empty, Data::Empty, Data::Empty],
+            &[text("ID"), text(" 数量 "), Data::Empty],
+            &[text("A-1"), Data::Int(3), date],
+            &[text("A-2"), Data::Float(1.5), text("无")],
+            &[text("A-3"), text(" "), Data::Empty],
+        ]);
+        let sheet = Sheet::from_range("用例", &cells);
+
+        assert_eq!(sheet.headers, ["ID", "数量", "C"]);
+        assert_eq!(sheet.column_types, [ColumnType::Text, ColumnType::Number, ColumnType::Mixed]);
+        assert_eq!(sheet.rows.len(), 3);
+        // Two rows above the range, one blank row and the header.
+        assert_eq!(sheet.first_row, 5);
+    }
+
+    #[test]
+    fn from_range_without_a_header_names_columns_by_letter() {
+        let cells = range((0, 26), &[&[Data::Int(1), text("a")], &[Data::Int(2), text("b")]]);
+        let sheet = Sheet::from_range("数据", &cells);
+
+        assert_eq!(sheet.headers, ["AA", "AB"]);
+        assert_eq!(sheet.column_types, [ColumnType::Number, ColumnType::Text]);
+        assert_eq!((sheet.rows.len(), sheet.first_row), (2, 1));
+
+        // A single text row is data, not a header without rows.
+        let single = Sheet::from_range("数据", &range((0, 0), &[&[text("ID")]]));
+        assert_eq!((single.headers, single.rows.len()), (vec!["A".to_string()], 1));
+    }
+
+    #[test]
+    fn iso_date_times_keep_fractional_seconds() {
+        let cell = |value: &str| CellValue::from(&Data::DateTimeIso(value.to_string()));
+        let at = |millis| {
+            NaiveDate::from_ymd_opt(2024, 3, 5)
+                .unwrap()
+                .and_hms_milli_opt(8, 30, 15, millis)
+                .unwrap()
+        };
+        assert_eq!(cell("2024-03-05T08:30:15"), CellValue::DateTime(at(0)));
+        assert_eq!(cell("2024-03-05T08:30:15.250"), CellValue::DateTime(at(250)));
+        assert_eq!(
+            cell("2024-03-05"),
+            CellValue::DateTime(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap().into())
+        );
+        assert_eq!(cell("下周"), CellValue::Text("下周".to_string()));
+        assert_eq!(cell("2024-03-05T08:30:15.250").column_type(), ColumnType::DateTime);
+    }
 }
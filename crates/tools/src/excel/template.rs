@@ -0,0 +1,298 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use calamine::{Reader, Sheets};
+use chrono::{Local, NaiveDate, NaiveTime};
+use rust_xlsxwriter::{DataValidation, Format, Workbook as XlsxWorkbook};
+
+use crate::{
+    CellValue, ExcelError, ExcelTool, FieldKind, Importable, Issue, MergeRequest, Requirement,
+    VirtualEnv, Workbook, write_table,
+};
+
+/// The hidden sheet carrying the template kind and version.
+pub const TEMPLATE_META_SHEET: &str = "_meta";
+/// How many rows the dropdown validations cover.
+const TEMPLATE_ROWS: u32 = 1000;
+
+/// A record type with a downloadable import template.
+pub trait Template: Importable {
+    const TEMPLATE_NAME: &'static str;
+    /// Bump when this template's columns change, so imports of files made
+    /// from an older template can warn about it.
+    const TEMPLATE_VERSION: u32;
+
+    /// Example rows, one cell per import field.
+    fn examples() -> Vec<Vec<CellValue>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateInfo {
+    /// The table name of the record type the template was made for.
+    pub kind: String,
+    pub version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateCheck {
+    /// The file was not made from a template, nothing to check.
+    NotTemplate,
+    Current,
+    Outdated(u32),
+    WrongKind(String),
+}
+
+impl ExcelTool {
+    /// Write an empty import template for `T`: headers with required fields
+    /// marked `*`, dropdowns for choice fields, and a few example rows.
+    pub fn write_template<T: Template>(path: impl AsRef<Path>) -> Result<(), ExcelError> {
+        let mut workbook = XlsxWorkbook::new();
+        let header = Format::new().set_bold().set_background_color("#E5E7EB");
+        let date = Format::new().set_num_format("yyyy-mm-dd");
+
+        let headers: Vec<String> = T::IMPORT_FIELDS
+            .iter()
+            .map(|field| {
+                if field.required { format!("{} *", field.label) } else { field.label.to_string() }
+            })
+            .collect();
+        let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+
+        let sheet = workbook.add_worksheet().set_name(T::TEMPLATE_NAME)?;
+        write_table(sheet, &header, &headers, &T::examples())?;
+        for (column, field) in T::IMPORT_FIELDS.iter().enumerate() {
+            let column = column as u16;
+            match field.kind {
+                FieldKind::Choice => {
+                    let validation = DataValidation::new()
+                        .allow_list_strings(&T::choices(field.name))?
+                        .set_error_title(field.label)?
+                        .set_error_message(format!("请从下拉列表中选择{}", field.label))?;
+                    sheet.add_data_validation(1, column, TEMPLATE_ROWS, column, &validation)?;
+                },
+                FieldKind::Date => _ = sheet.set_column_format(column, &date)?,
+                FieldKind::Text | FieldKind::Number => {},
+            }
+        }
+
+        let meta = workbook.add_worksheet().set_name(TEMPLATE_META_SHEET)?;
+        meta.write_string(0, 0, "template")?;
+        meta.write_string(0, 1, T::NAME)?;
+        meta.write_string(1, 0, "version")?;
+        meta.write_number(1, 1, T::TEMPLATE_VERSION)?;
+        meta.set_hidden(true);
+
+        workbook.save(path.as_ref())?;
+        Ok(())
+    }
+}
+
+impl Workbook {
+    /// The sheet to import `T` from: the one named after its template if the
+    /// file has it, otherwise the first.
+    pub fn import_sheet<T: Template>(&self) -> Option<&str> {
+        self.sheet_names
+            .iter()
+            .find(|name| *name == T::TEMPLATE_NAME)
+            .or_else(|| self.sheet_names.first())
+            .map(String::as_str)
+    }
+
+    pub fn check_template<T: Template>(&self) -> TemplateCheck {
+        match &self.template {
+            None => TemplateCheck::NotTemplate,
+            Some(info) if info.kind != T::NAME => TemplateCheck::WrongKind(info.kind.clone()),
+            Some(info) if info.version < T::TEMPLATE_VERSION => {
+                TemplateCheck::Outdated(info.version)
+            },
+            Some(_) => TemplateCheck::Current,
+        }
+    }
+
+    /// Refuse files made from another record type's template or from an
+    /// older version of `T`'s, whose columns may no longer match.
+    pub fn ensure_template<T: Template>(&self) -> Result<(), ExcelError> {
+        match self.check_template::<T>() {
+            TemplateCheck::NotTemplate | TemplateCheck::Current => Ok(()),
+            TemplateCheck::Outdated(version) => {
+                Err(ExcelError::OutdatedTemplate { name: T::TEMPLATE_NAME, version })
+            },
+            TemplateCheck::WrongKind(kind) => {
+                Err(ExcelError::WrongTemplate { name: T::TEMPLATE_NAME, kind })
+            },
+        }
+    }
+}
+
+pub(crate) fn read_template_info(sheets: &mut Sheets<BufReader<File>>) -> Option<TemplateInfo> {
+    let range = sheets.worksheet_range(TEMPLATE_META_SHEET).ok()?;
+    let value = |key: &str| {
+        range
+            .rows()
+            .find(|row| row.first().is_some_and(|cell| *cell == key))
+            .and_then(|row| row.get(1))
+            .map(|cell| cell.to_string())
+    };
+    Some(TemplateInfo { kind: value("template")?, version: value("version")?.parse().ok()? })
+}
+
+fn date(year: i32, month: u32, day: u32) -> CellValue {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .map(|date| CellValue::DateTime(date.and_time(NaiveTime::MIN)))
+        .unwrap_or(CellValue::Empty)
+}
+
+fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+}
+
+impl Template for Requirement {
+    const TEMPLATE_NAME: &'static str = "需求";
+    const TEMPLATE_VERSION: u32 = 4;
+
+    fn examples() -> Vec<Vec<CellValue>> {
+        vec![
+            vec![
+                text("REQ-001"),
+                text("用户登录支持扫码"),
+                text("V1.2.0"),
+                date(2026, 1, 5),
+                date(2026, 1, 15),
+                text("进行中"),
+                text("张三"),
+            ],
+            vec![
+                text("REQ-002"),
+                text("导出报表增加筛选"),
+                text("V1.2.0"),
+                date(2026, 1, 12),
+                date(2026, 1, 17),
+                text("未开始"),
+                text("李四"),
+            ],
+        ]
+    }
+}
+
+impl Template for Issue {
+    const TEMPLATE_NAME: &'static str = "问题单";
+    const TEMPLATE_VERSION: u32 = 3;
+
+    fn examples() -> Vec<Vec<CellValue>> {
+        let now = CellValue::DateTime(Local::now().naive_local().date().and_time(NaiveTime::MIN));
+        vec![
+            vec![
+                text("DTS-0001"),
+                text("登录页偶现白屏"),
+                text("严重"),
+                text("提交"),
+                text("张三"),
+                now.clone(),
+                CellValue::Empty,
+                CellValue::Int(0),
+            ],
+            vec![
+                text("DTS-0002"),
+                text("导出文件名乱码"),
+                text("一般"),
+                text("回归"),
+                text("李四"),
+                date(2026, 1, 5),
+                now,
+                CellValue::Int(1),
+            ],
+        ]
+    }
+}
+
+impl Template for MergeRequest {
+    const TEMPLATE_NAME: &'static str = "MR";
+    const TEMPLATE_VERSION: u32 = 3;
+
+    fn examples() -> Vec<Vec<CellValue>> {
+        vec![vec![
+            text("MR-1024"),
+            text("修复登录页白屏"),
+            text("张三"),
+            text("portal-web"),
+            date(2026, 1, 6),
+            CellValue::Int(120),
+            CellValue::Int(35),
+            text("已合入"),
+            date(2026, 1, 8),
+        ]]
+    }
+}
+
+impl Template for VirtualEnv {
+    const TEMPLATE_NAME: &'static str = "虚拟环境";
+    const TEMPLATE_VERSION: u32 = 3;
+
+    fn examples() -> Vec<Vec<CellValue>> {
+        vec![vec![text("ENV-01"), text("测试环境 01"), text("空闲"), CellValue::Empty], vec![
+            text("ENV-02"),
+            text("测试环境 02"),
+            text("占用"),
+            text("张三"),
+        ]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Table;
+
+    fn write_and_open<T: Template>(file: &str) -> Workbook {
+        let dir = std::env::temp_dir().join(format!("tasks-mine-template-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(file);
+        ExcelTool::write_template::<T>(&path).unwrap();
+        ExcelTool::open(&path).unwrap()
+    }
+
+    #[test]
+    fn template_carries_its_kind_and_version_in_the_meta_sheet() {
+        let workbook = write_and_open::<Issue>("issue.xlsx");
+        assert_eq!(
+            workbook.template,
+            Some(TemplateInfo { kind: Issue::NAME.to_string(), version: Issue::TEMPLATE_VERSION })
+        );
+        assert_eq!(workbook.sheet_names, [Issue::TEMPLATE_NAME]);
+        assert_eq!(workbook.import_sheet::<Issue>(), Some(Issue::TEMPLATE_NAME));
+        assert_eq!(workbook.check_template::<Issue>(), TemplateCheck::Current);
+        assert!(workbook.ensure_template::<Issue>().is_ok());
+
+        let sheet = workbook.read_sheet(Issue::TEMPLATE_NAME).unwrap();
+        assert_eq!(sheet.headers[0], "ID *");
+        assert_eq!(sheet.rows.len(), Issue::examples().len());
+    }
+
+    #[test]
+    fn stale_template_is_rejected() {
+        let mut workbook = write_and_open::<MergeRequest>("mr.xlsx");
+        let stale = MergeRequest::TEMPLATE_VERSION - 1;
+        workbook.template.as_mut().unwrap().version = stale;
+        assert_eq!(workbook.check_template::<MergeRequest>(), TemplateCheck::Outdated(stale));
+        assert!(matches!(
+            workbook.ensure_template::<MergeRequest>(),
+            Err(ExcelError::OutdatedTemplate { version, .. }) if version == stale
+        ));
+    }
+
+    #[test]
+    fn template_of_another_kind_is_rejected() {
+        let workbook = write_and_open::<VirtualEnv>("env.xlsx");
+        assert!(matches!(
+            workbook.ensure_template::<Requirement>(),
+            Err(ExcelError::WrongTemplate { kind, .. }) if kind == VirtualEnv::NAME
+        ));
+    }
+
+    #[test]
+    fn plain_workbook_is_not_a_template() {
+        let mut workbook = write_and_open::<Requirement>("req.xlsx");
+        workbook.template = None;
+        assert_eq!(workbook.check_template::<Requirement>(), TemplateCheck::NotTemplate);
+        assert!(workbook.ensure_template::<Requirement>().is_ok());
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use crate::{HiveTool, Store, TestCase};
+
+/// How many earlier runs are looked at to tell flaky cases; older ones are
+/// dropped.
+pub const FLAKY_WINDOW: usize = 5;
+
+/// Failed cases sharing one error signature, most likely one root cause.
+#[derive(Debug, Clone)]
+pub struct FailureCluster {
+    /// The normalized first line of the error, empty for cases without an
+    /// error message.
+    pub signature: String,
+    pub cases: Vec<TestCase>,
+    /// The signature did not occur in the previous run.
+    pub new: bool,
+    /// Cases of the cluster that failed, were recorded as passed and failed
+    /// again over the recent runs.
+    pub flaky_cases: Vec<String>,
+}
+
+impl FailureCluster {
+    pub fn count(&self) -> usize {
+        self.cases.len()
+    }
+
+    pub fn is_flaky(&self) -> bool {
+        !self.flaky_cases.is_empty()
+    }
+
+    /// The raw message of one of the cases, to show what the signature
+    /// stands for.
+    pub fn example(&self) -> Option<&str> {
+        self.cases.iter().find_map(|case| case.error_msg.as_deref())
+    }
+}
+
+/// The outcome of one "analyze failures" run.
+#[derive(Debug, Clone)]
+pub struct FailureAnalysis {
+    pub analyzed_at: DateTime<Utc>,
+    /// When the run compared against was made, `None` on the first run, in
+    /// which case no cluster is marked new.
+    pub previous_run_at: Option<DateTime<Utc>>,
+    pub clusters: Vec<FailureCluster>,
+}
+
+impl TestCase {
+    pub fn is_failed(&self) -> bool {
+        self.status == "失败"
+    }
+
+    pub fn is_passed(&self) -> bool {
+        matches!(self.status.as_str(), "通过" | "成功")
+    }
+
+    /// The cluster signature of the case's error, see
+    /// [`HiveTool::normalize_error`].
+    pub fn signature(&self) -> String {
+        let message = self.error_msg.as_deref().unwrap_or_default();
+        let line = message.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("");
+        HiveTool::normalize_error(line)
+    }
+}
+
+impl HiveTool {
+    /// Strip what differs between two occurrences of the same error:
+    /// timestamps become `<time>`, addresses `<addr>`, ids and hashes `<id>`
+    /// and other numbers `<n>`. Whitespace is collapsed.
+    pub fn normalize_error(message: &str) -> String {
+        let mut normalized = String::with_capacity(message.len());
+        let mut token = String::new();
+        for ch in message.chars() {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | ':' | '.' | '/') {
+                token.push(ch);
+            } else {
+                push_token(&mut normalized, &token);
+                token.clear();
+                if ch.is_whitespace() {
+                    if !normalized.ends_with(' ') && !normalized.is_empty() {
+                        normalized.push(' ');
+                    }
+                } else {
+                    normalized.push(ch);
+                }
+            }
+        }
+        push_token(&mut normalized, &token);
+
+        // A date and a time written apart are still one timestamp.
+        while normalized.contains("<time> <time>") {
+            normalized = normalized.replace("<time> <time>", "<time>");
+        }
+        normalized.trim().to_string()
+    }
+
+    /// Group failed cases by signature, the largest cluster first.
+    pub fn cluster_failures(cases: &[TestCase]) -> Vec<FailureCluster> {
+        let mut clusters: Vec<FailureCluster> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for case in cases.iter().filter(|case| case.is_failed()) {
+            let signature = case.signature();
+            let at = *index.entry(signature.clone()).or_insert_with(|| {
+                clusters.push(FailureCluster {
+                    signature,
+                    cases: Vec::new(),
+                    new: false,
+                    flaky_cases: Vec::new(),
+                });
+                clusters.len() - 1
+            });
+            clusters[at].cases.push(case.clone());
+        }
+        clusters
+            .sort_by(|a, b| b.count().cmp(&a.count()).then_with(|| a.signature.cmp(&b.signature)));
+        clusters
+    }
+}
+
+/// Trailing punctuation that ends a sentence rather than a token.
+const TRAILING: &[char] = &['.', ':', '-', '/'];
+
+fn push_token(out: &mut String, token: &str) {
+    let core = token.trim_end_matches(TRAILING);
+    if !core.is_empty() {
+        out.push_str(&normalize_token(core));
+    }
+    out.push_str(&token[core.len()..]);
+}
+
+fn normalize_token(token: &str) -> String {
+    let digits = token.chars().filter(char::is_ascii_digit).count();
+    if digits == 0 {
+        return token.to_string();
+    }
+    let hex = |c: char| c.is_ascii_hexdigit();
+    if let Some(rest) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X"))
+        && !rest.is_empty()
+        && rest.chars().all(hex)
+    {
+        return "<addr>".to_string();
+    }
+    // An IPv4 address, with or without a port.
+    let host = token.split(':').next().unwrap_or(token);
+    if host.split('.').count() == 4
+        && host.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return "<addr>".to_string();
+    }
+    // Dates and times such as 2024-05-01, 2024/05/01, 12:30:45.123 or
+    // 2024-05-01T12:30:45Z.
+    if digits >= 4
+        && token.contains(['-', '/', ':'])
+        && token
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | '/' | ':' | '.' | 'T' | 'Z'))
+    {
+        return "<time>".to_string();
+    }
+    // UUIDs and hashes.
+    if token.chars().filter(|c| hex(*c)).count() >= 8 && token.chars().all(|c| hex(c) || c == '-') {
+        return "<id>".to_string();
+    }
+    // Anything else keeps its letters, with each run of digits masked, so
+    // `worker-17` and `worker-3` match.
+    let mut masked = String::with_capacity(token.len());
+    let mut in_digits = false;
+    for ch in token.chars() {
+        if ch.is_ascii_digit() {
+            if !in_digits {
+                masked.push_str("<n>");
+            }
+            in_digits = true;
+        } else {
+            masked.push(ch);
+            in_digits = false;
+        }
+    }
+    masked
+}
+
+/// A run's recorded results: the signature of each failed case, `None` for
+/// each passed case. Cases with any other status are left out, so nothing
+/// is known about them for that run.
+type RunResults = HashMap<String, Option<String>>;
+
+/// Test results are kept as runs whenever failures are analyzed, which is
+/// what "new" and "flaky" are judged against. Results equal to the latest
+/// run's don't add a run, so analyzing again keeps comparing with the run
+/// before.
+impl Store {
+    pub fn analyze_failures(
+        &self,
+        cases: &[TestCase],
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<FailureAnalysis> {
+        let mut clusters = HiveTool::cluster_failures(cases);
+        let analyzed_at = self.record_run(&run_results(cases), now)?;
+        // The first run is the current one.
+        let runs = self.failure_runs(FLAKY_WINDOW + 1)?;
+        let earlier = runs.get(1..).unwrap_or_default();
+
+        let previous_run_at = earlier.first().map(|(at, _)| *at);
+        let previous: Option<HashSet<&str>> = earlier
+            .first()
+            .map(|(_, results)| results.values().flatten().map(String::as_str).collect());
+        // Earlier runs, oldest first; the current run is not included.
+        let history: Vec<&RunResults> = earlier.iter().rev().map(|(_, results)| results).collect();
+
+        for cluster in &mut clusters {
+            cluster.new = previous
+                .as_ref()
+                .is_some_and(|previous| !previous.contains(cluster.signature.as_str()));
+            cluster.flaky_cases = cluster
+                .cases
+                .iter()
+                .filter(|case| {
+                    // Failed before, then recorded as passed at some later
+                    // run. A run without the case says nothing either way.
+                    let mut failed = false;
+                    history.iter().any(|run| match run.get(&case.id) {
+                        Some(Some(_)) => {
+                            failed = true;
+                            false
+                        },
+                        Some(None) => failed,
+                        None => false,
+                    })
+                })
+                .map(|case| case.id.clone())
+                .collect();
+        }
+        Ok(FailureAnalysis { analyzed_at, previous_run_at, clusters })
+    }
+
+    /// Save `results` as a new run unless they equal the latest run's, and
+    /// drop runs too old to matter. Returns when the run holding `results`
+    /// was made.
+    fn record_run(
+        &self,
+        results: &RunResults,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<DateTime<Utc>> {
+        if let Some((at, latest)) = self.failure_runs(1)?.pop()
+            && latest == *results
+        {
+            return Ok(at);
+        }
+        self.transaction(|tx| {
+            tx.conn().execute("INSERT INTO failure_runs (analyzed_at) VALUES (?1)", [now])?;
+            let run_id = tx.conn().last_insert_rowid();
+            let mut stmt = tx.conn().prepare_cached(
+                "INSERT INTO failure_run_cases (run_id, case_id, signature, passed) VALUES (?1, \
+                 ?2, ?3, ?4)",
+            )?;
+            for (case_id, signature) in results {
+                stmt.execute(params![
+                    run_id,
+                    case_id,
+                    signature.as_deref().unwrap_or_default(),
+                    signature.is_none()
+                ])?;
+            }
+            // The cases go with their run.
+            tx.conn().execute(
+                "DELETE FROM failure_runs WHERE id NOT IN (SELECT id FROM failure_runs ORDER BY \
+                 analyzed_at DESC, id DESC LIMIT ?1)",
+                [(FLAKY_WINDOW + 1) as i64],
+            )?;
+            Ok(())
+        })?;
+        Ok(now)
+    }
+
+    /// The latest `limit` runs, newest first.
+    fn failure_runs(&self, limit: usize) -> anyhow::Result<Vec<(DateTime<Utc>, RunResults)>> {
+        let conn = self.conn();
+        let runs: Vec<(i64, DateTime<Utc>)> = conn
+            .prepare_cached(
+                "SELECT id, analyzed_at FROM failure_runs ORDER BY analyzed_at DESC, id DESC \
+                 LIMIT ?1",
+            )?
+            .query_map([limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT case_id, signature, passed FROM failure_run_cases WHERE run_id = ?1",
+        )?;
+        runs.into_iter()
+            .map(|(id, at)| {
+                let results = stmt
+                    .query_map([id], |row| {
+                        let passed: bool = row.get(2)?;
+                        Ok((row.get(0)?, (!passed).then_some(row.get(1)?)))
+                    })?
+                    .collect::<rusqlite::Result<_>>()?;
+                Ok((at, results))
+            })
+            .collect()
+    }
+}
+
+fn run_results(cases: &[TestCase]) -> RunResults {
+    cases
+        .iter()
+        .filter_map(|case| {
+            if case.is_failed() {
+                Some((case.id.clone(), Some(case.signature())))
+            } else if case.is_passed() {
+                Some((case.id.clone(), None))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone as _;
+
+    use super::*;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 4, 1, hour, 0, 0).unwrap()
+    }
+
+    fn case(id: &str, status: &str, error_msg: Option<&str>) -> TestCase {
+        TestCase {
+            id: id.to_string(),
+            name: id.to_string(),
+            status: status.to_string(),
+            error_msg: error_msg.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn normalize_error_masks_what_varies() {
+        assert_eq!(
+            HiveTool::normalize_error("2024-05-01 12:30:45.123 timeout after 30s"),
+            "<time> timeout after <n>s"
+        );
+        assert_eq!(
+            HiveTool::normalize_error("connect to 10.0.0.12:8080 failed at 0x7ffee4a1"),
+            "connect to <addr> failed at <addr>"
+        );
+        assert_eq!(
+            HiveTool::normalize_error("session 3f2a9c1e-77b0-4d2e-9a51-0c6b2e8d4f10 not found"),
+            "session <id> not found"
+        );
+        assert_eq!(
+            HiveTool::normalize_error("worker-17   lost \t heartbeat"),
+            "worker-<n> lost heartbeat"
+        );
+    }
+
+    #[test]
+    fn normalize_error_keeps_trailing_punctuation() {
+        assert_eq!(
+            HiveTool::normalize_error("expected 3 rows, got 4."),
+            "expected <n> rows, got <n>."
+        );
+        assert_eq!(HiveTool::normalize_error("assertion failed:"), "assertion failed:");
+        assert_eq!(HiveTool::normalize_error(""), "");
+    }
+
+    #[test]
+    fn same_error_with_different_details_clusters_together() {
+        let clusters = HiveTool::cluster_failures(&[
+            case("a", "失败", Some("timeout after 30s on worker-1\nstack")),
+            case("b", "失败", Some("timeout after 45s on worker-9")),
+            case("c", "失败", None),
+            case("d", "通过", None),
+        ]);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].signature, "timeout after <n>s on worker-<n>");
+        assert_eq!(clusters[0].count(), 2);
+        assert_eq!(clusters[1].signature, "");
+    }
+
+    #[test]
+    fn new_clusters_are_judged_against_the_previous_run() {
+        let store = Store::open_in_memory().unwrap();
+        let first = store.analyze_failures(&[case("a", "失败", Some("timeout"))], at(1)).unwrap();
+        assert_eq!(first.previous_run_at, None);
+        assert!(!first.clusters[0].new);
+
+        let cases = [case("a", "失败", Some("timeout")), case("b", "失败", Some("refused"))];
+        let second = store.analyze_failures(&cases, at(2)).unwrap();
+        assert_eq!(second.previous_run_at, Some(at(1)));
+        let new: Vec<bool> = second.clusters.iter().map(|cluster| cluster.new).collect();
+        assert_eq!(new, [true, false]);
+
+        // Unchanged results don't add a run.
+        let again = store.analyze_failures(&cases, at(3)).unwrap();
+        assert_eq!(again.analyzed_at, at(2));
+        assert_eq!(again.previous_run_at, Some(at(1)));
+    }
+
+    #[test]
+    fn only_a_recorded_pass_makes_a_case_flaky() {
+        let store = Store::open_in_memory().unwrap();
+        let failed = [case("a", "失败", Some("timeout")), case("b", "失败", Some("timeout"))];
+        store.analyze_failures(&failed, at(1)).unwrap();
+        // `a` passed, `b` was not run at all.
+        store.analyze_failures(&[case("a", "通过", None)], at(2)).unwrap();
+
+        let analysis = store.analyze_failures(&failed, at(3)).unwrap();
+        assert_eq!(analysis.clusters[0].flaky_cases, ["a"]);
+    }
+
+    #[test]
+    fn old_runs_are_dropped() {
+        let store = Store::open_in_memory().unwrap();
+        for hour in 0..10 {
+            let status = if hour % 2 == 0 { "失败" } else { "通过" };
+            store.analyze_failures(&[case("a", status, Some("timeout"))], at(hour)).unwrap();
+        }
+        let runs = store.failure_runs(FLAKY_WINDOW * 2).unwrap();
+        assert_eq!(runs.len(), FLAKY_WINDOW + 1);
+        assert_eq!(runs[0].0, at(9));
+        let cases: i64 = store
+            .conn()
+            .query_row("SELECT COUNT(*) FROM failure_run_cases", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cases, (FLAKY_WINDOW + 1) as i64);
+    }
+}
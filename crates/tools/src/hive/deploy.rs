@@ -0,0 +1,375 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::{HiveTool, VirtualEnv};
+
+/// The steps of a deployment, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeployStep {
+    FetchImage,
+    Flash,
+    Configure,
+    HealthCheck,
+}
+
+impl DeployStep {
+    pub const ALL: &'static [Self] =
+        &[Self::FetchImage, Self::Flash, Self::Configure, Self::HealthCheck];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::FetchImage => "获取镜像",
+            Self::Flash => "烧录",
+            Self::Configure => "配置",
+            Self::HealthCheck => "健康检查",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|step| step == self).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepState {
+    Pending,
+    Running,
+    Succeeded,
+    Failed(String),
+    Cancelled,
+}
+
+/// A line of output from a step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeployLog {
+    pub step: DeployStep,
+    pub at: DateTime<Utc>,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DeployError {
+    #[error("部署已取消")]
+    Cancelled,
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// What a running deployment reports back, in order.
+#[derive(Debug, Clone)]
+enum DeployEvent {
+    Started(DeployStep),
+    Log(DeployLog),
+    Finished(DeployStep, Result<(), DeployError>),
+}
+
+/// Where a deployment actually happens. A backend runs one step at a time on
+/// a worker thread, so it may block; long waits should go through
+/// [`StepContext::wait`] so that cancelling takes effect promptly.
+pub trait DeployBackend: Send + Sync + 'static {
+    fn run_step(
+        &self,
+        env: &VirtualEnv,
+        step: DeployStep,
+        cx: &StepContext,
+    ) -> Result<(), DeployError>;
+}
+
+/// Handed to [`DeployBackend::run_step`] to stream logs and notice cancels.
+pub struct StepContext {
+    step: DeployStep,
+    events: Sender<DeployEvent>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl StepContext {
+    pub fn log(&self, line: impl Into<String>) {
+        // The deployment may have been dropped; its logs then go nowhere.
+        _ = self.events.send(DeployEvent::Log(DeployLog {
+            step: self.step,
+            at: Utc::now(),
+            line: line.into(),
+        }));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Sleep for `duration`, returning early with [`DeployError::Cancelled`]
+    /// if the deployment is cancelled meanwhile.
+    pub fn wait(&self, duration: Duration) -> Result<(), DeployError> {
+        let deadline = Instant::now() + duration;
+        loop {
+            if self.is_cancelled() {
+                return Err(DeployError::Cancelled);
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Ok(());
+            }
+            thread::sleep(left.min(Duration::from_millis(50)));
+        }
+    }
+}
+
+impl HiveTool {
+    /// Deploy `env` through `backend`, step by step.
+    pub fn deploy(backend: Arc<dyn DeployBackend>, env: VirtualEnv) -> Deployment {
+        Deployment::start(backend, env)
+    }
+}
+
+/// How a finished deployment ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeployOutcome {
+    Succeeded,
+    Failed(DeployStep, String),
+    Cancelled(DeployStep),
+}
+
+/// One deployment of an environment: the state of every step and all logs,
+/// kept across retries. Steps run on a worker thread; [`Deployment::poll`]
+/// brings their progress in.
+pub struct Deployment {
+    pub env: VirtualEnv,
+    pub steps: Vec<(DeployStep, StepState)>,
+    pub logs: Vec<DeployLog>,
+    run: Option<Run>,
+}
+
+struct Run {
+    cancel: Arc<AtomicBool>,
+    events: Receiver<DeployEvent>,
+}
+
+impl Deployment {
+    /// Start deploying `env` from the first step.
+    pub fn start(backend: Arc<dyn DeployBackend>, env: VirtualEnv) -> Self {
+        let mut deployment = Self {
+            env,
+            steps: DeployStep::ALL.iter().map(|step| (*step, StepState::Pending)).collect(),
+            logs: Vec::new(),
+            run: None,
+        };
+        deployment.run_from(backend, DeployStep::FetchImage);
+        deployment
+    }
+
+    /// Run again from the step that failed or was cancelled, keeping the
+    /// steps before it. Returns `false` when there is nothing to retry.
+    pub fn retry(&mut self, backend: Arc<dyn DeployBackend>) -> bool {
+        match self.resume_step() {
+            Some(step) if !self.is_running() => {
+                self.run_from(backend, step);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Ask the running step to stop; the remaining steps are not run.
+    pub fn cancel(&self) {
+        if let Some(run) = &self.run {
+            run.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// The first step that didn't succeed, if the last run stopped early.
+    pub fn resume_step(&self) -> Option<DeployStep> {
+        self.steps
+            .iter()
+            .find(|(_, state)| matches!(state, StepState::Failed(_) | StepState::Cancelled))
+            .map(|(step, _)| *step)
+    }
+
+    /// How the deployment ended, `None` while it is still running.
+    pub fn outcome(&self) -> Option<DeployOutcome> {
+        if self.is_running() {
+            return None;
+        }
+        self.steps
+            .iter()
+            .find_map(|(step, state)| match state {
+                StepState::Failed(message) => Some(DeployOutcome::Failed(*step, message.clone())),
+                StepState::Cancelled => Some(DeployOutcome::Cancelled(*step)),
+                StepState::Pending | StepState::Running => Some(DeployOutcome::Cancelled(*step)),
+                StepState::Succeeded => None,
+            })
+            .or(Some(DeployOutcome::Succeeded))
+    }
+
+    /// Take in everything the worker reported since the last poll. Returns
+    /// whether anything changed.
+    pub fn poll(&mut self) -> bool {
+        let Some(run) = &self.run else {
+            return false;
+        };
+        let mut changed = false;
+        loop {
+            match run.events.try_recv() {
+                Ok(event) => {
+                    changed = true;
+                    match event {
+                        DeployEvent::Started(step) => {
+                            set_state(&mut self.steps, step, StepState::Running)
+                        },
+                        DeployEvent::Log(log) => self.logs.push(log),
+                        DeployEvent::Finished(step, result) => {
+                            set_state(&mut self.steps, step, match result {
+                                Ok(()) => StepState::Succeeded,
+                                Err(DeployError::Cancelled) => StepState::Cancelled,
+                                Err(DeployError::Failed(message)) => StepState::Failed(message),
+                            })
+                        },
+                    }
+                },
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.run = None;
+                    changed = true;
+                    break;
+                },
+            }
+        }
+        changed
+    }
+
+    fn run_from(&mut self, backend: Arc<dyn DeployBackend>, first: DeployStep) {
+        let steps: Vec<DeployStep> = DeployStep::ALL[first.index()..].to_vec();
+        for step in &steps {
+            set_state(&mut self.steps, *step, StepState::Pending);
+        }
+        let (sender, events) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let env = self.env.clone();
+        let run_cancel = cancel.clone();
+        thread::Builder::new()
+            .name(format!("deploy-{}", env.id))
+            .spawn(move || {
+                for step in steps {
+                    let cx =
+                        StepContext { step, events: sender.clone(), cancel: run_cancel.clone() };
+                    _ = sender.send(DeployEvent::Started(step));
+                    let result = if cx.is_cancelled() {
+                        Err(DeployError::Cancelled)
+                    } else {
+                        backend.run_step(&env, step, &cx)
+                    };
+                    let stop = result.is_err();
+                    _ = sender.send(DeployEvent::Finished(step, result));
+                    if stop {
+                        break;
+                    }
+                }
+            })
+            .map_err(|err| tracing::error!("Failed to start deployment thread: {}", err))
+            .ok();
+        self.run = Some(Run { cancel, events });
+    }
+}
+
+fn set_state(steps: &mut [(DeployStep, StepState)], step: DeployStep, state: StepState) {
+    steps[step.index()].1 = state;
+}
+
+impl Drop for Deployment {
+    /// Nobody is left to hear how a dropped deployment goes, so stop it.
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnvStatus, MockDeployBackend};
+
+    fn env() -> VirtualEnv {
+        VirtualEnv {
+            id: "env-1".to_string(),
+            name: "环境 1".to_string(),
+            status: EnvStatus::Idle,
+            owner: None,
+            lease_until: None,
+            reason: None,
+        }
+    }
+
+    fn wait_until_finished(deployment: &mut Deployment) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while deployment.is_running() {
+            assert!(Instant::now() < deadline, "deployment did not finish");
+            deployment.poll();
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn logged_steps(deployment: &Deployment, step: DeployStep) -> usize {
+        deployment.logs.iter().filter(|log| log.step == step).count()
+    }
+
+    #[test]
+    fn retry_resumes_from_the_failed_step() {
+        let backend: Arc<dyn DeployBackend> = Arc::new(
+            MockDeployBackend::new(Duration::from_millis(10)).fail_once(DeployStep::Flash),
+        );
+        let mut deployment = HiveTool::deploy(backend.clone(), env());
+        wait_until_finished(&mut deployment);
+
+        assert!(matches!(deployment.outcome(), Some(DeployOutcome::Failed(DeployStep::Flash, _))));
+        assert_eq!(deployment.resume_step(), Some(DeployStep::Flash));
+        assert_eq!(deployment.steps[0].1, StepState::Succeeded);
+        assert_eq!(deployment.steps[2].1, StepState::Pending);
+        let fetched = logged_steps(&deployment, DeployStep::FetchImage);
+
+        assert!(deployment.retry(backend));
+        wait_until_finished(&mut deployment);
+        assert_eq!(deployment.outcome(), Some(DeployOutcome::Succeeded));
+        assert_eq!(deployment.resume_step(), None);
+        // The image was not fetched again.
+        assert_eq!(logged_steps(&deployment, DeployStep::FetchImage), fetched);
+        assert!(logged_steps(&deployment, DeployStep::HealthCheck) > 0);
+    }
+
+    #[test]
+    fn cancel_stops_the_remaining_steps() {
+        let backend: Arc<dyn DeployBackend> =
+            Arc::new(MockDeployBackend::new(Duration::from_secs(5)));
+        let mut deployment = HiveTool::deploy(backend.clone(), env());
+        deployment.cancel();
+        wait_until_finished(&mut deployment);
+
+        assert_eq!(deployment.outcome(), Some(DeployOutcome::Cancelled(DeployStep::FetchImage)));
+        assert!(deployment.steps[1..].iter().all(|(_, state)| *state == StepState::Pending));
+        assert_eq!(deployment.resume_step(), Some(DeployStep::FetchImage));
+        // A cancelled deployment can be retried from where it stopped.
+        assert!(deployment.retry(backend));
+        assert!(deployment.is_running());
+    }
+
+    #[test]
+    fn nothing_to_retry_after_success() {
+        let backend: Arc<dyn DeployBackend> =
+            Arc::new(MockDeployBackend::new(Duration::from_millis(4)));
+        let mut deployment = HiveTool::deploy(backend.clone(), env());
+        wait_until_finished(&mut deployment);
+
+        assert_eq!(deployment.outcome(), Some(DeployOutcome::Succeeded));
+        assert!(!deployment.retry(backend));
+    }
+}
@@ -0,0 +1,522 @@
+use std::cmp::Reverse;
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{HiveTool, Job, JobStatus, RequestError, Status as _, Store, Table};
+
+/// What a job runs. Saved as a template and sent to the server on launch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobParams {
+    pub branch: String,
+    pub env_id: String,
+    pub suite: String,
+    pub tags: Vec<String>,
+}
+
+impl JobParams {
+    /// Split tags typed as `smoke, nightly` or `smoke nightly`, dropping
+    /// duplicates.
+    pub fn parse_tags(text: &str) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in text.split([',', '，', ' ']).map(str::trim).filter(|tag| !tag.is_empty()) {
+            if !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        tags
+    }
+
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.branch.as_str(), self.env_id.as_str(), self.suite.as_str()];
+        parts.retain(|part| !part.is_empty());
+        let mut summary = parts.join(" · ");
+        if !self.tags.is_empty() {
+            summary.push_str(&format!(" [{}]", self.tags.join(", ")));
+        }
+        summary
+    }
+}
+
+/// Where a job got to, as last reported by the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobCheckpoint {
+    /// The server's handle for the checkpoint, sent back on resume.
+    pub id: String,
+    pub total: u32,
+    pub finished: u32,
+    pub failed_cases: Vec<String>,
+}
+
+/// Job parameters saved under a name, per user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobTemplate {
+    pub name: String,
+    pub params: JobParams,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// The job service endpoints. `{id}` in a URL is replaced by the job id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobEndpoints {
+    /// Where new jobs are POSTed to; the response carries the job id.
+    pub launch_url: String,
+    /// Where a failed job is POSTed to to resume it, e.g. `.../jobs/{id}/resume`.
+    pub resume_url: String,
+    /// Polled for a job's progress, e.g. `.../jobs/{id}`.
+    pub status_url: String,
+}
+
+impl JobEndpoints {
+    pub(crate) fn url(template: &str, id: &str) -> String {
+        template.replace("{id}", id)
+    }
+}
+
+/// A job's state as polled from the server. Counts missing from the
+/// response are taken as zero.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobProgress {
+    pub status: JobStatus,
+    #[serde(default)]
+    pub total: u32,
+    #[serde(default)]
+    pub finished: u32,
+    #[serde(default)]
+    pub failed_cases: Vec<String>,
+    /// Present once the job has written a checkpoint it can resume from.
+    #[serde(default)]
+    pub checkpoint: Option<String>,
+}
+
+/// What is sent to resume a failed job: its checkpoint and only the cases
+/// that failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobResume {
+    pub job_id: String,
+    pub checkpoint: String,
+    pub cases: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum JobError {
+    #[error("未配置 Job 服务地址")]
+    NotConfigured,
+    #[error("只能续跑失败的 Job，当前状态为{0}")]
+    NotFailed(JobStatus),
+    #[error("Job 没有可续跑的检查点")]
+    NoCheckpoint,
+    #[error("Job 没有失败的用例，无需续跑")]
+    NoFailedCases,
+    #[error("Job 服务返回的编号 {0} 在本地已存在，未保存")]
+    DuplicateId(String),
+    #[error(transparent)]
+    Request(#[from] RequestError),
+    #[error(transparent)]
+    Store(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JobFormError {
+    #[error("请填写{0}")]
+    Required(&'static str),
+}
+
+impl JobFormError {
+    /// The label of the form field the error belongs to.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Required(field) => field,
+        }
+    }
+}
+
+/// The launch form's values before validation.
+#[derive(Debug, Clone, Default)]
+pub struct JobDraft {
+    pub name: String,
+    pub params: JobParams,
+}
+
+impl JobDraft {
+    pub fn from_template(template: &JobTemplate) -> Self {
+        Self { name: template.name.clone(), params: template.params.clone() }
+    }
+
+    pub fn validate(&self) -> Result<(String, JobParams), Vec<JobFormError>> {
+        let mut params = self.params.clone();
+        params.branch = params.branch.trim().to_string();
+        params.env_id = params.env_id.trim().to_string();
+        params.suite = params.suite.trim().to_string();
+        let name = self.name.trim().to_string();
+
+        let errors: Vec<JobFormError> = [
+            ("名称", name.is_empty()),
+            ("分支", params.branch.is_empty()),
+            ("环境", params.env_id.is_empty()),
+            ("测试套", params.suite.is_empty()),
+        ]
+        .into_iter()
+        .filter(|(_, missing)| *missing)
+        .map(|(field, _)| JobFormError::Required(field))
+        .collect();
+        if errors.is_empty() { Ok((name, params)) } else { Err(errors) }
+    }
+}
+
+/// Jobs split by where they are in their life, most recently updated first.
+#[derive(Debug, Default)]
+pub struct JobBoard<'a> {
+    pub queued: Vec<&'a Job>,
+    pub running: Vec<&'a Job>,
+    pub finished: Vec<&'a Job>,
+}
+
+impl Job {
+    /// A newly launched job, before the server reported on it.
+    pub fn launched(id: String, name: String, params: JobParams, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            name,
+            status: JobStatus::Queued,
+            progress: 0.0,
+            params: Some(params),
+            checkpoint: None,
+            resumed_from: None,
+            updated_at: Some(now),
+        }
+    }
+
+    /// Queued and running jobs, which are still polled.
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, JobStatus::Queued | JobStatus::Running)
+    }
+
+    /// Take in what the server reported. Returns whether anything changed.
+    /// A report the status can't move to, such as a finished job running
+    /// again, is out of date and ignored.
+    pub fn apply_progress(&mut self, progress: JobProgress, now: DateTime<Utc>) -> bool {
+        let before = (self.status, self.progress, self.checkpoint.clone());
+        match self.status.transition_to(progress.status) {
+            Ok(status) => self.status = status,
+            Err(err) => {
+                tracing::warn!("Ignoring progress of job {}: {}", self.id, err);
+                return false;
+            },
+        }
+        if progress.status == JobStatus::Succeeded {
+            self.progress = 1.0;
+        } else if progress.total > 0 {
+            self.progress = (progress.finished.min(progress.total) as f32) / progress.total as f32;
+        }
+        if let Some(id) = progress.checkpoint {
+            self.checkpoint = Some(JobCheckpoint {
+                id,
+                total: progress.total,
+                finished: progress.finished,
+                failed_cases: progress.failed_cases,
+            });
+        }
+        let changed = before != (self.status, self.progress, self.checkpoint.clone());
+        if changed {
+            self.updated_at = Some(now);
+        }
+        changed
+    }
+}
+
+impl HiveTool {
+    pub fn job_board(jobs: &[Job]) -> JobBoard<'_> {
+        let mut jobs: Vec<&Job> = jobs.iter().collect();
+        jobs.sort_by_key(|job| Reverse(job.updated_at));
+        let mut board = JobBoard::default();
+        for job in jobs {
+            match job.status {
+                JobStatus::Queued => board.queued.push(job),
+                JobStatus::Running => board.running.push(job),
+                JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled => {
+                    board.finished.push(job)
+                },
+            }
+        }
+        board
+    }
+
+    /// What to send to resume `job` from its last checkpoint, rerunning
+    /// only the cases that failed.
+    pub fn resume_plan(job: &Job) -> Result<JobResume, JobError> {
+        if job.status != JobStatus::Failed {
+            return Err(JobError::NotFailed(job.status));
+        }
+        let checkpoint = job.checkpoint.as_ref().ok_or(JobError::NoCheckpoint)?;
+        if checkpoint.failed_cases.is_empty() {
+            return Err(JobError::NoFailedCases);
+        }
+        Ok(JobResume {
+            job_id: job.id.clone(),
+            checkpoint: checkpoint.id.clone(),
+            cases: checkpoint.failed_cases.clone(),
+        })
+    }
+}
+
+impl Store {
+    /// Save a job the server just created. The id comes from the server, so
+    /// one already used here is refused rather than overwriting that job.
+    pub fn insert_job(&self, job: &Job) -> Result<(), JobError> {
+        self.transaction(|tx| {
+            let taken: bool = tx.conn().query_row(
+                &format!("SELECT EXISTS (SELECT 1 FROM {} WHERE id = ?1)", Job::NAME),
+                [&job.id],
+                |row| row.get(0),
+            )?;
+            if taken {
+                return Err(JobError::DuplicateId(job.id.clone()).into());
+            }
+            tx.upsert(job)
+        })
+        .map_err(|err| err.downcast::<JobError>().unwrap_or_else(JobError::Store))
+    }
+}
+
+/// Templates are kept per user, with the parameters stored as JSON like
+/// saved filters.
+impl Store {
+    pub fn job_templates(&self, owner: &str) -> anyhow::Result<Vec<JobTemplate>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT name, params, saved_at FROM job_templates WHERE owner = ?1 ORDER BY name",
+        )?;
+        let rows = stmt
+            .query_map([owner], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, params, saved_at)| match serde_json::from_str(&params) {
+                Ok(params) => Some(JobTemplate { name, params, saved_at }),
+                Err(err) => {
+                    tracing::warn!("Skipping unreadable job template {}: {}", name, err);
+                    None
+                },
+            })
+            .collect())
+    }
+
+    /// Save `params` as template `name`, replacing a template of that name.
+    pub fn save_job_template(
+        &self,
+        owner: &str,
+        name: &str,
+        params: &JobParams,
+    ) -> anyhow::Result<()> {
+        self.conn().execute(
+            "INSERT INTO job_templates (owner, name, params, saved_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (owner, name)
+             DO UPDATE SET params = excluded.params, saved_at = excluded.saved_at",
+            params![owner, name, serde_json::to_string(params)?, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_job_template(&self, owner: &str, name: &str) -> anyhow::Result<bool> {
+        let deleted = self
+            .conn()
+            .execute("DELETE FROM job_templates WHERE owner = ?1 AND name = ?2", params![
+                owner, name
+            ])?;
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    #[test]
+    fn insert_job_refuses_a_taken_id() {
+        let store = Store::open_in_memory().unwrap();
+        let now = Utc::now();
+        let first =
+            Job::launched("42".to_string(), "夜间回归".to_string(), JobParams::default(), now);
+        store.insert_job(&first).unwrap();
+
+        let second = Job::launched("42".to_string(), "冒烟".to_string(), JobParams::default(), now);
+        assert!(matches!(store.insert_job(&second), Err(JobError::DuplicateId(id)) if id == "42"));
+        assert_eq!(store.jobs().get("42").unwrap().unwrap().name, "夜间回归");
+    }
+
+    fn progress(status: JobStatus, total: u32, finished: u32) -> JobProgress {
+        JobProgress { status, total, finished, failed_cases: Vec::new(), checkpoint: None }
+    }
+
+    fn job(id: &str, status: JobStatus, minutes: i64) -> Job {
+        let launched = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut job = Job::launched(id.to_string(), id.to_string(), JobParams::default(), launched);
+        job.status = status;
+        job.updated_at = Some(launched + TimeDelta::minutes(minutes));
+        job
+    }
+
+    #[test]
+    fn progress_is_the_finished_share_clamped_to_one() {
+        let now = Utc::now();
+        let mut running = job("1", JobStatus::Queued, 0);
+        assert!(running.apply_progress(progress(JobStatus::Running, 4, 1), now));
+        assert_eq!((running.status, running.progress), (JobStatus::Running, 0.25));
+
+        running.apply_progress(progress(JobStatus::Running, 4, 9), now);
+        assert_eq!(running.progress, 1.0);
+        // Without a total the last known share stays.
+        running.apply_progress(progress(JobStatus::Running, 0, 0), now);
+        assert_eq!(running.progress, 1.0);
+
+        let mut done = job("2", JobStatus::Running, 0);
+        done.apply_progress(progress(JobStatus::Succeeded, 0, 0), now);
+        assert_eq!(done.progress, 1.0);
+    }
+
+    #[test]
+    fn checkpoint_is_captured_with_the_failed_cases() {
+        let mut job = job("1", JobStatus::Running, 0);
+        let report = JobProgress {
+            failed_cases: vec!["login".to_string()],
+            checkpoint: Some("cp-3".to_string()),
+            ..progress(JobStatus::Failed, 10, 6)
+        };
+        job.apply_progress(report, Utc::now());
+        assert_eq!(
+            job.checkpoint,
+            Some(JobCheckpoint {
+                id: "cp-3".to_string(),
+                total: 10,
+                finished: 6,
+                failed_cases: vec!["login".to_string()],
+            })
+        );
+        // A report without a checkpoint keeps the last one.
+        job.apply_progress(progress(JobStatus::Failed, 10, 6), Utc::now());
+        assert_eq!(job.checkpoint.as_ref().map(|cp| cp.id.as_str()), Some("cp-3"));
+    }
+
+    #[test]
+    fn unchanged_report_is_not_a_change() {
+        let mut job = job("1", JobStatus::Running, 0);
+        let later = job.updated_at.unwrap() + TimeDelta::minutes(5);
+        assert!(job.apply_progress(progress(JobStatus::Running, 4, 2), later));
+        assert_eq!(job.updated_at, Some(later));
+
+        let even_later = later + TimeDelta::minutes(5);
+        assert!(!job.apply_progress(progress(JobStatus::Running, 4, 2), even_later));
+        assert_eq!(job.updated_at, Some(later));
+    }
+
+    #[test]
+    fn reports_follow_the_status_transitions() {
+        // A queued job may be seen finished without ever being seen running.
+        let mut job = job("1", JobStatus::Queued, 0);
+        assert!(job.apply_progress(progress(JobStatus::Succeeded, 3, 3), Utc::now()));
+        assert_eq!(job.status, JobStatus::Succeeded);
+
+        // A stale report can't bring a finished job back.
+        let before = job.clone();
+        assert!(!job.apply_progress(progress(JobStatus::Running, 3, 1), Utc::now()));
+        assert_eq!(
+            (job.status, job.progress, job.updated_at),
+            (before.status, before.progress, before.updated_at)
+        );
+    }
+
+    #[test]
+    fn resume_plan_needs_a_failed_job_with_a_checkpoint_and_failed_cases() {
+        let checkpoint = |failed_cases: &[&str]| JobCheckpoint {
+            id: "cp-1".to_string(),
+            total: 5,
+            finished: 5,
+            failed_cases: failed_cases.iter().map(|case| case.to_string()).collect(),
+        };
+
+        let mut running = job("1", JobStatus::Running, 0);
+        running.checkpoint = Some(checkpoint(&["login"]));
+        assert!(matches!(
+            HiveTool::resume_plan(&running),
+            Err(JobError::NotFailed(JobStatus::Running))
+        ));
+
+        let mut failed = job("2", JobStatus::Failed, 0);
+        assert!(matches!(HiveTool::resume_plan(&failed), Err(JobError::NoCheckpoint)));
+        failed.checkpoint = Some(checkpoint(&[]));
+        assert!(matches!(HiveTool::resume_plan(&failed), Err(JobError::NoFailedCases)));
+
+        failed.checkpoint = Some(checkpoint(&["login", "logout"]));
+        assert_eq!(
+            HiveTool::resume_plan(&failed).unwrap(),
+            JobResume {
+                job_id: "2".to_string(),
+                checkpoint: "cp-1".to_string(),
+                cases: vec!["login".to_string(), "logout".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn draft_is_trimmed_and_reports_every_missing_field() {
+        let draft = JobDraft {
+            name: " 夜间回归 ".to_string(),
+            params: JobParams {
+                branch: " main ".to_string(),
+                env_id: "env-1".to_string(),
+                suite: "smoke".to_string(),
+                tags: Vec::new(),
+            },
+        };
+        let (name, params) = draft.validate().unwrap();
+        assert_eq!((name.as_str(), params.branch.as_str()), ("夜间回归", "main"));
+
+        let empty = JobDraft { name: " ".to_string(), params: JobParams::default() };
+        assert_eq!(
+            empty.validate().unwrap_err(),
+            [
+                JobFormError::Required("名称"),
+                JobFormError::Required("分支"),
+                JobFormError::Required("环境"),
+                JobFormError::Required("测试套"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_split_on_commas_and_spaces_without_duplicates() {
+        assert_eq!(
+            JobParams::parse_tags(" smoke, nightly，smoke  ui,,"),
+            ["smoke", "nightly", "ui"]
+        );
+        assert!(JobParams::parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn job_board_groups_by_status_newest_first() {
+        let jobs = [
+            job("queued", JobStatus::Queued, 1),
+            job("old-run", JobStatus::Running, 2),
+            job("new-run", JobStatus::Running, 5),
+            job("passed", JobStatus::Succeeded, 3),
+            job("failed", JobStatus::Failed, 6),
+            job("cancelled", JobStatus::Cancelled, 4),
+        ];
+        let board = HiveTool::job_board(&jobs);
+        let ids = |jobs: &[&Job]| jobs.iter().map(|job| job.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&board.queued), ["queued"]);
+        assert_eq!(ids(&board.running), ["new-run", "old-run"]);
+        assert_eq!(ids(&board.finished), ["failed", "cancelled", "passed"]);
+    }
+}
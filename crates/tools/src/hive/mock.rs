@@ -0,0 +1,57 @@
+use std::{sync::Mutex, time::Duration};
+
+use crate::{DeployBackend, DeployError, DeployStep, StepContext, VirtualEnv};
+
+/// A backend that deploys nothing: each step logs a few lines and waits a
+/// little. It can be told to fail a step once, to try retries by hand.
+pub struct MockDeployBackend {
+    step_time: Duration,
+    fail_once: Mutex<Option<DeployStep>>,
+}
+
+impl Default for MockDeployBackend {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(1500))
+    }
+}
+
+impl MockDeployBackend {
+    pub fn new(step_time: Duration) -> Self {
+        Self { step_time, fail_once: Mutex::new(None) }
+    }
+
+    /// Fail the next run of `step`; runs after that succeed.
+    pub fn fail_once(self, step: DeployStep) -> Self {
+        *self.fail_once.lock().unwrap() = Some(step);
+        self
+    }
+}
+
+impl DeployBackend for MockDeployBackend {
+    fn run_step(
+        &self,
+        env: &VirtualEnv,
+        step: DeployStep,
+        cx: &StepContext,
+    ) -> Result<(), DeployError> {
+        let lines: &[&str] = match step {
+            DeployStep::FetchImage => &["解析镜像版本", "下载镜像", "校验镜像"],
+            DeployStep::Flash => &["连接设备", "写入镜像", "重启设备"],
+            DeployStep::Configure => &["下发网络配置", "下发测试账号"],
+            DeployStep::HealthCheck => &["检查服务端口", "检查业务接口"],
+        };
+        let pause = self.step_time / lines.len() as u32;
+        for line in lines {
+            cx.log(format!("[{}] {}...", env.name, line));
+            cx.wait(pause)?;
+        }
+        let mut fail_once = self.fail_once.lock().unwrap();
+        if *fail_once == Some(step) {
+            *fail_once = None;
+            cx.log(format!("[{}] {}失败", env.name, step.label()));
+            return Err(DeployError::Failed(format!("模拟{}失败", step.label())));
+        }
+        cx.log(format!("[{}] {}完成", env.name, step.label()));
+        Ok(())
+    }
+}
@@ -1,2 +1,12 @@
+mod cluster;
+mod deploy;
+mod job;
+mod mock;
 mod model;
+mod reservation;
+pub use cluster::*;
+pub use deploy::*;
+pub use job::*;
+pub use mock::*;
 pub use model::*;
+pub use reservation::*;
@@ -1,6 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Coded, JobCheckpoint, JobParams, Status, impl_coded};
+
 pub struct HiveTool {}
 impl HiveTool {
     fn new() -> Self {
         Self {}
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub error_msg: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualEnv {
+    pub id: String,
+    pub name: String,
+    pub status: EnvStatus,
+    pub owner: Option<String>,
+    /// When the current reservation runs out, `None` for an environment
+    /// occupied without a lease.
+    #[serde(default)]
+    pub lease_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub name: String,
+    pub status: JobStatus,
+    pub progress: f32,
+    /// What the job was launched with, `None` for jobs imported from
+    /// elsewhere.
+    #[serde(default)]
+    pub params: Option<JobParams>,
+    /// The last checkpoint the server reported, which a failed job resumes
+    /// from.
+    #[serde(default)]
+    pub checkpoint: Option<JobCheckpoint>,
+    /// The failed job this one resumes.
+    #[serde(default)]
+    pub resumed_from: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvStatus {
+    Idle,
+    Occupied,
+    Deploying,
+}
+
+impl Coded for EnvStatus {
+    const ALL: &'static [Self] = &[Self::Idle, Self::Occupied, Self::Deploying];
+    const KIND: &'static str = "环境状态";
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Occupied => "occupied",
+            Self::Deploying => "deploying",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Idle => "空闲",
+            Self::Occupied => "占用",
+            Self::Deploying => "部署中",
+        }
+    }
+}
+
+impl Status for EnvStatus {}
+impl_coded!(EnvStatus);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl Coded for JobStatus {
+    const ALL: &'static [Self] =
+        &[Self::Queued, Self::Running, Self::Succeeded, Self::Failed, Self::Cancelled];
+    const KIND: &'static str = "Job 状态";
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Queued => "排队中",
+            Self::Running => "运行中",
+            Self::Succeeded => "成功",
+            Self::Failed => "失败",
+            Self::Cancelled => "已取消",
+        }
+    }
+}
+
+impl Status for JobStatus {
+    /// Finished jobs can only be queued again, which is how a job is rerun.
+    /// The server is only polled now and then, so a queued job may already
+    /// have finished when it is first seen again.
+    fn can_transition_to(&self, next: Self) -> bool {
+        use JobStatus::*;
+        matches!(
+            (self, next),
+            (Queued, Running | Succeeded | Failed | Cancelled)
+                | (Running, Succeeded | Failed | Cancelled)
+                | (Succeeded | Failed | Cancelled, Queued)
+        )
+    }
+}
+impl_coded!(JobStatus);
@@ -0,0 +1,816 @@
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    EnvStatus, HiveTool, RequestError, RequestTool, Store, Table, Transaction, VirtualEnv,
+};
+
+/// How long the environment service holds an idle environment taken for a
+/// deployment. The hold is given back when the deployment ends.
+const DEPLOY_LEASE: TimeDelta = TimeDelta::hours(2);
+/// Reason sent with holds taken for a deployment.
+const DEPLOY_REASON: &str = "部署";
+
+/// The Hive environment service, which holds are shared through so everyone
+/// sees the same holders. `{id}` in a URL is replaced by the environment id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvEndpoints {
+    /// Lists who holds which environment, e.g. `.../envs`.
+    pub list_url: String,
+    /// Where a hold is POSTed to, e.g. `.../envs/{id}/reserve`.
+    pub reserve_url: String,
+    /// Where a release is POSTed to, e.g. `.../envs/{id}/release`.
+    pub release_url: String,
+}
+
+impl EnvEndpoints {
+    pub(crate) fn url(template: &str, id: &str) -> String {
+        template.replace("{id}", id)
+    }
+}
+
+/// An environment's holder as reported by the environment service; `owner`
+/// is `None` for a free environment.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EnvLease {
+    pub id: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub lease_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// How a reservation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeaseEnd {
+    /// Released by the holder.
+    Released,
+    /// The lease ran out.
+    Expired,
+}
+
+impl LeaseEnd {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Released => "released",
+            Self::Expired => "expired",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Released => "主动释放",
+            Self::Expired => "到期释放",
+        }
+    }
+
+    fn parse(code: &str) -> Option<Self> {
+        [Self::Released, Self::Expired].into_iter().find(|end| end.code() == code)
+    }
+}
+
+/// One reservation of an environment, open while `released_at` is `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    pub id: i64,
+    pub env_id: String,
+    pub owner: String,
+    pub reason: String,
+    pub reserved_at: DateTime<Utc>,
+    pub lease_until: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub lease_end: Option<LeaseEnd>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReservationError {
+    #[error("环境 {0} 不存在")]
+    NotFound(String),
+    #[error("环境已被 {owner} 占用{}", held_until(.until))]
+    HeldBy { owner: String, until: Option<DateTime<Utc>> },
+    #[error("环境正在部署，暂不能占用")]
+    Deploying,
+    #[error("环境未被你占用，不能释放")]
+    NotHolder,
+    #[error("占用时长必须大于 0")]
+    InvalidDuration,
+    #[error("请填写占用原因")]
+    MissingReason,
+    #[error(transparent)]
+    Request(#[from] RequestError),
+    #[error(transparent)]
+    Store(#[from] anyhow::Error),
+}
+
+impl From<rusqlite::Error> for ReservationError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Store(err.into())
+    }
+}
+
+fn held_until(until: &Option<DateTime<Utc>>) -> String {
+    until.map_or(String::new(), |until| {
+        format!("至 {}", until.with_timezone(&Local).format("%Y-%m-%d %H:%M"))
+    })
+}
+
+impl HiveTool {
+    /// Environments held by `owner` whose lease runs out within `within` of
+    /// `now`, soonest first.
+    pub fn expiring_leases<'a>(
+        envs: &'a [VirtualEnv],
+        owner: &str,
+        now: DateTime<Utc>,
+        within: TimeDelta,
+    ) -> Vec<&'a VirtualEnv> {
+        let mut expiring: Vec<&VirtualEnv> = envs
+            .iter()
+            .filter(|env| env.status == EnvStatus::Occupied && env.owner.as_deref() == Some(owner))
+            .filter(|env| env.lease_until.is_some_and(|until| until > now && until - now <= within))
+            .collect();
+        expiring.sort_by_key(|env| env.lease_until);
+        expiring
+    }
+}
+
+/// Reserves and releases environments through the environment service when
+/// one is configured, keeping the store in step with it. Without a service,
+/// holds are only known to this machine.
+#[derive(Clone)]
+pub struct LeaseTool {
+    store: Store,
+    request: RequestTool,
+    endpoints: Option<EnvEndpoints>,
+}
+
+impl LeaseTool {
+    pub fn new(store: Store, request: RequestTool, endpoints: Option<EnvEndpoints>) -> Self {
+        Self { store, request, endpoints }
+    }
+
+    /// Release lapsed leases and take in the holders the service reports.
+    /// Returns the environments whose lease ran out, as they were before.
+    pub async fn refresh(&self, now: DateTime<Utc>) -> Result<Vec<VirtualEnv>, ReservationError> {
+        let expired = self.store.expire_leases(now)?;
+        if let Some(endpoints) = &self.endpoints {
+            let leases = self.request.env_leases(endpoints).await?;
+            self.store.apply_env_leases(&leases, now)?;
+        }
+        Ok(expired)
+    }
+
+    /// Reserve `env_id` for `owner`, or extend the lease `owner` holds; see
+    /// [`Store::reserve_env`]. The service has the last word: a hold it
+    /// refuses is not taken here either.
+    pub async fn reserve(
+        &self,
+        env_id: &str,
+        owner: &str,
+        reason: &str,
+        duration: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Result<VirtualEnv, ReservationError> {
+        let reason = check_request(reason, duration)?;
+        if let Some(endpoints) = &self.endpoints {
+            self.refresh(now).await?;
+            check_reservable(&self.load_env(env_id)?, owner)?;
+            let sent =
+                self.request.reserve_env(endpoints, env_id, owner, reason, now + duration).await;
+            if let Err(err) = sent {
+                return Err(self.refusal(env_id, owner, now, err).await);
+            }
+        }
+        self.store.reserve_env(env_id, owner, reason, duration, now)
+    }
+
+    /// Release `env_id`, which only its holder may do.
+    pub async fn release(
+        &self,
+        env_id: &str,
+        owner: &str,
+        now: DateTime<Utc>,
+    ) -> Result<VirtualEnv, ReservationError> {
+        if let Some(endpoints) = &self.endpoints {
+            self.refresh(now).await?;
+            check_holder(&self.load_env(env_id)?, owner)?;
+            self.request.release_env(endpoints, env_id, owner).await?;
+        }
+        self.store.release_env(env_id, owner, now)
+    }
+
+    /// Mark `env_id` as deploying for `owner`; see [`Store::begin_deploy`].
+    /// Like [`LeaseTool::reserve`], the holders are refreshed first: a hold
+    /// `owner` already has is confirmed, and an idle environment is held on
+    /// the service for the deployment so nobody takes it meanwhile.
+    pub async fn begin_deploy(
+        &self,
+        env_id: &str,
+        owner: &str,
+        now: DateTime<Utc>,
+    ) -> Result<VirtualEnv, ReservationError> {
+        if let Some(endpoints) = &self.endpoints {
+            self.refresh(now).await?;
+            let env = self.load_env(env_id)?;
+            check_reservable(&env, owner)?;
+            if env.status == EnvStatus::Idle {
+                let sent = self
+                    .request
+                    .reserve_env(endpoints, env_id, owner, DEPLOY_REASON, now + DEPLOY_LEASE)
+                    .await;
+                if let Err(err) = sent {
+                    return Err(self.refusal(env_id, owner, now, err).await);
+                }
+            }
+        }
+        self.store.begin_deploy(env_id, owner, now)
+    }
+
+    /// End the deployment of `env_id`; see [`Store::finish_deploy`]. The
+    /// hold taken on the service for deploying an idle environment is given
+    /// back.
+    pub async fn finish_deploy(&self, env_id: &str) -> Result<VirtualEnv, ReservationError> {
+        let deploying = self.load_env(env_id)?;
+        let env = self.store.finish_deploy(env_id)?;
+        if let Some(endpoints) = &self.endpoints
+            && deploying.status == EnvStatus::Deploying
+            && env.status == EnvStatus::Idle
+            && let Some(owner) = &deploying.owner
+        {
+            self.request.release_env(endpoints, env_id, owner).await?;
+        }
+        Ok(env)
+    }
+
+    fn load_env(&self, env_id: &str) -> Result<VirtualEnv, ReservationError> {
+        load_env(&self.store.conn(), env_id)?
+            .ok_or_else(|| ReservationError::NotFound(env_id.to_string()))
+    }
+
+    /// Why the service refused to reserve `env_id`: usually someone took it
+    /// in the meantime, which a fresh look at the holders tells.
+    async fn refusal(
+        &self,
+        env_id: &str,
+        owner: &str,
+        now: DateTime<Utc>,
+        err: RequestError,
+    ) -> ReservationError {
+        if self.refresh(now).await.is_ok()
+            && let Ok(env) = self.load_env(env_id)
+            && let Err(refused) = check_reservable(&env, owner)
+        {
+            return refused;
+        }
+        err.into()
+    }
+}
+
+/// Reservations keep the environment row and its history in step: the
+/// environment holds the current lease, `env_reservations` every lease ever
+/// taken. Expired leases are released before any change, so a lapsed lease
+/// never blocks someone else.
+impl Store {
+    /// Reserve `env_id` for `owner` for `duration` from `now`. Reserving an
+    /// environment one already holds extends the lease instead.
+    pub fn reserve_env(
+        &self,
+        env_id: &str,
+        owner: &str,
+        reason: &str,
+        duration: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Result<VirtualEnv, ReservationError> {
+        let reason = check_request(reason, duration)?;
+        let lease_until = now + duration;
+
+        self.lease_transaction(now, |tx| {
+            let env = load_env(tx.conn(), env_id)?
+                .ok_or_else(|| ReservationError::NotFound(env_id.to_string()))?;
+            check_reservable(&env, owner)?;
+            if env.status == EnvStatus::Occupied {
+                Ok(extend(tx, env, reason, Some(lease_until))?)
+            } else {
+                Ok(hold(tx, env, owner, reason, now, Some(lease_until))?)
+            }
+        })
+    }
+
+    /// Release `env_id`, which only its holder may do.
+    pub fn release_env(
+        &self,
+        env_id: &str,
+        owner: &str,
+        now: DateTime<Utc>,
+    ) -> Result<VirtualEnv, ReservationError> {
+        self.lease_transaction(now, |tx| {
+            let env = load_env(tx.conn(), env_id)?
+                .ok_or_else(|| ReservationError::NotFound(env_id.to_string()))?;
+            check_holder(&env, owner)?;
+            Ok(release(tx, env, LeaseEnd::Released, now)?)
+        })
+    }
+
+    /// Mark `env_id` as deploying for `owner`. The holder's lease, if any,
+    /// is kept and comes back once [`Store::finish_deploy`] is called.
+    pub fn begin_deploy(
+        &self,
+        env_id: &str,
+        owner: &str,
+        now: DateTime<Utc>,
+    ) -> Result<VirtualEnv, ReservationError> {
+        self.lease_transaction(now, |tx| {
+            let mut env = load_env(tx.conn(), env_id)?
+                .ok_or_else(|| ReservationError::NotFound(env_id.to_string()))?;
+            check_reservable(&env, owner)?;
+            if env.status == EnvStatus::Idle {
+                env.owner = Some(owner.to_string());
+            }
+            env.status = EnvStatus::Deploying;
+            tx.upsert(&env)?;
+            Ok(env)
+        })
+    }
+
+    /// End the deployment of `env_id`, however it went: back to occupied
+    /// while a lease is open, idle otherwise.
+    pub fn finish_deploy(&self, env_id: &str) -> Result<VirtualEnv, ReservationError> {
+        self.transaction(|tx| {
+            let mut env = load_env(tx.conn(), env_id)?
+                .ok_or_else(|| ReservationError::NotFound(env_id.to_string()))?;
+            if env.status == EnvStatus::Deploying {
+                if env.lease_until.is_some() {
+                    env.status = EnvStatus::Occupied;
+                } else {
+                    env.status = EnvStatus::Idle;
+                    env.owner = None;
+                }
+                tx.upsert(&env)?;
+            }
+            Ok(env)
+        })
+        .map_err(|err| err.downcast::<ReservationError>().unwrap_or_else(ReservationError::Store))
+    }
+
+    /// Take in the holders reported by the environment service, so holds
+    /// taken or ended elsewhere show here too. Environments the service
+    /// doesn't list and those deploying here are left alone.
+    pub fn apply_env_leases(&self, leases: &[EnvLease], now: DateTime<Utc>) -> anyhow::Result<()> {
+        self.transaction(|tx| {
+            expire(tx, now)?;
+            for lease in leases {
+                let Some(env) = load_env(tx.conn(), &lease.id)? else {
+                    continue;
+                };
+                if env.status == EnvStatus::Deploying {
+                    continue;
+                }
+                let held = lease
+                    .owner
+                    .as_deref()
+                    .filter(|_| lease.lease_until.is_none_or(|until| until > now));
+                let reason = lease.reason.as_deref().unwrap_or_default();
+                match (held, env.owner.as_deref()) {
+                    (None, None) => {},
+                    (None, Some(_)) => _ = release(tx, env, LeaseEnd::Released, now)?,
+                    (Some(owner), Some(holder)) if owner == holder => {
+                        if (env.lease_until, env.reason.as_deref())
+                            != (lease.lease_until, lease.reason.as_deref())
+                        {
+                            extend(tx, env, reason, lease.lease_until)?;
+                        }
+                    },
+                    (Some(owner), holder) => {
+                        let env = match holder {
+                            Some(_) => release(tx, env, LeaseEnd::Released, now)?,
+                            None => env,
+                        };
+                        hold(tx, env, owner, reason, now, lease.lease_until)?;
+                    },
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// End the deployments left over from an earlier run, which nothing is
+    /// deploying anymore, returning the environments reset.
+    pub fn reset_deploys(&self) -> Result<Vec<VirtualEnv>, ReservationError> {
+        let deploying: Vec<String> = self
+            .virtual_envs()
+            .all()?
+            .into_iter()
+            .filter(|env| env.status == EnvStatus::Deploying)
+            .map(|env| env.id)
+            .collect();
+        deploying.iter().map(|env_id| self.finish_deploy(env_id)).collect()
+    }
+
+    /// Release every environment whose lease ran out by `now`, returning
+    /// them as they were before, so callers can tell whose lease it was.
+    pub fn expire_leases(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<VirtualEnv>> {
+        self.transaction(|tx| expire(tx, now))
+    }
+
+    /// The reservations of `env_id`, newest first.
+    pub fn env_reservations(&self, env_id: &str) -> anyhow::Result<Vec<Reservation>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, env_id, owner, reason, reserved_at, lease_until, released_at, lease_end
+             FROM env_reservations WHERE env_id = ?1 ORDER BY reserved_at DESC, id DESC",
+        )?;
+        let reservations = stmt
+            .query_map([env_id], reservation_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(reservations)
+    }
+
+    /// Release lapsed leases, then run `f` in a transaction of its own so a
+    /// refused change still keeps the expiries.
+    fn lease_transaction<R>(
+        &self,
+        now: DateTime<Utc>,
+        f: impl FnOnce(&Transaction) -> Result<R, ReservationError>,
+    ) -> Result<R, ReservationError> {
+        self.expire_leases(now)?;
+        self.transaction(|tx| Ok(f(tx)?)).map_err(|err| {
+            err.downcast::<ReservationError>().unwrap_or_else(ReservationError::Store)
+        })
+    }
+}
+
+/// The reason and duration of a reservation, checked before anything is
+/// changed.
+fn check_request(reason: &str, duration: TimeDelta) -> Result<&str, ReservationError> {
+    if duration <= TimeDelta::zero() {
+        return Err(ReservationError::InvalidDuration);
+    }
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(ReservationError::MissingReason);
+    }
+    Ok(reason)
+}
+
+/// Whether `owner` may reserve or deploy `env`: not while it deploys, nor
+/// while someone else holds it.
+fn check_reservable(env: &VirtualEnv, owner: &str) -> Result<(), ReservationError> {
+    match env.status {
+        EnvStatus::Deploying => Err(ReservationError::Deploying),
+        EnvStatus::Occupied if env.owner.as_deref() != Some(owner) => {
+            Err(ReservationError::HeldBy {
+                owner: env.owner.clone().unwrap_or_else(|| "未知用户".to_string()),
+                until: env.lease_until,
+            })
+        },
+        EnvStatus::Occupied | EnvStatus::Idle => Ok(()),
+    }
+}
+
+fn check_holder(env: &VirtualEnv, owner: &str) -> Result<(), ReservationError> {
+    if env.status != EnvStatus::Occupied || env.owner.as_deref() != Some(owner) {
+        return Err(ReservationError::NotHolder);
+    }
+    Ok(())
+}
+
+/// Open a reservation of `env` for `owner`.
+fn hold(
+    tx: &Transaction,
+    mut env: VirtualEnv,
+    owner: &str,
+    reason: &str,
+    now: DateTime<Utc>,
+    lease_until: Option<DateTime<Utc>>,
+) -> anyhow::Result<VirtualEnv> {
+    tx.conn().execute(
+        "INSERT INTO env_reservations (env_id, owner, reason, reserved_at, lease_until)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![env.id, owner, reason, now, lease_until],
+    )?;
+    env.status = EnvStatus::Occupied;
+    env.owner = Some(owner.to_string());
+    env.lease_until = lease_until;
+    env.reason = Some(reason.to_string());
+    tx.upsert(&env)?;
+    Ok(env)
+}
+
+/// Move the end of the open reservation of `env`, keeping its holder.
+fn extend(
+    tx: &Transaction,
+    mut env: VirtualEnv,
+    reason: &str,
+    lease_until: Option<DateTime<Utc>>,
+) -> anyhow::Result<VirtualEnv> {
+    tx.conn().execute(
+        "UPDATE env_reservations SET lease_until = ?2, reason = ?3
+         WHERE env_id = ?1 AND released_at IS NULL",
+        params![env.id, lease_until, reason],
+    )?;
+    env.lease_until = lease_until;
+    env.reason = Some(reason.to_string());
+    tx.upsert(&env)?;
+    Ok(env)
+}
+
+fn expire(tx: &Transaction, now: DateTime<Utc>) -> anyhow::Result<Vec<VirtualEnv>> {
+    let occupied: Vec<VirtualEnv> = {
+        let mut stmt = tx.conn().prepare_cached(&format!(
+            "SELECT {} FROM {} WHERE status = ?1",
+            VirtualEnv::COLUMNS.join(", "),
+            VirtualEnv::NAME
+        ))?;
+        stmt.query_map([EnvStatus::Occupied], VirtualEnv::from_row)?
+            .collect::<rusqlite::Result<_>>()?
+    };
+    occupied
+        .into_iter()
+        .filter(|env| env.lease_until.is_some_and(|until| until <= now))
+        .map(|env| release(tx, env.clone(), LeaseEnd::Expired, now).map(|_| env))
+        .collect()
+}
+
+fn release(
+    tx: &Transaction,
+    mut env: VirtualEnv,
+    end: LeaseEnd,
+    now: DateTime<Utc>,
+) -> anyhow::Result<VirtualEnv> {
+    // An expired lease ends when it ran out, not when it was noticed.
+    let released_at = match end {
+        LeaseEnd::Expired => env.lease_until.map_or(now, |until| until.min(now)),
+        LeaseEnd::Released => now,
+    };
+    tx.conn().execute(
+        "UPDATE env_reservations SET released_at = ?2, lease_end = ?3
+         WHERE env_id = ?1 AND released_at IS NULL",
+        params![env.id, released_at, end.code()],
+    )?;
+    env.status = EnvStatus::Idle;
+    env.owner = None;
+    env.lease_until = None;
+    env.reason = None;
+    tx.upsert(&env)?;
+    Ok(env)
+}
+
+fn load_env(conn: &Connection, id: &str) -> rusqlite::Result<Option<VirtualEnv>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM {} WHERE id = ?1",
+            VirtualEnv::COLUMNS.join(", "),
+            VirtualEnv::NAME
+        ),
+        [id],
+        VirtualEnv::from_row,
+    )
+    .optional()
+}
+
+fn reservation_from_row(row: &Row) -> rusqlite::Result<Reservation> {
+    Ok(Reservation {
+        id: row.get(0)?,
+        env_id: row.get(1)?,
+        owner: row.get(2)?,
+        reason: row.get(3)?,
+        reserved_at: row.get(4)?,
+        lease_until: row.get(5)?,
+        released_at: row.get(6)?,
+        lease_end: row.get::<_, Option<String>>(7)?.as_deref().and_then(LeaseEnd::parse),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+    };
+
+    use chrono::TimeZone as _;
+
+    use super::*;
+
+    /// A stand-in environment service answering `GET /envs` with `leases`
+    /// and every POST with an empty object. Returns its endpoints and the
+    /// requests it received, as `METHOD path body`.
+    fn env_service(leases: &'static str) -> (EnvEndpoints, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+                received.lock().unwrap().push(format!(
+                    "{} {} {}",
+                    method,
+                    path,
+                    String::from_utf8(body).unwrap()
+                ));
+
+                let response = if method == "GET" { leases } else { "{}" };
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+        let endpoints = EnvEndpoints {
+            list_url: format!("{}/envs", base),
+            reserve_url: format!("{}/envs/{{id}}/reserve", base),
+            release_url: format!("{}/envs/{{id}}/release", base),
+        };
+        (endpoints, requests)
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 4, 1, hour, 0, 0).unwrap()
+    }
+
+    fn store_with_env() -> Store {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .virtual_envs()
+            .upsert(&VirtualEnv {
+                id: "env-1".to_string(),
+                name: "环境 1".to_string(),
+                status: EnvStatus::Idle,
+                owner: None,
+                lease_until: None,
+                reason: None,
+            })
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn reserving_a_held_env_is_refused() {
+        let store = store_with_env();
+        store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(2), at(8)).unwrap();
+
+        let refused = store.reserve_env("env-1", "bob", "复现", TimeDelta::hours(1), at(9));
+        assert!(matches!(
+            refused,
+            Err(ReservationError::HeldBy { owner, until }) if owner == "alice" && until == Some(at(10))
+        ));
+        assert!(matches!(
+            store.release_env("env-1", "bob", at(9)),
+            Err(ReservationError::NotHolder)
+        ));
+        assert!(matches!(
+            store.reserve_env("env-1", "bob", " ", TimeDelta::hours(1), at(9)),
+            Err(ReservationError::MissingReason)
+        ));
+    }
+
+    #[test]
+    fn holder_extends_instead_of_reserving_again() {
+        let store = store_with_env();
+        store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(1), at(8)).unwrap();
+        let env = store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(3), at(8)).unwrap();
+
+        assert_eq!(env.lease_until, Some(at(11)));
+        let reservations = store.env_reservations("env-1").unwrap();
+        assert_eq!(reservations.len(), 1);
+        assert_eq!(reservations[0].lease_until, at(11));
+    }
+
+    #[test]
+    fn lapsed_lease_expires_when_it_ran_out() {
+        let store = store_with_env();
+        store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(1), at(8)).unwrap();
+
+        let env = store.reserve_env("env-1", "bob", "复现", TimeDelta::hours(1), at(12)).unwrap();
+        assert_eq!(env.owner.as_deref(), Some("bob"));
+        let reservations = store.env_reservations("env-1").unwrap();
+        let alice = reservations.iter().find(|r| r.owner == "alice").unwrap();
+        assert_eq!(alice.released_at, Some(at(9)));
+        assert_eq!(alice.lease_end, Some(LeaseEnd::Expired));
+        assert!(store.expire_leases(at(12)).unwrap().is_empty());
+        assert_eq!(store.expire_leases(at(13)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn leftover_deployments_are_reset() {
+        let store = store_with_env();
+        store.begin_deploy("env-1", "alice", at(8)).unwrap();
+        assert!(matches!(
+            store.reserve_env("env-1", "bob", "复现", TimeDelta::hours(1), at(9)),
+            Err(ReservationError::Deploying)
+        ));
+
+        let reset = store.reset_deploys().unwrap();
+        assert_eq!(reset.len(), 1);
+        assert_eq!((reset[0].status, reset[0].owner.as_deref()), (EnvStatus::Idle, None));
+        assert!(store.reset_deploys().unwrap().is_empty());
+    }
+
+    #[test]
+    fn service_leases_replace_local_holders() {
+        let store = store_with_env();
+        store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(4), at(8)).unwrap();
+        let lease = |owner: Option<&str>, until| EnvLease {
+            id: "env-1".to_string(),
+            owner: owner.map(str::to_string),
+            lease_until: until,
+            reason: Some("联调".to_string()),
+        };
+
+        // Released elsewhere, then taken by someone else.
+        store.apply_env_leases(&[lease(Some("bob"), Some(at(11)))], at(9)).unwrap();
+        let refused = store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(1), at(9));
+        assert!(matches!(refused, Err(ReservationError::HeldBy { owner, .. }) if owner == "bob"));
+        let reservations = store.env_reservations("env-1").unwrap();
+        assert_eq!(reservations.len(), 2);
+        assert_eq!(reservations[1].lease_end, Some(LeaseEnd::Released));
+
+        // A lapsed lease on the service counts as free.
+        store.apply_env_leases(&[lease(Some("bob"), Some(at(9)))], at(10)).unwrap();
+        let env = store.reserve_env("env-1", "alice", "回归", TimeDelta::hours(1), at(10)).unwrap();
+        assert_eq!(env.owner.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn deploying_an_idle_env_holds_it_on_the_service_until_done() {
+        let store = store_with_env();
+        let (endpoints, requests) = env_service(r#"[{"id": "env-1"}]"#);
+        let leases = LeaseTool::new(store.clone(), RequestTool::new().unwrap(), Some(endpoints));
+
+        let env = block_on(leases.begin_deploy("env-1", "alice", at(8))).unwrap();
+        assert_eq!((env.status, env.owner.as_deref()), (EnvStatus::Deploying, Some("alice")));
+        let env = block_on(leases.finish_deploy("env-1")).unwrap();
+        assert_eq!((env.status, env.owner), (EnvStatus::Idle, None));
+
+        let requests = requests.lock().unwrap();
+        let paths: Vec<&str> =
+            requests.iter().map(|request| request.split(' ').nth(1).unwrap()).collect();
+        assert_eq!(paths, ["/envs", "/envs/env-1/reserve", "/envs/env-1/release"]);
+        assert!(requests[1].contains(r#""owner":"alice""#), "{}", requests[1]);
+        assert!(requests[1].contains(DEPLOY_REASON), "{}", requests[1]);
+    }
+
+    #[test]
+    fn deploying_an_env_held_elsewhere_is_refused_after_refreshing() {
+        let store = store_with_env();
+        let (endpoints, requests) = env_service(
+            r#"[{"id": "env-1", "owner": "bob", "lease_until": "2026-04-01T12:00:00Z"}]"#,
+        );
+        let leases = LeaseTool::new(store.clone(), RequestTool::new().unwrap(), Some(endpoints));
+
+        // Still idle here: the holder is only known to the service so far.
+        let refused = block_on(leases.begin_deploy("env-1", "alice", at(8)));
+        assert!(matches!(refused, Err(ReservationError::HeldBy { owner, .. }) if owner == "bob"));
+        let env = store.virtual_envs().get("env-1").unwrap().unwrap();
+        assert_eq!((env.status, env.owner.as_deref()), (EnvStatus::Occupied, Some("bob")));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn deploying_a_held_env_confirms_the_hold_and_keeps_it() {
+        let store = store_with_env();
+        let (endpoints, requests) = env_service(
+            r#"[{"id": "env-1", "owner": "alice", "lease_until": "2026-04-01T12:00:00Z"}]"#,
+        );
+        let leases = LeaseTool::new(store.clone(), RequestTool::new().unwrap(), Some(endpoints));
+
+        let env = block_on(leases.begin_deploy("env-1", "alice", at(8))).unwrap();
+        assert_eq!((env.status, env.lease_until), (EnvStatus::Deploying, Some(at(12))));
+        let env = block_on(leases.finish_deploy("env-1")).unwrap();
+        assert_eq!((env.status, env.owner.as_deref()), (EnvStatus::Occupied, Some("alice")));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+}
@@ -1,12 +1,20 @@
+mod chart;
 mod codehub;
 mod dts;
 mod excel;
 mod hive;
+mod record;
 mod request;
 mod requirement;
+mod store;
+mod sync;
+pub use chart::*;
 pub use codehub::*;
 pub use dts::*;
 pub use excel::*;
 pub use hive::*;
+pub use record::*;
 pub use request::*;
 pub use requirement::*;
+pub use store::*;
+pub use sync::*;
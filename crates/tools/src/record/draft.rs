@@ -0,0 +1,140 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::{DateTime, Utc};
+
+use crate::Status;
+
+/// Prefix of the ids given to records created locally without one.
+pub const LOCAL_ID_PREFIX: &str = "LOCAL-";
+
+/// Milliseconds of the last local id handed out.
+static LAST_LOCAL_ID: AtomicI64 = AtomicI64::new(0);
+
+/// A new local id not rejected by `taken`. Ids carry the creation time down
+/// to the millisecond and are strictly increasing within a run, so records
+/// created in quick succession never share one.
+pub fn local_id(taken: impl Fn(&str) -> bool) -> String {
+    loop {
+        let now = Utc::now().timestamp_millis();
+        let previous = LAST_LOCAL_ID
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(now.max(last + 1)))
+            .unwrap_or_default();
+        let millis = now.max(previous + 1);
+        let time = DateTime::from_timestamp_millis(millis).unwrap_or_default();
+        let id = format!("{}{}", LOCAL_ID_PREFIX, time.format("%Y%m%d%H%M%S%3f"));
+        if !taken(&id) {
+            return id;
+        }
+    }
+}
+
+/// The validation errors every record form has, so [`DraftCheck`] can
+/// report them for any form.
+pub trait FormError {
+    fn required(label: &'static str) -> Self;
+
+    fn duplicate_id(id: String) -> Self;
+
+    /// Label of the form field the error belongs to.
+    fn field(&self) -> &'static str;
+}
+
+/// Collects every problem found in a draft, not just the first.
+pub struct DraftCheck<E> {
+    errors: Vec<E>,
+}
+
+impl<E: FormError> Default for DraftCheck<E> {
+    fn default() -> Self {
+        Self { errors: Vec::new() }
+    }
+}
+
+impl<E: FormError> DraftCheck<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, error: impl Into<E>) {
+        self.errors.push(error.into());
+    }
+
+    /// `value` trimmed, reporting the field if that leaves it empty.
+    pub fn text<'a>(&mut self, label: &'static str, value: &'a str) -> &'a str {
+        let value = value.trim();
+        if value.is_empty() {
+            self.push(E::required(label));
+        }
+        value
+    }
+
+    /// `value`, reporting the field if it is not set.
+    pub fn required<T>(&mut self, label: &'static str, value: Option<T>) -> Option<T> {
+        if value.is_none() {
+            self.push(E::required(label));
+        }
+        value
+    }
+
+    /// The id of a new record: `id` as typed, or a new local id if it was
+    /// left empty. An id already in use is reported.
+    pub fn new_id(&mut self, id: &str, id_taken: impl Fn(&str) -> bool) -> String {
+        match id.trim() {
+            "" => local_id(id_taken),
+            id => {
+                if id_taken(id) {
+                    self.push(E::duplicate_id(id.to_string()));
+                }
+                id.to_string()
+            },
+        }
+    }
+
+    pub fn finish(self) -> Result<(), Vec<E>> {
+        if self.errors.is_empty() { Ok(()) } else { Err(self.errors) }
+    }
+
+    /// The problems found so far, to return early when a required value is
+    /// missing and the record can't be built.
+    pub fn into_errors(self) -> Vec<E> {
+        self.errors
+    }
+}
+
+/// The statuses a form offers: `initial` for a new record, the current
+/// status and its legal successors for an existing one.
+pub fn status_choices<S: Status>(current: Option<S>, initial: S) -> Vec<S> {
+    match current {
+        None => vec![initial],
+        Some(current) => {
+            let mut statuses = vec![current];
+            statuses.extend(current.next_states());
+            statuses
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::HashSet};
+
+    use super::*;
+
+    #[test]
+    fn local_ids_are_unique_and_increasing() {
+        let ids: Vec<String> = (0..100).map(|_| local_id(|_| false)).collect();
+        assert!(ids.iter().all(|id| id.starts_with(LOCAL_ID_PREFIX)));
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+    }
+
+    #[test]
+    fn local_id_skips_taken_ids() {
+        let tried = Cell::new(0);
+        local_id(|_| {
+            tried.set(tried.get() + 1);
+            tried.get() <= 3
+        });
+        assert_eq!(tried.get(), 4);
+    }
+}
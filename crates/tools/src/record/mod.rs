@@ -0,0 +1,4 @@
+mod draft;
+mod model;
+pub use draft::*;
+pub use model::*;
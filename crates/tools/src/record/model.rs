@@ -0,0 +1,377 @@
+use std::{cmp::Ordering, fmt};
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+
+use crate::{ExcelData, Issue, Job, MergeRequest, Requirement, TestCase, VirtualEnv};
+
+/// Common view over every record type, so that searching, sorting and
+/// exporting can be written once instead of per type.
+pub trait Record {
+    fn id(&self) -> &str;
+
+    fn title(&self) -> &str;
+
+    fn owner(&self) -> Option<&str> {
+        None
+    }
+
+    fn status(&self) -> &str;
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at()
+    }
+
+    /// Case-insensitive substring match against id, title, owner and status.
+    /// An empty query matches everything.
+    fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || [Some(self.id()), Some(self.title()), self.owner(), Some(self.status())]
+                .into_iter()
+                .flatten()
+                .any(|field| field.to_lowercase().contains(&query))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordField {
+    Id,
+    Title,
+    Owner,
+    Status,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl RecordField {
+    pub fn compare<T: Record + ?Sized>(&self, a: &T, b: &T) -> Ordering {
+        match self {
+            Self::Id => a.id().cmp(b.id()),
+            Self::Title => a.title().cmp(b.title()),
+            Self::Owner => a.owner().cmp(&b.owner()),
+            Self::Status => a.status().cmp(b.status()),
+            Self::CreatedAt => a.created_at().cmp(&b.created_at()),
+            Self::UpdatedAt => a.updated_at().cmp(&b.updated_at()),
+        }
+    }
+}
+
+pub fn search_records<'a, T: Record>(records: &'a [T], query: &str) -> Vec<&'a T> {
+    records.iter().filter(|record| record.matches(query)).collect()
+}
+
+pub fn sort_records<T: Record>(records: &mut [T], field: RecordField, descending: bool) {
+    records.sort_by(|a, b| {
+        let ordering = field.compare(a, b);
+        if descending { ordering.reverse() } else { ordering }
+    });
+}
+
+impl Record for MergeRequest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn owner(&self) -> Option<&str> {
+        Some(&self.author)
+    }
+
+    fn status(&self) -> &str {
+        self.status.label()
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        Some(self.created_at)
+    }
+
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at.or(Some(self.created_at))
+    }
+}
+
+impl Record for Issue {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn owner(&self) -> Option<&str> {
+        Some(&self.assignee)
+    }
+
+    fn status(&self) -> &str {
+        self.status.label()
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        Some(self.created_at)
+    }
+
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at.or(self.resolved_at).or(Some(self.created_at))
+    }
+}
+
+impl Record for Requirement {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn owner(&self) -> Option<&str> {
+        Some(&self.owner)
+    }
+
+    fn status(&self) -> &str {
+        self.status.label()
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        Some(self.start_date)
+    }
+
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at.or(Some(self.start_date))
+    }
+}
+
+impl Record for TestCase {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &str {
+        &self.status
+    }
+}
+
+impl Record for VirtualEnv {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.name
+    }
+
+    fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    fn status(&self) -> &str {
+        self.status.label()
+    }
+}
+
+impl Record for Job {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &str {
+        self.status.label()
+    }
+}
+
+impl Record for ExcelData {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &str {
+        &self.file_type
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        // Written from the file's local modification time, see `From<&Workbook>`.
+        NaiveDateTime::parse_from_str(&self.created_at, "%Y-%m-%d %H:%M")
+            .ok()
+            .and_then(|datetime| datetime.and_local_timezone(Local).earliest())
+            .map(|datetime| datetime.to_utc())
+    }
+}
+
+/// A closed set of values such as a status or a severity.
+///
+/// Each value has a stable `code`, used for storage and by the remote
+/// systems, and a localized `label` shown in the UI.
+pub trait Coded: Copy + PartialEq + 'static {
+    /// Human readable name of the value set, used in error messages.
+    const KIND: &'static str;
+    const ALL: &'static [Self];
+
+    fn code(&self) -> &'static str;
+
+    fn label(&self) -> &'static str;
+
+    /// Accepts either the code (case-insensitive) or the localized label.
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|known| known.code().eq_ignore_ascii_case(value) || known.label() == value)
+    }
+}
+
+/// A [`Coded`] value that moves through a lifecycle, with transitions
+/// validated by [`Status::transition_to`].
+pub trait Status: Coded {
+    fn can_transition_to(&self, _next: Self) -> bool {
+        true
+    }
+
+    fn transition_to(self, next: Self) -> Result<Self, TransitionError> {
+        if self == next || self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(TransitionError { kind: Self::KIND, from: self.label(), to: next.label() })
+        }
+    }
+
+    /// The values reachable from `self` in a single transition.
+    fn next_states(&self) -> Vec<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|next| self != next && self.can_transition_to(*next))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionError {
+    pub kind: &'static str,
+    pub from: &'static str,
+    pub to: &'static str,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}不允许从「{}」变更为「{}」", self.kind, self.from, self.to)
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownStatus {
+    pub kind: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for UnknownStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "未知的{}: {}", self.kind, self.value)
+    }
+}
+
+impl std::error::Error for UnknownStatus {}
+
+/// Implements `Display`, `FromStr` and serde for a [`Coded`] type in terms
+/// of its code and label.
+macro_rules! impl_coded {
+    ($ty:ty) => {
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str($crate::Coded::label(self))
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = $crate::UnknownStatus;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                <$ty as $crate::Coded>::parse(value).ok_or_else(|| $crate::UnknownStatus {
+                    kind: <$ty as $crate::Coded>::KIND,
+                    value: value.to_string(),
+                })
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str($crate::Coded::code(self))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+pub(crate) use impl_coded;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JobStatus, MrStatus, RequirementStatus};
+
+    #[test]
+    fn staying_in_the_same_state_is_always_allowed() {
+        assert!(!MrStatus::Merged.can_transition_to(MrStatus::Merged));
+        assert_eq!(MrStatus::Merged.transition_to(MrStatus::Merged), Ok(MrStatus::Merged));
+    }
+
+    #[test]
+    fn refused_transitions_name_both_states() {
+        assert_eq!(
+            MrStatus::Merged.transition_to(MrStatus::Open),
+            Err(TransitionError { kind: "MR 状态", from: "已合入", to: "待合入" })
+        );
+        assert_eq!(
+            RequirementStatus::NotStarted.transition_to(RequirementStatus::Done),
+            Err(TransitionError { kind: "需求状态", from: "未开始", to: "已完成" })
+        );
+        assert_eq!(JobStatus::Queued.transition_to(JobStatus::Running), Ok(JobStatus::Running));
+    }
+
+    #[test]
+    fn statuses_round_trip_through_their_codes() {
+        for &status in RequirementStatus::ALL {
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status.code()));
+            assert_eq!(serde_json::from_str::<RequirementStatus>(&json).unwrap(), status);
+            assert_eq!(status.label().parse(), Ok(status));
+        }
+    }
+
+    #[test]
+    fn unknown_status_names_the_value_set() {
+        let err = serde_json::from_str::<MrStatus>("\"draft\"").unwrap_err();
+        assert_eq!(err.to_string(), "未知的MR 状态: draft");
+        assert_eq!(
+            " ".parse::<JobStatus>(),
+            Err(UnknownStatus { kind: "Job 状态", value: " ".to_string() })
+        );
+    }
+}
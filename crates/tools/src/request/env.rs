@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+
+use super::{RequestError, RequestTool, fetch::lookup};
+use crate::{EnvEndpoints, EnvLease};
+
+impl RequestTool {
+    /// Who holds which environment, as the environment service sees it.
+    pub async fn env_leases(
+        &self,
+        endpoints: &EnvEndpoints,
+    ) -> Result<Vec<EnvLease>, RequestError> {
+        let body = self.get(&endpoints.list_url).await?;
+        let value: Value =
+            serde_json::from_str(&body).map_err(|err| RequestError::schema("环境占用", err))?;
+        // Some services wrap the list in `data`, like the job responses.
+        let value = lookup(&value, "data").filter(|data| data.is_array()).unwrap_or(&value);
+        serde_json::from_value(value.clone()).map_err(|err| RequestError::schema("环境占用", err))
+    }
+
+    /// Hold `env_id` for `owner` until `lease_until`, or move the end of the
+    /// lease when `owner` already holds it. The service refuses with an HTTP
+    /// error when someone else holds the environment.
+    pub async fn reserve_env(
+        &self,
+        endpoints: &EnvEndpoints,
+        env_id: &str,
+        owner: &str,
+        reason: &str,
+        lease_until: DateTime<Utc>,
+    ) -> Result<(), RequestError> {
+        let body = json!({
+            "owner": owner,
+            "reason": reason,
+            "lease_until": lease_until,
+        });
+        self.post(&EnvEndpoints::url(&endpoints.reserve_url, env_id), &body).await?;
+        Ok(())
+    }
+
+    pub async fn release_env(
+        &self,
+        endpoints: &EnvEndpoints,
+        env_id: &str,
+        owner: &str,
+    ) -> Result<(), RequestError> {
+        let body = json!({ "owner": owner });
+        self.post(&EnvEndpoints::url(&endpoints.release_url, env_id), &body).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RequestError {
+    /// The server rejected our credentials, the user needs to log in again.
+    #[error("登录已过期，请重新登录")]
+    AuthExpired,
+    #[error("请求 {url} 失败: HTTP {status}")]
+    Http { status: u16, url: String },
+    #[error("网络错误: {0}")]
+    Network(#[from] reqwest::Error),
+    /// The response could not be mapped onto our models.
+    #[error("数据格式不匹配 ({context}): {message}")]
+    Schema { context: String, message: String },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl RequestError {
+    pub(crate) fn schema(context: impl Into<String>, message: impl ToString) -> Self {
+        Self::Schema { context: context.into(), message: message.to_string() }
+    }
+}
@@ -0,0 +1,463 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+
+use super::{RequestError, RequestTool};
+use crate::{Issue, MergeRequest, Requirement, local_midnight};
+
+/// How to read one remote list endpoint and map its items onto a model.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointConfig {
+    pub url: String,
+    /// Where new records are POSTed to, see [`RequestTool::create`]. Records
+    /// can't be pushed when this is unset.
+    pub create_url: Option<String>,
+    /// Dotted path to the item array in the response, empty if the response
+    /// body is the array itself.
+    pub items_path: String,
+    pub pagination: Option<Pagination>,
+    pub filters: FilterParams,
+    pub mapping: FieldMapping,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pagination {
+    pub page_param: String,
+    pub size_param: String,
+    pub page_size: usize,
+    /// Page number of the first page, usually 0 or 1.
+    pub first_page: usize,
+    /// Dotted path to the total item count, if the server reports one.
+    pub total_path: Option<String>,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            page_param: "page".into(),
+            size_param: "page_size".into(),
+            page_size: 100,
+            first_page: 1,
+            total_path: None,
+        }
+    }
+}
+
+/// Query parameter names the server uses for the filters in [`FetchQuery`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterParams {
+    pub since: String,
+    pub until: String,
+    /// The "modified since" parameter. Without one, the endpoint can't be
+    /// asked for changes only and incremental syncs fetch everything.
+    pub updated_since: Option<String>,
+    pub owner: String,
+    pub status: String,
+    /// Format of the date parameters, in `chrono` syntax.
+    pub date_format: String,
+}
+
+impl Default for FilterParams {
+    fn default() -> Self {
+        Self {
+            since: "start_date".into(),
+            until: "end_date".into(),
+            updated_since: None,
+            owner: "owner".into(),
+            status: "status".into(),
+            date_format: "%Y-%m-%d".into(),
+        }
+    }
+}
+
+/// Maps remote field names and values onto the fields of a model.
+///
+/// Fields without an entry in `fields` are read from the remote field with
+/// the same name. `values` translates remote values of a field, e.g. a DTS
+/// status code, into the value our model expects. When several remote values
+/// map to the same local one, pushing sends the first of them in sort order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldMapping {
+    pub fields: HashMap<String, String>,
+    pub values: HashMap<String, BTreeMap<String, String>>,
+}
+
+/// Server-side filters for a fetch. `None` means no filter.
+#[derive(Debug, Clone, Default)]
+pub struct FetchQuery {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// Only records modified on or after this date. Ignored by endpoints
+    /// without [`FilterParams::updated_since`].
+    pub updated_since: Option<NaiveDate>,
+    pub owner: Option<String>,
+    pub status: Option<String>,
+}
+
+/// A model that can be fetched from a remote list endpoint.
+pub trait Fetchable: DeserializeOwned {
+    const KIND: &'static str;
+    const FIELDS: &'static [&'static str];
+    /// Fields holding timestamps, normalized to RFC 3339 before deserializing
+    /// since remote systems rarely agree on a date format.
+    const TIMESTAMP_FIELDS: &'static [&'static str];
+
+    /// Fill in fields derived from the fetched ones.
+    fn normalize(&mut self) {}
+}
+
+impl Fetchable for Issue {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "severity",
+        "status",
+        "created_at",
+        "resolved_at",
+        "assignee",
+        "updated_at",
+        "reopen_count",
+    ];
+    const KIND: &'static str = "DTS 问题单";
+    const TIMESTAMP_FIELDS: &'static [&'static str] = &["created_at", "resolved_at", "updated_at"];
+}
+
+impl Fetchable for MergeRequest {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "author",
+        "created_at",
+        "add_lines",
+        "del_lines",
+        "status",
+        "updated_at",
+        "repository",
+        "merged_at",
+    ];
+    const KIND: &'static str = "MR";
+    const TIMESTAMP_FIELDS: &'static [&'static str] = &["created_at", "updated_at", "merged_at"];
+}
+
+impl Fetchable for Requirement {
+    const FIELDS: &'static [&'static str] =
+        &["id", "title", "version", "start_date", "end_date", "status", "owner", "updated_at"];
+    const KIND: &'static str = "需求";
+    const TIMESTAMP_FIELDS: &'static [&'static str] = &["start_date", "end_date", "updated_at"];
+
+    fn normalize(&mut self) {
+        self.set_schedule(self.start_date, self.end_date);
+    }
+}
+
+impl RequestTool {
+    pub async fn fetch_dts_issues(
+        &self,
+        endpoint: &EndpointConfig,
+        query: &FetchQuery,
+    ) -> Result<Vec<Issue>, RequestError> {
+        self.fetch(endpoint, query).await
+    }
+
+    pub async fn fetch_merge_requests(
+        &self,
+        endpoint: &EndpointConfig,
+        query: &FetchQuery,
+    ) -> Result<Vec<MergeRequest>, RequestError> {
+        self.fetch(endpoint, query).await
+    }
+
+    pub async fn fetch_requirements(
+        &self,
+        endpoint: &EndpointConfig,
+        query: &FetchQuery,
+    ) -> Result<Vec<Requirement>, RequestError> {
+        self.fetch(endpoint, query).await
+    }
+
+    /// Fetch every page of `endpoint` and map the items onto `T`. Paging stops
+    /// once the reported total is reached, or without a total on a short page,
+    /// and always on an empty or repeated page.
+    pub async fn fetch<T: Fetchable>(
+        &self,
+        endpoint: &EndpointConfig,
+        query: &FetchQuery,
+    ) -> Result<Vec<T>, RequestError> {
+        let params = endpoint.filters.params(query);
+        let Some(pagination) = &endpoint.pagination else {
+            let body = self.get_with_query(&endpoint.url, &params).await?;
+            let (items, ..) = parse_page::<T>(endpoint, &body, None)?;
+            return Ok(items);
+        };
+
+        let mut records = Vec::new();
+        let mut page = pagination.first_page;
+        let mut previous: Option<Vec<Value>> = None;
+        loop {
+            let mut page_params = params.clone();
+            page_params.push((pagination.page_param.clone(), page.to_string()));
+            page_params.push((pagination.size_param.clone(), pagination.page_size.to_string()));
+            let body = self.get_with_query(&endpoint.url, &page_params).await?;
+            let (items, total, raw) =
+                parse_page::<T>(endpoint, &body, pagination.total_path.as_deref())?;
+
+            match next_page(&raw, previous.as_deref(), records.len(), total, pagination.page_size) {
+                PageStep::Drop => break,
+                PageStep::Last => {
+                    records.extend(items);
+                    break;
+                },
+                PageStep::More => records.extend(items),
+            }
+            previous = Some(raw);
+            page += 1;
+        }
+        Ok(records)
+    }
+}
+
+/// What [`RequestTool::fetch`] does with a page it fetched.
+#[derive(Debug, PartialEq, Eq)]
+enum PageStep {
+    /// Discard the page and stop.
+    Drop,
+    /// Keep the page and stop.
+    Last,
+    /// Keep the page and fetch the next one.
+    More,
+}
+
+/// Decide on `page`, given the page before it and how many records the
+/// earlier pages held.
+fn next_page(
+    page: &[Value],
+    previous: Option<&[Value]>,
+    fetched: usize,
+    total: Option<usize>,
+    page_size: usize,
+) -> PageStep {
+    // A server that ignores the page parameter answers every page with the
+    // first one; stop instead of looping on it.
+    if page.is_empty() || previous == Some(page) {
+        return PageStep::Drop;
+    }
+    let done = match total {
+        // Trust the count over the page size, which servers cap.
+        Some(total) => fetched + page.len() >= total,
+        None => page.len() < page_size,
+    };
+    if done { PageStep::Last } else { PageStep::More }
+}
+
+impl FilterParams {
+    pub(crate) fn params(&self, query: &FetchQuery) -> Vec<(String, String)> {
+        let mut params = Vec::new();
+        if let Some(since) = query.since {
+            params.push((self.since.clone(), since.format(&self.date_format).to_string()));
+        }
+        if let Some(until) = query.until {
+            params.push((self.until.clone(), until.format(&self.date_format).to_string()));
+        }
+        if let (Some(param), Some(updated_since)) = (&self.updated_since, query.updated_since) {
+            params.push((param.clone(), updated_since.format(&self.date_format).to_string()));
+        }
+        if let Some(owner) = &query.owner {
+            params.push((self.owner.clone(), owner.clone()));
+        }
+        if let Some(status) = &query.status {
+            params.push((self.status.clone(), status.clone()));
+        }
+        params
+    }
+}
+
+impl FieldMapping {
+    /// Build the JSON object our model deserializes from out of a remote item.
+    pub fn apply<T: Fetchable>(&self, item: &Value) -> Map<String, Value> {
+        let mut object = Map::new();
+        for field in T::FIELDS {
+            let path = self.fields.get(*field).map(String::as_str).unwrap_or(field);
+            let Some(mut value) = lookup(item, path).cloned() else {
+                continue;
+            };
+            if let Some(mapped) = value
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| value.as_i64().map(|value| value.to_string()))
+                .and_then(|key| self.values.get(*field)?.get(&key))
+            {
+                value = Value::String(mapped.clone());
+            }
+            if T::TIMESTAMP_FIELDS.contains(field) {
+                value = normalize_timestamp(value);
+            }
+            // Our ids are strings, many systems number their records.
+            if *field == "id"
+                && let Value::Number(id) = &value
+            {
+                value = Value::String(id.to_string());
+            }
+            object.insert(field.to_string(), value);
+        }
+        object
+    }
+}
+
+fn parse_page<T: Fetchable>(
+    endpoint: &EndpointConfig,
+    body: &str,
+    total_path: Option<&str>,
+) -> Result<(Vec<T>, Option<usize>, Vec<Value>), RequestError> {
+    let body: Value =
+        serde_json::from_str(body).map_err(|err| RequestError::schema(T::KIND, err))?;
+    let total = total_path
+        .and_then(|path| lookup(&body, path))
+        .and_then(|total| total.as_u64())
+        .map(|total| total as usize);
+    let items = lookup(&body, &endpoint.items_path).and_then(Value::as_array).ok_or_else(|| {
+        RequestError::schema(T::KIND, format!("`{}` 不是数组", endpoint.items_path))
+    })?;
+
+    let records = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let mut record: T = serde_json::from_value(Value::Object(
+                endpoint.mapping.apply::<T>(item),
+            ))
+            .map_err(|err| RequestError::schema(format!("{} 第 {} 条", T::KIND, index + 1), err))?;
+            record.normalize();
+            Ok(record)
+        })
+        .collect::<Result<Vec<T>, RequestError>>()?;
+    Ok((records, total, items.clone()))
+}
+
+/// Look up a dotted path such as `data.items` or `fields.owner.name`.
+pub(super) fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|segment| !segment.is_empty()).try_fold(value, |value, segment| {
+        match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => value.get(segment),
+        }
+    })
+}
+
+/// Convert the date formats we have seen from remote systems to RFC 3339:
+/// RFC 3339 itself, `YYYY-MM-DD HH:MM:SS`, plain dates and epoch milliseconds.
+/// Plain dates become local midnight, like dates entered in the forms.
+/// Anything else is passed through so deserializing reports the bad value.
+fn normalize_timestamp(value: Value) -> Value {
+    let timestamp = match &value {
+        Value::Number(millis) => millis.as_i64().and_then(DateTime::<Utc>::from_timestamp_millis),
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|datetime| datetime.and_utc())
+            })
+            .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().map(local_midnight)),
+        _ => None,
+    };
+    timestamp.map(|timestamp| Value::String(timestamp.to_rfc3339())).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{IssueStatus, Severity};
+
+    fn page(ids: std::ops::Range<u32>) -> Vec<Value> {
+        ids.map(|id| json!({ "id": id })).collect()
+    }
+
+    #[test]
+    fn lookup_follows_objects_and_array_indices() {
+        let body = json!({ "data": { "items": [{ "owner": { "name": "张三" } }] } });
+        assert_eq!(lookup(&body, "data.items.0.owner.name"), Some(&json!("张三")));
+        assert_eq!(lookup(&body, ""), Some(&body));
+        assert_eq!(lookup(&body, "data.items.1"), None);
+        assert_eq!(lookup(&body, "data.items.first"), None);
+        assert_eq!(lookup(&body, "data.missing"), None);
+    }
+
+    #[test]
+    fn timestamps_are_normalized_to_rfc3339() {
+        let expected = json!("2024-03-05T08:30:00+00:00");
+        assert_eq!(normalize_timestamp(json!("2024-03-05T16:30:00+08:00")), expected);
+        assert_eq!(normalize_timestamp(json!("2024-03-05 08:30:00")), expected);
+        assert_eq!(normalize_timestamp(json!(1_709_627_400_000_i64)), expected);
+        let midnight = local_midnight(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap());
+        assert_eq!(normalize_timestamp(json!("2024-03-05")), json!(midnight.to_rfc3339()));
+        assert_eq!(normalize_timestamp(json!("下周一")), json!("下周一"));
+        assert_eq!(normalize_timestamp(Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn mapping_renames_fields_translates_values_and_stringifies_ids() {
+        let mapping = FieldMapping {
+            fields: HashMap::from([
+                ("assignee".to_string(), "owner.name".to_string()),
+                ("status".to_string(), "state".to_string()),
+            ]),
+            values: HashMap::from([(
+                "status".to_string(),
+                BTreeMap::from([("3".to_string(), "regression".to_string())]),
+            )]),
+        };
+        let item = json!({
+            "id": 1024,
+            "title": "登录页面显示异常",
+            "severity": "严重",
+            "state": 3,
+            "created_at": "2024-03-05 08:30:00",
+            "owner": { "name": "张三" },
+            "ignored": true,
+        });
+
+        let object = mapping.apply::<Issue>(&item);
+        assert_eq!(object["id"], json!("1024"));
+        assert_eq!(object["status"], json!("regression"));
+        assert_eq!(object["assignee"], json!("张三"));
+        assert_eq!(object["created_at"], json!("2024-03-05T08:30:00+00:00"));
+        assert!(!object.contains_key("ignored"));
+        assert!(!object.contains_key("resolved_at"));
+
+        let issue: Issue = serde_json::from_value(Value::Object(object)).unwrap();
+        assert_eq!(issue.status, IssueStatus::Regression);
+        assert_eq!(issue.severity, Severity::Serious);
+    }
+
+    #[test]
+    fn paging_stops_once_the_total_is_reached() {
+        assert_eq!(next_page(&page(0..10), None, 0, Some(25), 10), PageStep::More);
+        assert_eq!(next_page(&page(20..25), None, 20, Some(25), 10), PageStep::Last);
+        // Servers cap the page size, so a short page doesn't end the fetch
+        // while the total says more are coming.
+        assert_eq!(next_page(&page(0..5), None, 0, Some(25), 10), PageStep::More);
+    }
+
+    #[test]
+    fn paging_without_a_total_stops_on_a_short_page() {
+        assert_eq!(next_page(&page(0..10), None, 0, None, 10), PageStep::More);
+        assert_eq!(next_page(&page(10..13), None, 10, None, 10), PageStep::Last);
+    }
+
+    #[test]
+    fn paging_drops_empty_and_repeated_pages() {
+        assert_eq!(next_page(&[], None, 10, Some(25), 10), PageStep::Drop);
+        let first = page(0..10);
+        assert_eq!(next_page(&first, Some(&first), 10, Some(25), 10), PageStep::Drop);
+        assert_eq!(next_page(&first, Some(&first), 10, None, 10), PageStep::Drop);
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::Utc;
+use serde_json::{Value, json};
+
+use super::{RequestError, RequestTool, fetch::lookup};
+use crate::{Job, JobEndpoints, JobParams, JobProgress, JobResume};
+
+impl RequestTool {
+    /// Launch a job with `params`. The server answers with the new job's id,
+    /// either at the top level or under `data`.
+    pub async fn launch_job(
+        &self,
+        endpoints: &JobEndpoints,
+        name: &str,
+        params: &JobParams,
+    ) -> Result<Job, RequestError> {
+        let body = json!({
+            "name": name,
+            "branch": params.branch,
+            "env": params.env_id,
+            "suite": params.suite,
+            "tags": params.tags,
+        });
+        let response = self.post(&endpoints.launch_url, &body).await?;
+        let id = job_id(&response)?;
+        Ok(Job::launched(id, name.to_string(), params.clone(), Utc::now()))
+    }
+
+    /// Resume a failed job as a new job that reruns only its failed cases
+    /// from its checkpoint.
+    pub async fn resume_job(
+        &self,
+        endpoints: &JobEndpoints,
+        job: &Job,
+        resume: &JobResume,
+    ) -> Result<Job, RequestError> {
+        let url = JobEndpoints::url(&endpoints.resume_url, &job.id);
+        let response = self.post(&url, resume).await?;
+        let id = job_id(&response)?;
+        let mut resumed = Job::launched(
+            id,
+            format!("{}（续跑）", job.name),
+            job.params.clone().unwrap_or_default(),
+            Utc::now(),
+        );
+        resumed.resumed_from = Some(job.id.clone());
+        Ok(resumed)
+    }
+
+    pub async fn job_progress(
+        &self,
+        endpoints: &JobEndpoints,
+        id: &str,
+    ) -> Result<JobProgress, RequestError> {
+        let body = self.get(&JobEndpoints::url(&endpoints.status_url, id)).await?;
+        let value: Value =
+            serde_json::from_str(&body).map_err(|err| RequestError::schema("Job 进度", err))?;
+        // Some services wrap the object in `data`, like the create responses.
+        let value = lookup(&value, "data").filter(|data| data.is_object()).unwrap_or(&value);
+        serde_json::from_value(value.clone()).map_err(|err| RequestError::schema("Job 进度", err))
+    }
+}
+
+fn job_id(response: &str) -> Result<String, RequestError> {
+    let value: Value =
+        serde_json::from_str(response).map_err(|err| RequestError::schema("Job 编号", err))?;
+    lookup(&value, "id")
+        .or_else(|| lookup(&value, "data.id"))
+        .and_then(|id| match id {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        })
+        .ok_or_else(|| RequestError::schema("Job 编号", "响应中没有 Job 编号"))
+}
@@ -1,2 +1,9 @@
+mod env;
+mod error;
+mod fetch;
+mod job;
 mod model;
+mod push;
+pub use error::*;
+pub use fetch::*;
 pub use model::*;
@@ -1,10 +1,357 @@
-pub struct RequestTool {}
+use std::{
+    convert::Infallible,
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use anyhow::{Context as _, Result};
+use cookie_store::{Cookie, CookieStore, RawCookie};
+use reqwest::{RequestBuilder, StatusCode, Url, header::HeaderValue};
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Runtime;
+
+use super::RequestError;
+
+/// A long-lived HTTP client shared by all tools.
+///
+/// Every request goes through the same connection pool and cookie jar, and
+/// the bearer token (if any) is attached automatically. When created with
+/// [`RequestTool::with_session_file`] the cookies and token are written back
+/// to disk whenever they change, readable by the current user only, so a
+/// login survives restarts.
+///
+/// reqwest needs a Tokio reactor, so requests run on a small runtime owned by
+/// the tool; the returned futures can be awaited from any executor.
+#[derive(Clone)]
+pub struct RequestTool {
+    client: reqwest::Client,
+    jar: Arc<CookieJar>,
+    token: Arc<Mutex<Option<String>>>,
+    session_file: Option<PathBuf>,
+    /// What was last written to `session_file`, to skip unchanged writes.
+    saved_session: Arc<Mutex<Vec<u8>>>,
+    runtime: Arc<Runtime>,
+}
+
 impl RequestTool {
-    fn new() -> Self {
-        Self {}
+    pub fn new() -> Result<Self> {
+        Self::build(Session::default(), None)
+    }
+
+    /// Create a client whose session is loaded from, and saved to, `path`.
+    pub fn with_session_file(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let (session, saved) = match std::fs::read(&path) {
+            Ok(bytes) => match serde_json::from_slice(&bytes) {
+                Ok(session) => (session, bytes),
+                Err(err) => {
+                    tracing::warn!("Ignoring unreadable session {}: {}", path.display(), err);
+                    (Session::default(), Vec::new())
+                },
+            },
+            Err(_) => (Session::default(), Vec::new()),
+        };
+        let tool = Self::build(session, Some(path))?;
+        *tool.saved_session.lock().unwrap_or_else(|err| err.into_inner()) = saved;
+        Ok(tool)
+    }
+
+    fn build(session: Session, session_file: Option<PathBuf>) -> Result<Self> {
+        let cookies =
+            CookieStore::from_cookies(session.cookies.into_iter().map(Ok::<_, Infallible>), false)
+                .unwrap_or_default();
+        let jar = Arc::new(CookieJar(Mutex::new(cookies)));
+        let client = reqwest::Client::builder()
+            .cookie_provider(jar.clone())
+            .build()
+            .context("failed to build HTTP client")?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("request-tool")
+            .enable_all()
+            .build()
+            .context("failed to start HTTP runtime")?;
+
+        Ok(Self {
+            client,
+            jar,
+            token: Arc::new(Mutex::new(session.token)),
+            session_file,
+            saved_session: Arc::new(Mutex::new(Vec::new())),
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    pub async fn get(&self, url: &str) -> Result<String, RequestError> {
+        self.send(self.client.get(url)).await
+    }
+
+    pub async fn get_with_query(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+    ) -> Result<String, RequestError> {
+        self.send(self.client.get(url).query(query)).await
+    }
+
+    pub async fn post(&self, url: &str, body: &impl Serialize) -> Result<String, RequestError> {
+        self.send(self.client.post(url).json(body)).await
+    }
+
+    /// Log in with a username and password. The server is expected to answer
+    /// with a session cookie, and optionally a `token` field in the body.
+    pub async fn login(
+        &self,
+        url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<(), RequestError> {
+        let body = serde_json::json!({
+            "username": username,
+            "password": password,
+        });
+        let response = self.post(url, &body).await?;
+        if let Some(token) = serde_json::from_str::<serde_json::Value>(&response)
+            .ok()
+            .and_then(|body| body.get("token")?.as_str().map(str::to_string))
+        {
+            self.set_token(Some(token));
+        }
+        self.save_session();
+        Ok(())
+    }
+
+    pub fn logout(&self) {
+        self.jar.store().clear();
+        self.set_token(None);
+    }
+
+    pub fn token(&self) -> Option<String> {
+        self.token.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    /// Replace the bearer token, saving it with the session.
+    pub fn set_token(&self, token: Option<String>) {
+        *self.token.lock().unwrap_or_else(|err| err.into_inner()) = token;
+        self.save_session();
+    }
+
+    /// The cookies that would be sent with a request to `url`.
+    pub fn cookies(&self, url: &str) -> Vec<(String, String)> {
+        let Ok(url) = Url::parse(url) else {
+            return Vec::new();
+        };
+        self.jar
+            .store()
+            .get_request_values(&url)
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
     }
 
-    fn post() {}
+    pub fn add_cookie(&self, url: &str, cookie: &str) -> Result<()> {
+        let url = Url::parse(url)?;
+        self.jar.store().parse(cookie, &url)?;
+        self.save_session();
+        Ok(())
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<String, RequestError> {
+        let request = match self.token() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        let body = self
+            .runtime
+            .spawn(async move {
+                let response = request.send().await?;
+                let status = response.status();
+                if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+                    return Err(RequestError::AuthExpired);
+                }
+                if !status.is_success() {
+                    let url = response.url().to_string();
+                    return Err(RequestError::Http { status: status.as_u16(), url });
+                }
+                Ok(response.text().await?)
+            })
+            .await
+            .context("HTTP task panicked")??;
+        self.save_session();
+        Ok(body)
+    }
+
+    fn save_session(&self) {
+        let Some(path) = &self.session_file else {
+            return;
+        };
+        if let Err(err) = self.write_session(path) {
+            tracing::error!("Failed to save session to {}: {:?}", path.display(), err);
+        }
+    }
+
+    fn write_session(&self, path: &Path) -> Result<()> {
+        let token = self.token();
+        let json = {
+            let store = self.jar.store();
+            serde_json::to_vec_pretty(&SessionRef {
+                cookies: store.iter_unexpired().collect(),
+                token: token.as_deref(),
+            })?
+        };
+        let mut saved = self.saved_session.lock().unwrap_or_else(|err| err.into_inner());
+        if *saved == json {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_private(path, &json)?;
+        *saved = json;
+        Ok(())
+    }
+}
 
-    fn get() {}
+/// Write `bytes` to `path` so that only the current user can read it, as it
+/// holds the login token.
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt as _, PermissionsExt as _};
+        options.mode(0o600);
+        // `mode` only applies to new files; tighten one an older version wrote.
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(bytes)
+}
+
+#[derive(Default, Deserialize)]
+struct Session {
+    cookies: Vec<Cookie<'static>>,
+    token: Option<String>,
+}
+
+/// Borrowed form of [`Session`]. Unlike `CookieStore`'s own serde impl this
+/// keeps session cookies too, since those are what a login usually returns.
+#[derive(Serialize)]
+struct SessionRef<'a> {
+    cookies: Vec<&'a Cookie<'static>>,
+    token: Option<&'a str>,
+}
+
+struct CookieJar(Mutex<CookieStore>);
+
+impl CookieJar {
+    fn store(&self) -> MutexGuard<'_, CookieStore> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|header| header.to_str().ok())
+            .filter_map(|header| RawCookie::parse(header.to_string()).ok());
+        self.store().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .store()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() { None } else { HeaderValue::from_str(&header).ok() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::cookie::CookieStore as _;
+
+    use super::*;
+
+    fn session_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tasks-mine-session-{}", std::process::id()));
+        let path = dir.join(name);
+        _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn token_and_cookies_survive_a_restart() {
+        let path = session_file("restart.json");
+        let tool = RequestTool::with_session_file(&path).unwrap();
+        tool.set_token(Some("secret".to_string()));
+        assert!(path.exists(), "the token alone is saved");
+        tool.add_cookie("https://dts.example.com/", "SESSION=abc; Path=/").unwrap();
+        drop(tool);
+
+        let tool = RequestTool::with_session_file(&path).unwrap();
+        assert_eq!(tool.token().as_deref(), Some("secret"));
+        assert_eq!(tool.cookies("https://dts.example.com/issues"), [(
+            "SESSION".to_string(),
+            "abc".to_string()
+        )]);
+        assert!(tool.cookies("https://codehub.example.com/").is_empty());
+
+        tool.logout();
+        let tool = RequestTool::with_session_file(&path).unwrap();
+        assert_eq!(tool.token(), None);
+        assert!(tool.cookies("https://dts.example.com/").is_empty());
+    }
+
+    #[test]
+    fn unreadable_session_starts_logged_out() {
+        let path = session_file("unreadable.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not json").unwrap();
+
+        let tool = RequestTool::with_session_file(&path).unwrap();
+        assert_eq!(tool.token(), None);
+        tool.set_token(Some("secret".to_string()));
+        let reloaded = RequestTool::with_session_file(&path).unwrap();
+        assert_eq!(reloaded.token().as_deref(), Some("secret"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn session_file_is_private() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let path = session_file("private.json");
+        let tool = RequestTool::with_session_file(&path).unwrap();
+        tool.set_token(Some("secret".to_string()));
+        assert_eq!(mode(&path), 0o600);
+
+        // A file an older version left world-readable is tightened on write.
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_private(&path, b"{}").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(std::fs::read(&path).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn cookie_jar_keeps_response_cookies_per_host() {
+        let jar = CookieJar(Mutex::new(CookieStore::default()));
+        let url = Url::parse("https://dts.example.com/login").unwrap();
+        let headers = [
+            HeaderValue::from_static("SESSION=abc; Path=/"),
+            HeaderValue::from_static("theme=dark; Path=/"),
+            HeaderValue::from_static("not a cookie"),
+        ];
+        jar.set_cookies(&mut headers.iter(), &url);
+
+        let header = jar.cookies(&Url::parse("https://dts.example.com/issues").unwrap()).unwrap();
+        let mut cookies: Vec<&str> = header.to_str().unwrap().split("; ").collect();
+        cookies.sort();
+        assert_eq!(cookies, ["SESSION=abc", "theme=dark"]);
+        assert_eq!(jar.cookies(&Url::parse("https://codehub.example.com/").unwrap()), None);
+    }
 }
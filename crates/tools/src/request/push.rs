@@ -0,0 +1,158 @@
+use anyhow::anyhow;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{EndpointConfig, Fetchable, FieldMapping, RequestError, RequestTool, fetch::lookup};
+
+impl RequestTool {
+    /// POST `record` to the endpoint's `create_url`, mapped back onto the
+    /// remote field names. The local id is left out, the server assigns its
+    /// own. Returns that id, found either at the top level of the response
+    /// or under `data`.
+    ///
+    /// A response without an id is an error even though the record was
+    /// created: stored under its local id, the next sync would add it again.
+    pub async fn create<T: Fetchable + Serialize>(
+        &self,
+        endpoint: &EndpointConfig,
+        record: &T,
+    ) -> Result<String, RequestError> {
+        let url =
+            endpoint.create_url.as_deref().ok_or_else(|| anyhow!("未配置{}的创建地址", T::KIND))?;
+        let fields: Vec<&str> = T::FIELDS.iter().copied().filter(|field| *field != "id").collect();
+        let body = endpoint.mapping.unapply_fields(record, &fields).map_err(|err| anyhow!(err))?;
+        let response = self.post(url, &body).await?;
+
+        let id_path = endpoint.mapping.fields.get("id").map(String::as_str).unwrap_or("id");
+        created_id(&response, id_path, T::KIND)
+    }
+}
+
+fn created_id(response: &str, id_path: &str, kind: &str) -> Result<String, RequestError> {
+    let context = format!("{}编号", kind);
+    let value: Value =
+        serde_json::from_str(response).map_err(|err| RequestError::schema(&context, err))?;
+    lookup(&value, id_path)
+        .or_else(|| lookup(&value, &format!("data.{}", id_path)))
+        .and_then(|id| match id {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        })
+        .ok_or_else(|| RequestError::schema(&context, format!("响应中没有 `{}`", id_path)))
+}
+
+impl FieldMapping {
+    /// The inverse of [`FieldMapping::apply`]: the remote JSON object for a
+    /// model, with fields renamed and values translated back.
+    pub fn unapply<T: Fetchable + Serialize>(
+        &self,
+        record: &T,
+    ) -> Result<Value, serde_json::Error> {
+        self.unapply_fields(record, T::FIELDS)
+    }
+
+    /// [`FieldMapping::unapply`] limited to `names`.
+    fn unapply_fields<T: Serialize>(
+        &self,
+        record: &T,
+        names: &[&str],
+    ) -> Result<Value, serde_json::Error> {
+        let Value::Object(fields) = serde_json::to_value(record)? else {
+            return Ok(Value::Object(Map::new()));
+        };
+
+        let mut object = Value::Object(Map::new());
+        for field in names {
+            let Some(mut value) = fields.get(*field).cloned() else {
+                continue;
+            };
+            if let Some(remote) = self.values.get(*field).and_then(|values| {
+                values.iter().find(|(_, local)| value.as_str() == Some(local.as_str()))
+            }) {
+                value = Value::String(remote.0.clone());
+            }
+            let path = self.fields.get(*field).map(String::as_str).unwrap_or(field);
+            insert(&mut object, path, value);
+        }
+        Ok(object)
+    }
+}
+
+/// Set a dotted path such as `fields.owner.name`, creating the objects on
+/// the way.
+fn insert(object: &mut Value, path: &str, value: Value) {
+    let mut segments = path.split('.').filter(|segment| !segment.is_empty()).peekable();
+    let mut current = object;
+    while let Some(segment) = segments.next() {
+        let Value::Object(map) = current else {
+            return;
+        };
+        if segments.peek().is_none() {
+            map.insert(segment.to_string(), value);
+            return;
+        }
+        current = map.entry(segment).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::MergeRequest;
+
+    #[test]
+    fn unapply_picks_the_same_remote_value_when_several_map_to_one() {
+        let mapping = FieldMapping {
+            fields: HashMap::from([("author".to_string(), "owner.name".to_string())]),
+            values: HashMap::from([(
+                "status".to_string(),
+                BTreeMap::from([
+                    ("opened".to_string(), "open".to_string()),
+                    ("draft".to_string(), "open".to_string()),
+                    ("reopened".to_string(), "open".to_string()),
+                    ("merged".to_string(), "merged".to_string()),
+                ]),
+            )]),
+        };
+        let mr: MergeRequest = serde_json::from_value(json!({
+            "id": "MR-1",
+            "title": "修复登录",
+            "author": "张三",
+            "created_at": "2024-03-05T08:30:00Z",
+            "add_lines": 3,
+            "del_lines": 1,
+            "status": "open",
+        }))
+        .unwrap();
+
+        let body = mapping.unapply_fields(&mr, &["id", "author", "status"]).unwrap();
+        assert_eq!(body, json!({ "id": "MR-1", "owner": { "name": "张三" }, "status": "draft" }));
+    }
+
+    #[test]
+    fn created_id_is_read_at_the_top_level_or_under_data() {
+        assert_eq!(created_id(r#"{"id": "DTS-7"}"#, "id", "DTS 问题单").unwrap(), "DTS-7");
+        assert_eq!(created_id(r#"{"data": {"key": 42}}"#, "key", "DTS 问题单").unwrap(), "42");
+        assert_eq!(
+            created_id(r#"{"data": {"issue": {"no": "DTS-8"}}}"#, "issue.no", "DTS 问题单")
+                .unwrap(),
+            "DTS-8"
+        );
+    }
+
+    #[test]
+    fn created_id_fails_without_an_id() {
+        for response in [r#"{"ok": true}"#, r#"{"id": null}"#, "created"] {
+            let err = created_id(response, "id", "DTS 问题单").unwrap_err();
+            assert!(
+                matches!(&err, RequestError::Schema { context, .. } if context == "DTS 问题单编号"),
+                "{response}: {err}"
+            );
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::NaiveDate;
+
+use crate::{Chart, ChartKind, Requirement, RequirementStatus, RequirementTool};
+
+impl RequirementTool {
+    /// Daily count of unfinished requirements whose test window overlaps
+    /// `start..=end`, next to the ideal straight line down to zero.
+    ///
+    /// A done requirement counts as finished from its end date. The day it
+    /// was marked done isn't recorded, and its last update would move the
+    /// finish day with every later edit.
+    pub fn burndown(requirements: &[&Requirement], start: NaiveDate, end: NaiveDate) -> Chart {
+        let finished_on: Vec<Option<NaiveDate>> = requirements
+            .iter()
+            .filter(|req| req.start_day() <= end && req.end_day() >= start)
+            .map(|req| (req.status == RequirementStatus::Done).then(|| req.end_day()))
+            .collect();
+
+        let days: Vec<NaiveDate> = start.iter_days().take_while(|day| *day <= end).collect();
+        let total = finished_on.len() as f64;
+        let steps = days.len().saturating_sub(1).max(1) as f64;
+        let remaining = days
+            .iter()
+            .map(|day| {
+                finished_on
+                    .iter()
+                    .filter(|finished| finished.is_none_or(|date| date > *day))
+                    .count() as f64
+            })
+            .collect();
+        let ideal = (0..days.len()).map(|index| total * (1.0 - index as f64 / steps)).collect();
+
+        let labels = days.iter().map(|day| day.format("%m-%d").to_string()).collect();
+        Chart::new("需求燃尽图", ChartKind::Line, labels)
+            .series("剩余需求", remaining)
+            .series("理想进度", ideal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone as _};
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    fn requirement(id: &str, start: u32, end: u32, status: RequirementStatus) -> Requirement {
+        let noon = |day: u32| Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap().to_utc();
+        Requirement {
+            id: id.to_string(),
+            title: id.to_string(),
+            version: "v1.0".to_string(),
+            test_cycle_days: i64::from(end - start + 1),
+            start_date: noon(start),
+            end_date: noon(end),
+            status,
+            owner: "张三".to_string(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn done_requirements_burn_down_on_their_end_date() {
+        let done = requirement("REQ-1", 1, 3, RequirementStatus::Done);
+        // Edited long after it was done, which must not move the finish day.
+        let edited = Requirement {
+            updated_at: Some(Local.with_ymd_and_hms(2024, 3, 5, 9, 0, 0).unwrap().to_utc()),
+            ..requirement("REQ-2", 1, 2, RequirementStatus::Done)
+        };
+        let open = requirement("REQ-3", 2, 6, RequirementStatus::InProgress);
+        let outside = requirement("REQ-4", 10, 12, RequirementStatus::NotStarted);
+
+        let chart = RequirementTool::burndown(&[&done, &edited, &open, &outside], day(1), day(5));
+        assert_eq!(chart.labels, ["03-01", "03-02", "03-03", "03-04", "03-05"]);
+        assert_eq!(chart.series[0].values, [3.0, 2.0, 1.0, 1.0, 1.0]);
+        assert_eq!(chart.series[1].values, [3.0, 2.25, 1.5, 0.75, 0.0]);
+    }
+}
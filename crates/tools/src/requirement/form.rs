@@ -0,0 +1,197 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use thiserror::Error;
+
+use crate::{DraftCheck, FormError, Requirement, RequirementStatus};
+
+/// The editable fields of a requirement, as entered in the requirement form.
+/// The status is not among them: it is derived from the dates and whether
+/// the requirement is completed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequirementDraft {
+    /// Left empty for a new requirement to get a local id.
+    pub id: String,
+    pub title: String,
+    pub version: String,
+    pub owner: String,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RequirementFormError {
+    #[error("请填写{0}")]
+    Required(&'static str),
+    #[error("结束日期不能早于开始日期")]
+    EndBeforeStart,
+    #[error("需求 {0} 已存在")]
+    DuplicateId(String),
+}
+
+impl FormError for RequirementFormError {
+    fn required(label: &'static str) -> Self {
+        Self::Required(label)
+    }
+
+    fn duplicate_id(id: String) -> Self {
+        Self::DuplicateId(id)
+    }
+
+    fn field(&self) -> &'static str {
+        match self {
+            Self::Required(field) => field,
+            Self::EndBeforeStart => "结束日期",
+            Self::DuplicateId(_) => "ID",
+        }
+    }
+}
+
+impl RequirementDraft {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn edit(req: &Requirement) -> Self {
+        Self {
+            id: req.id.clone(),
+            title: req.title.clone(),
+            version: req.version.clone(),
+            owner: req.owner.clone(),
+            start: Some(req.start_day()),
+            end: Some(req.end_day()),
+            completed: req.status == RequirementStatus::Done,
+        }
+    }
+
+    /// Check the draft and build the requirement to save, like
+    /// [`IssueDraft::validate`](crate::IssueDraft::validate). The status is
+    /// derived for `today`, and the test cycle is rewritten from the dates.
+    pub fn validate(
+        &self,
+        existing: Option<&Requirement>,
+        id_taken: impl Fn(&str) -> bool,
+        today: NaiveDate,
+    ) -> Result<Requirement, Vec<RequirementFormError>> {
+        let mut check = DraftCheck::new();
+        let title = check.text("标题", &self.title);
+        let version = check.text("版本", &self.version);
+        let owner = check.text("负责人", &self.owner);
+        let start = check.required("开始日期", self.start);
+        let end = check.required("结束日期", self.end);
+        if let (Some(start), Some(end)) = (start, end)
+            && end < start
+        {
+            check.push(RequirementFormError::EndBeforeStart);
+        }
+
+        let now = Utc::now();
+        let mut req = match existing {
+            Some(req) => req.clone(),
+            None => Requirement {
+                id: check.new_id(&self.id, id_taken),
+                title: String::new(),
+                version: String::new(),
+                test_cycle_days: 0,
+                start_date: now,
+                end_date: now,
+                status: RequirementStatus::NotStarted,
+                owner: String::new(),
+                updated_at: None,
+            },
+        };
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(check.into_errors());
+        };
+        check.finish()?;
+
+        req.title = title.to_string();
+        req.version = version.to_string();
+        req.owner = owner.to_string();
+        req.set_schedule(local_midnight(start), local_midnight(end));
+        req.status =
+            if self.completed { RequirementStatus::Done } else { req.scheduled_status(today) };
+        req.updated_at = Some(now);
+        Ok(req)
+    }
+}
+
+/// The start of `date` in local time.
+pub(crate) fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(Default::default());
+    midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or_else(|| midnight.and_utc(), |datetime| datetime.to_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LOCAL_ID_PREFIX;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn draft(id: &str) -> RequirementDraft {
+        RequirementDraft {
+            id: id.to_string(),
+            title: "登录改版".to_string(),
+            version: "1.2.0".to_string(),
+            owner: "张三".to_string(),
+            start: Some(day(2)),
+            end: Some(day(6)),
+            completed: false,
+        }
+    }
+
+    #[test]
+    fn end_before_start_is_refused() {
+        let draft = RequirementDraft { start: Some(day(6)), end: Some(day(2)), ..draft("REQ-1") };
+        let errors = draft.validate(None, |_| false, day(1)).unwrap_err();
+        assert_eq!(errors, [RequirementFormError::EndBeforeStart]);
+        assert_eq!(errors[0].field(), "结束日期");
+    }
+
+    #[test]
+    fn missing_fields_are_all_reported() {
+        let draft = RequirementDraft { title: " ".to_string(), end: None, ..draft("REQ-1") };
+        let errors = draft.validate(None, |_| false, day(1)).unwrap_err();
+        assert_eq!(
+            errors,
+            [RequirementFormError::Required("标题"), RequirementFormError::Required("结束日期"),]
+        );
+    }
+
+    #[test]
+    fn status_is_derived_from_the_dates_unless_completed() {
+        let req = draft("REQ-1").validate(None, |_| false, day(4)).unwrap();
+        assert_eq!(req.status, RequirementStatus::InProgress);
+        assert_eq!(req.test_cycle_days, 5);
+        assert_eq!((req.start_day(), req.end_day()), (day(2), day(6)));
+
+        for today in [day(1), day(4), day(10)] {
+            let completed = RequirementDraft { completed: true, ..draft("REQ-1") };
+            let req = completed.validate(None, |_| false, today).unwrap();
+            assert_eq!(req.status, RequirementStatus::Done);
+        }
+    }
+
+    #[test]
+    fn new_requirement_without_an_id_gets_a_local_one() {
+        let req = draft("  ").validate(None, |_| false, day(1)).unwrap();
+        assert!(req.id.starts_with(LOCAL_ID_PREFIX), "{}", req.id);
+    }
+
+    #[test]
+    fn taken_id_is_refused_for_a_new_requirement_only() {
+        let errors = draft("REQ-1").validate(None, |id| id == "REQ-1", day(1)).unwrap_err();
+        assert_eq!(errors, [RequirementFormError::DuplicateId("REQ-1".to_string())]);
+        assert_eq!(errors[0].field(), "ID");
+
+        let existing = draft("REQ-1").validate(None, |_| false, day(1)).unwrap();
+        let edited = RequirementDraft { title: "登录页改版".to_string(), ..draft("REQ-1") };
+        let req = edited.validate(Some(&existing), |id| id == "REQ-1", day(1)).unwrap();
+        assert_eq!((req.id.as_str(), req.title.as_str()), ("REQ-1", "登录页改版"));
+    }
+}
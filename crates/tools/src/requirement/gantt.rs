@@ -0,0 +1,188 @@
+use chrono::{Days, NaiveDate, TimeDelta, Utc};
+
+use crate::{Requirement, RequirementStatus, RequirementTool};
+
+/// Days of padding shown before the earliest and after the latest date.
+const PADDING_DAYS: u64 = 3;
+
+/// Which part of a Gantt bar is dragged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GanttEdge {
+    /// The whole bar, moving both dates.
+    Both,
+    Start,
+    End,
+}
+
+/// One requirement's test cycle on the timeline, in days from the start of
+/// the chart.
+#[derive(Debug, Clone)]
+pub struct GanttBar<'a> {
+    pub requirement: &'a Requirement,
+    pub offset: i64,
+    /// Number of days the bar covers, at least one.
+    pub span: i64,
+    /// Unfinished past its end date.
+    pub overdue: bool,
+}
+
+/// The layout of a Gantt chart: a run of days and a bar per requirement.
+#[derive(Debug, Clone)]
+pub struct Gantt<'a> {
+    pub start: NaiveDate,
+    pub days: i64,
+    /// Offset of today's marker.
+    pub today: i64,
+    pub bars: Vec<GanttBar<'a>>,
+}
+
+impl Gantt<'_> {
+    /// The date of the day at `offset`.
+    pub fn day(&self, offset: i64) -> NaiveDate {
+        self.start + TimeDelta::days(offset)
+    }
+}
+
+impl RequirementTool {
+    /// Lay out the test cycles of `requirements` on a timeline that spans
+    /// all of them and today, ordered by start date.
+    pub fn gantt<'a>(requirements: &[&'a Requirement], today: NaiveDate) -> Gantt<'a> {
+        let first = requirements.iter().map(|req| req.start_day()).chain([today]).min();
+        let last = requirements.iter().map(|req| req.end_day()).chain([today]).max();
+        let start = first.unwrap_or(today) - Days::new(PADDING_DAYS);
+        let end = last.unwrap_or(today) + Days::new(PADDING_DAYS);
+
+        let mut bars: Vec<GanttBar> = requirements
+            .iter()
+            .map(|req| GanttBar {
+                requirement: req,
+                offset: (req.start_day() - start).num_days(),
+                span: req.cycle_days(),
+                overdue: req.status != RequirementStatus::Done && req.days_left(today) < 0,
+            })
+            .collect();
+        bars.sort_by_key(|bar| (bar.offset, bar.requirement.id.clone()));
+
+        Gantt { start, days: (end - start).num_days() + 1, today: (today - start).num_days(), bars }
+    }
+}
+
+impl Requirement {
+    /// The requirement after dragging `edge` of its Gantt bar by `days`. An
+    /// edge can't be dragged past the other one, so the cycle keeps at
+    /// least one day; the status is derived again for `today`.
+    pub fn rescheduled(&self, edge: GanttEdge, days: i64, today: NaiveDate) -> Self {
+        let shift = TimeDelta::days(days);
+        let (start_date, end_date) = match edge {
+            GanttEdge::Both => (self.start_date + shift, self.end_date + shift),
+            GanttEdge::Start => ((self.start_date + shift).min(self.end_date), self.end_date),
+            GanttEdge::End => (self.start_date, (self.end_date + shift).max(self.start_date)),
+        };
+        let mut req = self.clone();
+        req.set_schedule(start_date, end_date);
+        req.status = req.derived_status(today);
+        req.updated_at = Some(Utc::now());
+        req
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_midnight;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, day).unwrap()
+    }
+
+    fn requirement(id: &str, start: u32, end: u32, status: RequirementStatus) -> Requirement {
+        let mut req = Requirement {
+            id: id.to_string(),
+            title: id.to_string(),
+            version: String::new(),
+            test_cycle_days: 0,
+            start_date: Default::default(),
+            end_date: Default::default(),
+            status,
+            owner: String::new(),
+            updated_at: None,
+        };
+        req.set_schedule(local_midnight(day(start)), local_midnight(day(end)));
+        req
+    }
+
+    #[test]
+    fn bars_are_placed_from_the_padded_start_and_ordered_by_it() {
+        let late = requirement("LATE", 10, 12, RequirementStatus::NotStarted);
+        let behind = requirement("BEHIND", 2, 5, RequirementStatus::InProgress);
+        let done = requirement("DONE", 1, 3, RequirementStatus::Done);
+        let gantt = RequirementTool::gantt(&[&late, &behind, &done], day(8));
+
+        assert_eq!(gantt.start, day(1) - Days::new(PADDING_DAYS));
+        assert_eq!(gantt.day(gantt.days - 1), day(12) + Days::new(PADDING_DAYS));
+        assert_eq!(gantt.day(gantt.today), day(8));
+        let bars: Vec<(&str, i64, i64, bool)> = gantt
+            .bars
+            .iter()
+            .map(|bar| (bar.requirement.id.as_str(), bar.offset, bar.span, bar.overdue))
+            .collect();
+        assert_eq!(bars, [("DONE", 3, 3, false), ("BEHIND", 4, 4, true), ("LATE", 12, 3, false)]);
+    }
+
+    #[test]
+    fn empty_chart_is_centered_on_today() {
+        let gantt = RequirementTool::gantt(&[], day(8));
+        assert_eq!((gantt.day(0), gantt.days, gantt.today), (day(5), 7, 3));
+        assert!(gantt.bars.is_empty());
+    }
+
+    #[test]
+    fn dragging_the_bar_moves_both_dates() {
+        let req = requirement("REQ", 10, 12, RequirementStatus::NotStarted);
+        let moved = req.rescheduled(GanttEdge::Both, -3, day(8));
+        assert_eq!(
+            (moved.start_day(), moved.end_day(), moved.test_cycle_days),
+            (day(7), day(9), 3)
+        );
+        assert_eq!(moved.status, RequirementStatus::InProgress);
+        assert!(moved.updated_at.is_some());
+    }
+
+    #[test]
+    fn start_can_not_pass_the_end() {
+        let req = requirement("REQ", 10, 12, RequirementStatus::NotStarted);
+        let moved = req.rescheduled(GanttEdge::Start, 5, day(8));
+        assert_eq!(
+            (moved.start_day(), moved.end_day(), moved.test_cycle_days),
+            (day(12), day(12), 1)
+        );
+
+        let widened = req.rescheduled(GanttEdge::Start, -4, day(8));
+        assert_eq!((widened.start_day(), widened.end_day()), (day(6), day(12)));
+        assert_eq!(widened.status, RequirementStatus::InProgress);
+    }
+
+    #[test]
+    fn end_can_not_pass_the_start() {
+        let req = requirement("REQ", 2, 9, RequirementStatus::InProgress);
+        let moved = req.rescheduled(GanttEdge::End, -10, day(8));
+        assert_eq!(
+            (moved.start_day(), moved.end_day(), moved.test_cycle_days),
+            (day(2), day(2), 1)
+        );
+        assert_eq!(moved.status, RequirementStatus::Delayed);
+
+        let extended = moved.rescheduled(GanttEdge::End, 8, day(8));
+        assert_eq!(extended.end_day(), day(10));
+        assert_eq!(extended.status, RequirementStatus::InProgress);
+    }
+
+    #[test]
+    fn done_stays_done_when_dragged() {
+        let req = requirement("REQ", 2, 5, RequirementStatus::Done);
+        let moved = req.rescheduled(GanttEdge::Both, 10, day(8));
+        assert_eq!(moved.status, RequirementStatus::Done);
+        let gantt = RequirementTool::gantt(&[&req], day(8));
+        assert!(!gantt.bars[0].overdue);
+    }
+}
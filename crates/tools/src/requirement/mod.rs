@@ -1,2 +1,10 @@
+mod burndown;
+mod form;
+mod gantt;
 mod model;
+mod release;
+mod schedule;
+pub use form::*;
+pub use gantt::*;
 pub use model::*;
+pub use release::*;
@@ -1,6 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Coded, Status, impl_coded};
+
 pub struct RequirementTool {}
 impl RequirementTool {
     fn new() -> Self {
         Self {}
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Requirement {
+    pub id: String,
+    pub title: String,
+    pub version: String,
+    /// Length of the test window in days, kept in step with the dates by
+    /// [`set_schedule`](Self::set_schedule).
+    #[serde(default)]
+    pub test_cycle_days: i64,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub status: RequirementStatus,
+    pub owner: String,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequirementStatus {
+    NotStarted,
+    InProgress,
+    Done,
+    Delayed,
+}
+
+impl Coded for RequirementStatus {
+    const ALL: &'static [Self] = &[Self::NotStarted, Self::InProgress, Self::Done, Self::Delayed];
+    const KIND: &'static str = "需求状态";
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotStarted => "not_started",
+            Self::InProgress => "in_progress",
+            Self::Done => "done",
+            Self::Delayed => "delayed",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::NotStarted => "未开始",
+            Self::InProgress => "进行中",
+            Self::Done => "已完成",
+            Self::Delayed => "延期",
+        }
+    }
+}
+
+impl Status for RequirementStatus {
+    fn can_transition_to(&self, next: Self) -> bool {
+        use RequirementStatus::*;
+        matches!(
+            (self, next),
+            (NotStarted, InProgress | Delayed)
+                | (InProgress, Done | Delayed)
+                | (Delayed, InProgress | Done)
+                | (Done, InProgress)
+        )
+    }
+}
+impl_coded!(RequirementStatus);
@@ -0,0 +1,343 @@
+use std::{cmp::Ordering, fmt};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Requirement, RequirementStatus, RequirementTool, local_midnight};
+
+/// A release that requirements are planned for. The id is the version as
+/// written by the user, e.g. `v1.2`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub id: String,
+    pub name: String,
+    pub release_date: Option<DateTime<Utc>>,
+    pub description: String,
+}
+
+/// A version number like `v1.2.3` or `1.0-rc1`. Missing minor and patch
+/// numbers are zero, so `v1.0` and `1.0.0` are the same version.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release tag after a `-`, which sorts before the plain version.
+    /// Numbers in tags compare by value, so `rc2` comes before `rc10`.
+    pub pre: Option<String>,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix(['v', 'V']).unwrap_or(text);
+        let (numbers, pre) = match text.split_once('-') {
+            Some((numbers, pre)) if !pre.is_empty() => (numbers, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (text, None),
+        };
+        let mut parts = numbers.split('.').map(|part| part.parse::<u64>().ok());
+        let major = parts.next()??;
+        let minor = parts.next().unwrap_or(Some(0))?;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self { major, minor, patch, pre })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre(a, b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare pre-release tags piece by piece, numbers by value. Tags that only
+/// differ in leading zeros fall back to plain text to stay consistent with
+/// `Eq`.
+fn compare_pre(a: &str, b: &str) -> Ordering {
+    let (mut left, mut right) = (pre_pieces(a), pre_pieces(b));
+    loop {
+        let order = match (left.next(), right.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            },
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+}
+
+/// The runs of digits and of everything else in a tag, e.g. `rc10.1` as
+/// `rc`, `10`, `.`, `1`.
+fn pre_pieces(tag: &str) -> impl Iterator<Item = &str> {
+    let mut rest = tag;
+    std::iter::from_fn(move || {
+        let digits = rest.chars().next()?.is_ascii_digit();
+        let end = rest.find(|c: char| c.is_ascii_digit() != digits).unwrap_or(rest.len());
+        let (piece, tail) = rest.split_at(end);
+        rest = tail;
+        Some(piece)
+    })
+}
+
+/// The requirements of one version and how far along they are.
+#[derive(Debug, Clone)]
+pub struct ReleaseProgress<'a> {
+    /// The release id, or the version as written on the requirements when
+    /// no release was created for it.
+    pub version: String,
+    pub release: Option<Release>,
+    pub requirements: Vec<&'a Requirement>,
+    pub done: usize,
+    /// Unfinished requirements past their end date.
+    pub overdue: usize,
+}
+
+impl ReleaseProgress<'_> {
+    pub fn total(&self) -> usize {
+        self.requirements.len()
+    }
+
+    /// Share of requirements done, from 0 to 1; an empty release counts as
+    /// not started.
+    pub fn ratio(&self) -> f64 {
+        if self.requirements.is_empty() { 0.0 } else { self.done as f64 / self.total() as f64 }
+    }
+}
+
+impl RequirementTool {
+    /// Order two version strings semantically. Versions that don't parse
+    /// sort after those that do, by plain text.
+    pub fn compare_versions(a: &str, b: &str) -> Ordering {
+        match (Version::parse(a), Version::parse(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.trim().cmp(b.trim()),
+        }
+    }
+
+    /// Whether two version strings name the same version, e.g. `v1.0` and
+    /// `1.0.0`.
+    pub fn same_version(a: &str, b: &str) -> bool {
+        match (Version::parse(a), Version::parse(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a.trim() == b.trim(),
+        }
+    }
+
+    /// Group requirements under their release, newest version first and
+    /// unrecognized versions last. Every release is listed even without
+    /// requirements, and versions used by requirements but never created as
+    /// a release get a group of their own.
+    pub fn group_by_release<'a>(
+        requirements: &[&'a Requirement],
+        releases: &[Release],
+        today: NaiveDate,
+    ) -> Vec<ReleaseProgress<'a>> {
+        let mut groups: Vec<ReleaseProgress> = releases
+            .iter()
+            .map(|release| ReleaseProgress {
+                version: release.id.clone(),
+                release: Some(release.clone()),
+                requirements: Vec::new(),
+                done: 0,
+                overdue: 0,
+            })
+            .collect();
+        for req in requirements {
+            let index = match groups
+                .iter()
+                .position(|group| Self::same_version(&group.version, &req.version))
+            {
+                Some(index) => index,
+                None => {
+                    groups.push(ReleaseProgress {
+                        version: req.version.trim().to_string(),
+                        release: None,
+                        requirements: Vec::new(),
+                        done: 0,
+                        overdue: 0,
+                    });
+                    groups.len() - 1
+                },
+            };
+            let group = &mut groups[index];
+            group.requirements.push(req);
+            if req.status == RequirementStatus::Done {
+                group.done += 1;
+            } else if req.days_left(today) < 0 {
+                group.overdue += 1;
+            }
+        }
+        groups.sort_by(|a, b| match (Version::parse(&a.version), Version::parse(&b.version)) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            _ => Self::compare_versions(&a.version, &b.version),
+        });
+        groups
+    }
+}
+
+/// The editable fields of a release, as entered in the release form.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseDraft {
+    pub version: String,
+    pub name: String,
+    pub release_date: Option<NaiveDate>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReleaseFormError {
+    #[error("请填写{0}")]
+    Required(&'static str),
+    #[error("版本号 {0} 无法识别，应形如 v1.2.3")]
+    InvalidVersion(String),
+    #[error("版本 {0} 已存在")]
+    DuplicateVersion(String),
+}
+
+impl ReleaseFormError {
+    /// Label of the form field the error belongs to.
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::Required(field) => field,
+            Self::InvalidVersion(_) | Self::DuplicateVersion(_) => "版本号",
+        }
+    }
+}
+
+impl ReleaseDraft {
+    pub fn edit(release: &Release) -> Self {
+        Self {
+            version: release.id.clone(),
+            name: release.name.clone(),
+            release_date: release.release_date.map(|date| date.with_timezone(&Local).date_naive()),
+            description: release.description.clone(),
+        }
+    }
+
+    /// Check the draft and build the release to save. `existing` lists the
+    /// releases already stored; a new version must not match any of them,
+    /// also when written differently.
+    pub fn validate(
+        &self,
+        editing: Option<&Release>,
+        existing: &[Release],
+    ) -> Result<Release, Vec<ReleaseFormError>> {
+        let mut errors = Vec::new();
+        let version = match editing {
+            Some(release) => release.id.clone(),
+            None => self.version.trim().to_string(),
+        };
+        if version.is_empty() {
+            errors.push(ReleaseFormError::Required("版本号"));
+        } else if Version::parse(&version).is_none() {
+            errors.push(ReleaseFormError::InvalidVersion(version.clone()));
+        } else if editing.is_none()
+            && let Some(taken) =
+                existing.iter().find(|release| RequirementTool::same_version(&release.id, &version))
+        {
+            errors.push(ReleaseFormError::DuplicateVersion(taken.id.clone()));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(Release {
+            id: version,
+            name: self.name.trim().to_string(),
+            release_date: self.release_date.map(local_midnight),
+            description: self.description.trim().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parse_fills_missing_numbers_and_keeps_pre_release() {
+        assert_eq!(version("v1.0"), version("1.0.0"));
+        assert_eq!(version(" V2 "), Version { major: 2, minor: 0, patch: 0, pre: None });
+        assert_eq!(version("1.2.3-rc1").pre.as_deref(), Some("rc1"));
+        assert_eq!(version("1.2-rc1").to_string(), "v1.2.0-rc1");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_versions() {
+        for text in ["", "v", "1.x", "1.2.3.4", "1.2-", "release"] {
+            assert_eq!(Version::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn versions_order_numerically_with_pre_releases_first() {
+        assert!(version("v1.10") > version("v1.9"));
+        assert!(version("2.0-rc1") < version("2.0"));
+        assert!(version("2.0-rc1") < version("2.0-rc2"));
+        assert!(version("2.0-rc2") < version("2.0-rc10"));
+        assert!(version("2.0-beta.2") < version("2.0-beta.11"));
+        assert!(version("2.0-beta") < version("2.0-beta.1"));
+        assert!(version("2.0-beta9") < version("2.0-rc1"));
+        assert_eq!(version("2.0-rc01").cmp(&version("2.0-rc1")), Ordering::Less);
+        assert!(version("1.9.9") < version("2.0-beta"));
+    }
+
+    #[test]
+    fn unparsed_versions_sort_last() {
+        assert_eq!(RequirementTool::compare_versions("v1.0", "next"), Ordering::Less);
+        assert_eq!(RequirementTool::compare_versions("beta", "alpha"), Ordering::Greater);
+        assert!(RequirementTool::same_version("v1.0", "1.0.0"));
+        assert!(!RequirementTool::same_version("next", "Next"));
+    }
+
+    #[test]
+    fn release_date_is_local_midnight() {
+        let date = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+        let draft = ReleaseDraft {
+            version: "v1.0".to_string(),
+            release_date: Some(date),
+            ..Default::default()
+        };
+        let release = draft.validate(None, &[]).unwrap();
+        assert_eq!(release.release_date, Some(local_midnight(date)));
+        assert_eq!(ReleaseDraft::edit(&release).release_date, Some(date));
+    }
+}
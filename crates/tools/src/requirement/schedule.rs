@@ -0,0 +1,138 @@
+use anyhow::Result;
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+
+use crate::{Requirement, RequirementStatus, RequirementTool, Store, SyncSource};
+
+impl Requirement {
+    /// First day of the test window, in local time.
+    pub fn start_day(&self) -> NaiveDate {
+        self.start_date.with_timezone(&Local).date_naive()
+    }
+
+    /// Last day of the test window, in local time.
+    pub fn end_day(&self) -> NaiveDate {
+        self.end_date.with_timezone(&Local).date_naive()
+    }
+
+    /// Length of the test window in days, counting both ends.
+    pub fn cycle_days(&self) -> i64 {
+        (self.end_day() - self.start_day()).num_days().max(0) + 1
+    }
+
+    /// Move the test window and update the test cycle to match it.
+    pub fn set_schedule(&mut self, start_date: DateTime<Utc>, end_date: DateTime<Utc>) {
+        self.start_date = start_date;
+        self.end_date = end_date;
+        self.test_cycle_days = self.cycle_days();
+    }
+
+    /// Days from `today` to the end date, negative once it has passed.
+    pub fn days_left(&self, today: NaiveDate) -> i64 {
+        (self.end_day() - today).num_days()
+    }
+
+    /// The status implied by the dates on `today`: done stays done, anything
+    /// else follows [`scheduled_status`](Self::scheduled_status).
+    pub fn derived_status(&self, today: NaiveDate) -> RequirementStatus {
+        if self.status == RequirementStatus::Done {
+            RequirementStatus::Done
+        } else {
+            self.scheduled_status(today)
+        }
+    }
+
+    /// The status of an unfinished requirement on `today`: delayed after its
+    /// end date, in progress inside its window and not started before it.
+    pub fn scheduled_status(&self, today: NaiveDate) -> RequirementStatus {
+        if today > self.end_day() {
+            RequirementStatus::Delayed
+        } else if today >= self.start_day() {
+            RequirementStatus::InProgress
+        } else {
+            RequirementStatus::NotStarted
+        }
+    }
+}
+
+impl RequirementTool {
+    /// Unfinished requirements whose end date falls within the next `days`
+    /// days of `today`, today included, soonest first.
+    pub fn due_within<'a>(
+        requirements: &[&'a Requirement],
+        today: NaiveDate,
+        days: u64,
+    ) -> Vec<&'a Requirement> {
+        let last = today.checked_add_days(Days::new(days)).unwrap_or(NaiveDate::MAX);
+        let mut due: Vec<&Requirement> = requirements
+            .iter()
+            .copied()
+            .filter(|req| req.status != RequirementStatus::Done)
+            .filter(|req| (today..=last).contains(&req.end_day()))
+            .collect();
+        due.sort_by_key(|req| (req.end_date, req.id.clone()));
+        due
+    }
+}
+
+impl Store {
+    /// Every requirement with the status its dates imply on `today`. The
+    /// derived statuses are not saved, and requirements that came from a sync
+    /// keep the status their source system gave them.
+    pub fn requirements_on(&self, today: NaiveDate) -> Result<Vec<Requirement>> {
+        let synced = self.synced_ids(SyncSource::Requirement)?;
+        let mut requirements = self.requirements().all()?;
+        for req in requirements.iter_mut().filter(|req| !synced.contains(&req.id)) {
+            req.status = req.derived_status(today);
+        }
+        Ok(requirements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone as _;
+
+    use super::*;
+    use crate::{RequestTool, SyncTool};
+
+    fn requirement(id: &str, status: RequirementStatus) -> Requirement {
+        let mut req = Requirement {
+            id: id.to_string(),
+            title: id.to_string(),
+            version: String::new(),
+            test_cycle_days: 0,
+            start_date: Default::default(),
+            end_date: Default::default(),
+            status,
+            owner: String::new(),
+            updated_at: None,
+        };
+        let day = |day| Local.with_ymd_and_hms(2026, 3, day, 0, 0, 0).unwrap().to_utc();
+        req.set_schedule(day(2), day(6));
+        req
+    }
+
+    #[test]
+    fn set_schedule_counts_both_ends() {
+        assert_eq!(requirement("REQ-1", RequirementStatus::NotStarted).test_cycle_days, 5);
+    }
+
+    #[test]
+    fn requirements_on_derives_statuses_without_saving() {
+        let store = Store::open_in_memory().unwrap();
+        store.requirements().upsert(&requirement("LOCAL", RequirementStatus::NotStarted)).unwrap();
+        SyncTool::new(store.clone(), RequestTool::new().unwrap())
+            .merge(vec![requirement("SYNCED", RequirementStatus::NotStarted)])
+            .unwrap();
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let status = |requirements: Vec<Requirement>, id: &str| {
+            requirements.into_iter().find(|req| req.id == id).unwrap().status
+        };
+        let shown = store.requirements_on(today).unwrap();
+        assert_eq!(status(shown.clone(), "LOCAL"), RequirementStatus::Delayed);
+        assert_eq!(status(shown, "SYNCED"), RequirementStatus::NotStarted);
+        let saved = store.requirements().all().unwrap();
+        assert_eq!(status(saved, "LOCAL"), RequirementStatus::NotStarted);
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Serialize, de::DeserializeOwned};
+
+use super::{Store, Table};
+
+/// A filter the user saved under a name, for one record type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedFilter<F> {
+    pub name: String,
+    pub filter: F,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Saved filters are kept per user and per record type `T`, with the filter
+/// itself stored as JSON so any serializable filter type fits.
+impl Store {
+    pub fn saved_filters<T: Table, F: DeserializeOwned>(
+        &self,
+        owner: &str,
+    ) -> Result<Vec<SavedFilter<F>>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT name, filter, saved_at FROM saved_filters
+             WHERE owner = ?1 AND kind = ?2 ORDER BY name",
+        )?;
+        let rows = stmt
+            .query_map(params![owner, T::NAME], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // A filter written by an older version may no longer parse; skip it
+        // rather than failing the whole list.
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, filter, saved_at)| match serde_json::from_str(&filter) {
+                Ok(filter) => Some(SavedFilter { name, filter, saved_at }),
+                Err(err) => {
+                    tracing::warn!("Skipping unreadable saved filter {}: {}", name, err);
+                    None
+                },
+            })
+            .collect())
+    }
+
+    /// Save `filter` under `name`, replacing a filter of the same name.
+    pub fn save_filter<T: Table, F: Serialize>(
+        &self,
+        owner: &str,
+        name: &str,
+        filter: &F,
+    ) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO saved_filters (owner, kind, name, filter, saved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (owner, kind, name)
+             DO UPDATE SET filter = excluded.filter, saved_at = excluded.saved_at",
+            params![owner, T::NAME, name, serde_json::to_string(filter)?, Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_filter<T: Table>(&self, owner: &str, name: &str) -> Result<bool> {
+        let deleted = self.conn().execute(
+            "DELETE FROM saved_filters WHERE owner = ?1 AND kind = ?2 AND name = ?3",
+            params![owner, T::NAME, name],
+        )?;
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Issue, IssueFilter, IssueStatus, MergeRequest};
+
+    fn open_issues() -> IssueFilter {
+        IssueFilter { statuses: vec![IssueStatus::Submitted], ..Default::default() }
+    }
+
+    #[test]
+    fn saved_filters_are_listed_by_name_and_replaced_by_name() {
+        let store = Store::open_in_memory().unwrap();
+        store.save_filter::<Issue, _>("张三", "我的问题单", &IssueFilter::default()).unwrap();
+        store.save_filter::<Issue, _>("张三", "待处理", &IssueFilter::default()).unwrap();
+        store.save_filter::<Issue, _>("张三", "我的问题单", &open_issues()).unwrap();
+
+        let saved = store.saved_filters::<Issue, IssueFilter>("张三").unwrap();
+        let names: Vec<&str> = saved.iter().map(|saved| saved.name.as_str()).collect();
+        let mut expected = vec!["我的问题单", "待处理"];
+        expected.sort();
+        assert_eq!(names, expected);
+        let mine = saved.iter().find(|saved| saved.name == "我的问题单").unwrap();
+        assert_eq!(mine.filter, open_issues());
+    }
+
+    #[test]
+    fn saved_filters_are_kept_per_owner_and_record_type() {
+        let store = Store::open_in_memory().unwrap();
+        store.save_filter::<Issue, _>("张三", "待处理", &open_issues()).unwrap();
+
+        assert!(store.saved_filters::<Issue, IssueFilter>("李四").unwrap().is_empty());
+        assert!(store.saved_filters::<MergeRequest, IssueFilter>("张三").unwrap().is_empty());
+        assert!(!store.delete_filter::<MergeRequest>("张三", "待处理").unwrap());
+        assert!(!store.delete_filter::<Issue>("李四", "待处理").unwrap());
+
+        assert!(store.delete_filter::<Issue>("张三", "待处理").unwrap());
+        assert!(store.saved_filters::<Issue, IssueFilter>("张三").unwrap().is_empty());
+    }
+
+    #[test]
+    fn unreadable_filters_are_skipped() {
+        let store = Store::open_in_memory().unwrap();
+        store.save_filter::<Issue, _>("张三", "旧版", &"not a filter").unwrap();
+        store.save_filter::<Issue, _>("张三", "待处理", &open_issues()).unwrap();
+
+        let saved = store.saved_filters::<Issue, IssueFilter>("张三").unwrap();
+        let names: Vec<&str> = saved.iter().map(|saved| saved.name.as_str()).collect();
+        assert_eq!(names, ["待处理"]);
+    }
+}
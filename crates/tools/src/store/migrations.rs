@@ -0,0 +1,288 @@
+use rusqlite::Connection;
+
+/// Schema migrations, applied in order. The index of the last applied
+/// migration is tracked in SQLite's `user_version` pragma, so entries must
+/// only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    r#"
+    CREATE TABLE merge_requests (
+        id TEXT PRIMARY KEY NOT NULL,
+        title TEXT NOT NULL,
+        author TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        add_lines INTEGER NOT NULL DEFAULT 0,
+        del_lines INTEGER NOT NULL DEFAULT 0,
+        status TEXT NOT NULL
+    );
+
+    CREATE TABLE issues (
+        id TEXT PRIMARY KEY NOT NULL,
+        title TEXT NOT NULL,
+        severity TEXT NOT NULL,
+        status TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        resolved_at TEXT,
+        assignee TEXT NOT NULL
+    );
+
+    CREATE TABLE requirements (
+        id TEXT PRIMARY KEY NOT NULL,
+        title TEXT NOT NULL,
+        version TEXT NOT NULL,
+        test_cycle TEXT NOT NULL,
+        start_date TEXT NOT NULL,
+        end_date TEXT NOT NULL,
+        status TEXT NOT NULL,
+        owner TEXT NOT NULL
+    );
+
+    CREATE TABLE test_cases (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        error_msg TEXT
+    );
+
+    CREATE TABLE virtual_envs (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        owner TEXT
+    );
+
+    CREATE TABLE jobs (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        progress REAL NOT NULL DEFAULT 0
+    );
+
+    CREATE TABLE excel_files (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        file_type TEXT NOT NULL,
+        size TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        description TEXT NOT NULL
+    );
+    "#,
+    // 2: sync bookkeeping
+    r#"
+    ALTER TABLE merge_requests ADD COLUMN updated_at TEXT;
+    ALTER TABLE issues ADD COLUMN updated_at TEXT;
+    ALTER TABLE requirements ADD COLUMN updated_at TEXT;
+
+    CREATE TABLE sync_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        source TEXT NOT NULL,
+        synced_at TEXT NOT NULL,
+        fetched INTEGER NOT NULL,
+        created INTEGER NOT NULL,
+        updated INTEGER NOT NULL
+    );
+
+    CREATE TABLE sync_changes (
+        run_id INTEGER NOT NULL REFERENCES sync_runs(id) ON DELETE CASCADE,
+        record_id TEXT NOT NULL,
+        title TEXT NOT NULL,
+        change TEXT NOT NULL
+    );
+    CREATE INDEX sync_runs_source ON sync_runs(source, synced_at);
+    "#,
+    // 3: MR repository and merge time for workload stats
+    r#"
+    ALTER TABLE merge_requests ADD COLUMN repository TEXT NOT NULL DEFAULT '';
+    ALTER TABLE merge_requests ADD COLUMN merged_at TEXT;
+    "#,
+    // 4: issue reopen counter for DTS stats
+    r#"
+    ALTER TABLE issues ADD COLUMN reopen_count INTEGER NOT NULL DEFAULT 0;
+    "#,
+    // 5: named filters saved per user
+    r#"
+    CREATE TABLE saved_filters (
+        owner TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        name TEXT NOT NULL,
+        filter TEXT NOT NULL,
+        saved_at TEXT NOT NULL,
+        PRIMARY KEY (owner, kind, name)
+    );
+    "#,
+    // 6: issue status history, local comments and links
+    r#"
+    CREATE TABLE issue_history (
+        issue_id TEXT NOT NULL REFERENCES issues(id) ON DELETE CASCADE,
+        from_status TEXT,
+        to_status TEXT NOT NULL,
+        actor TEXT NOT NULL,
+        at TEXT NOT NULL
+    );
+    CREATE INDEX issue_history_issue ON issue_history(issue_id, at);
+
+    CREATE TABLE issue_comments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        issue_id TEXT NOT NULL REFERENCES issues(id) ON DELETE CASCADE,
+        author TEXT NOT NULL,
+        body TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    CREATE INDEX issue_comments_issue ON issue_comments(issue_id, created_at);
+
+    CREATE TABLE issue_links (
+        issue_id TEXT NOT NULL REFERENCES issues(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        target_id TEXT NOT NULL,
+        PRIMARY KEY (issue_id, kind, target_id)
+    );
+    "#,
+    // 7: releases that requirements are grouped under
+    r#"
+    CREATE TABLE releases (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        release_date TEXT,
+        description TEXT NOT NULL
+    );
+    "#,
+    // 8: per-user UI settings
+    r#"
+    CREATE TABLE settings (
+        owner TEXT NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (owner, key)
+    );
+    "#,
+    // 9: environment leases and reservation history
+    r#"
+    ALTER TABLE virtual_envs ADD COLUMN lease_until TEXT;
+    ALTER TABLE virtual_envs ADD COLUMN reason TEXT;
+
+    CREATE TABLE env_reservations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        env_id TEXT NOT NULL REFERENCES virtual_envs(id) ON DELETE CASCADE,
+        owner TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        reserved_at TEXT NOT NULL,
+        lease_until TEXT NOT NULL,
+        released_at TEXT,
+        lease_end TEXT
+    );
+    CREATE INDEX env_reservations_env ON env_reservations(env_id, reserved_at);
+    "#,
+    // 10: job parameters, checkpoints and per-user job templates
+    r#"
+    ALTER TABLE jobs ADD COLUMN params TEXT;
+    ALTER TABLE jobs ADD COLUMN checkpoint TEXT;
+    ALTER TABLE jobs ADD COLUMN resumed_from TEXT;
+    ALTER TABLE jobs ADD COLUMN updated_at TEXT;
+
+    CREATE TABLE job_templates (
+        owner TEXT NOT NULL,
+        name TEXT NOT NULL,
+        params TEXT NOT NULL,
+        saved_at TEXT NOT NULL,
+        PRIMARY KEY (owner, name)
+    );
+    "#,
+    // 11: failure analysis runs, for new and flaky failure clusters
+    r#"
+    CREATE TABLE failure_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        analyzed_at TEXT NOT NULL
+    );
+
+    CREATE TABLE failure_run_cases (
+        run_id INTEGER NOT NULL REFERENCES failure_runs(id) ON DELETE CASCADE,
+        case_id TEXT NOT NULL,
+        signature TEXT NOT NULL,
+        PRIMARY KEY (run_id, case_id)
+    );
+    "#,
+    // 12: requirement test cycles as a day count instead of display text
+    r#"
+    ALTER TABLE requirements ADD COLUMN test_cycle_days INTEGER NOT NULL DEFAULT 0;
+    UPDATE requirements SET test_cycle_days = MAX(
+        CAST(julianday(date(end_date, 'localtime')) - julianday(date(start_date, 'localtime')) AS INTEGER),
+        0
+    ) + 1;
+    ALTER TABLE requirements DROP COLUMN test_cycle;
+    "#,
+    // 13: passed cases in failure analysis runs
+    r#"
+    ALTER TABLE failure_run_cases ADD COLUMN passed INTEGER NOT NULL DEFAULT 0;
+    "#,
+];
+
+pub(super) fn run(conn: &mut Connection) -> rusqlite::Result<()> {
+    let applied: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if applied >= MIGRATIONS.len() {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(applied) {
+        tracing::info!("Applying store migration {}", index + 1);
+        tx.execute_batch(sql)?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn migrates_an_empty_database_to_the_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(user_version(&conn), 0);
+
+        run(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        let columns: Vec<String> = conn
+            .prepare("SELECT name FROM pragma_table_info('requirements')")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(columns.contains(&"test_cycle_days".to_string()));
+        assert!(!columns.contains(&"test_cycle".to_string()));
+
+        // Already up to date, nothing is applied twice.
+        run(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn keeps_rows_written_by_an_older_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        for sql in &MIGRATIONS[..11] {
+            conn.execute_batch(sql).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 11).unwrap();
+        conn.execute(
+            "INSERT INTO requirements (id, title, version, test_cycle, start_date, end_date, status, owner)
+             VALUES ('REQ-1', '登录', 'v1.0', '5 天', '2024-03-01T00:00:00Z', '2024-03-05T00:00:00Z', '进行中', '张三')",
+            [],
+        )
+        .unwrap();
+
+        run(&mut conn).unwrap();
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        let (title, days): (String, i64) = conn
+            .query_row("SELECT title, test_cycle_days FROM requirements", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(title, "登录");
+        assert_eq!(days, 5);
+    }
+}
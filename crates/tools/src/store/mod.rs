@@ -0,0 +1,7 @@
+mod filters;
+mod migrations;
+mod model;
+mod settings;
+mod table;
+pub use filters::*;
+pub use model::*;
@@ -0,0 +1,415 @@
+use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use anyhow::{Context as _, Result};
+use rusqlite::{Connection, OptionalExtension, Row, ToSql};
+
+use super::migrations;
+use crate::{ExcelData, Issue, Job, MergeRequest, Release, Requirement, TestCase, VirtualEnv};
+
+/// A record type that is persisted in its own SQLite table.
+///
+/// `COLUMNS` lists the table columns in the same order as [`Table::values`]
+/// returns them, and the first column is always the `id` primary key.
+pub trait Table: Sized {
+    const NAME: &'static str;
+    const COLUMNS: &'static [&'static str];
+
+    fn values(&self) -> Vec<&dyn ToSql>;
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self>;
+}
+
+/// SQLite backed storage for all record types.
+///
+/// Cloning is cheap, every clone shares the same connection.
+#[derive(Clone)]
+pub struct Store {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open store at {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrations::run(&mut conn).context("failed to migrate store")?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    pub fn repository<T: Table>(&self) -> Repository<T> {
+        Repository { store: self.clone(), _marker: PhantomData }
+    }
+
+    pub fn merge_requests(&self) -> Repository<MergeRequest> {
+        self.repository()
+    }
+
+    pub fn issues(&self) -> Repository<Issue> {
+        self.repository()
+    }
+
+    pub fn requirements(&self) -> Repository<Requirement> {
+        self.repository()
+    }
+
+    pub fn releases(&self) -> Repository<Release> {
+        self.repository()
+    }
+
+    pub fn test_cases(&self) -> Repository<TestCase> {
+        self.repository()
+    }
+
+    pub fn virtual_envs(&self) -> Repository<VirtualEnv> {
+        self.repository()
+    }
+
+    pub fn jobs(&self) -> Repository<Job> {
+        self.repository()
+    }
+
+    pub fn excel_files(&self) -> Repository<ExcelData> {
+        self.repository()
+    }
+
+    /// Everyone the store knows of as an issue assignee, MR author or
+    /// requirement owner, sorted and without duplicates.
+    pub fn people(&self) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT assignee FROM issues
+             UNION SELECT author FROM merge_requests
+             UNION SELECT owner FROM requirements
+             ORDER BY 1",
+        )?;
+        let people = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter(|person| !matches!(person, Ok(person) if person.trim().is_empty()))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(people)
+    }
+
+    /// Run `f` inside a single transaction, rolling back if it returns an error.
+    pub fn transaction<R>(&self, f: impl FnOnce(&Transaction) -> Result<R>) -> Result<R> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let result = f(&Transaction { conn: &tx })?;
+        tx.commit()?;
+        Ok(result)
+    }
+
+    pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock can't leave SQLite in a torn state,
+        // so a poisoned mutex is still safe to use.
+        self.conn.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Typed access to the table of a single record type.
+pub struct Repository<T> {
+    store: Store,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Table> Repository<T> {
+    pub fn all(&self) -> Result<Vec<T>> {
+        let conn = self.store.conn();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM {} ORDER BY rowid",
+            T::COLUMNS.join(", "),
+            T::NAME
+        ))?;
+        let rows = stmt.query_map([], T::from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<T>> {
+        let conn = self.store.conn();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM {} WHERE id = ?1",
+            T::COLUMNS.join(", "),
+            T::NAME
+        ))?;
+        Ok(stmt.query_row([id], T::from_row).optional()?)
+    }
+
+    pub fn count(&self) -> Result<usize> {
+        let conn = self.store.conn();
+        let count =
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", T::NAME), [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    /// Insert the record, or replace the stored one with the same id.
+    pub fn upsert(&self, record: &T) -> Result<()> {
+        upsert(&self.store.conn(), record)?;
+        Ok(())
+    }
+
+    pub fn upsert_all(&self, records: &[T]) -> Result<()> {
+        self.store.transaction(|tx| {
+            for record in records {
+                tx.upsert(record)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns `false` if no record with `id` existed.
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        Ok(delete::<T>(&self.store.conn(), id)?)
+    }
+
+    /// Delete every record in `ids` at once, returning how many existed.
+    pub fn delete_all(&self, ids: &[String]) -> Result<usize> {
+        self.store.transaction(|tx| {
+            let mut deleted = 0;
+            for id in ids {
+                deleted += usize::from(tx.delete::<T>(id)?);
+            }
+            Ok(deleted)
+        })
+    }
+}
+
+/// A handle to an open transaction, see [`Store::transaction`].
+pub struct Transaction<'a> {
+    conn: &'a Connection,
+}
+
+impl Transaction<'_> {
+    pub fn upsert<T: Table>(&self, record: &T) -> Result<()> {
+        upsert(self.conn, record)?;
+        Ok(())
+    }
+
+    pub fn delete<T: Table>(&self, id: &str) -> Result<bool> {
+        Ok(delete::<T>(self.conn, id)?)
+    }
+
+    pub(crate) fn conn(&self) -> &Connection {
+        self.conn
+    }
+}
+
+fn upsert<T: Table>(conn: &Connection, record: &T) -> rusqlite::Result<()> {
+    let placeholders =
+        (1..=T::COLUMNS.len()).map(|i| format!("?{i}")).collect::<Vec<_>>().join(", ");
+    let updates = T::COLUMNS[1..]
+        .iter()
+        .map(|column| format!("{column} = excluded.{column}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut stmt = conn.prepare_cached(&format!(
+        "INSERT INTO {} ({}) VALUES ({placeholders}) ON CONFLICT(id) DO UPDATE SET {updates}",
+        T::NAME,
+        T::COLUMNS.join(", "),
+    ))?;
+    stmt.execute(&*record.values())?;
+    Ok(())
+}
+
+fn delete<T: Table>(conn: &Connection, id: &str) -> rusqlite::Result<bool> {
+    let deleted = conn.execute(&format!("DELETE FROM {} WHERE id = ?1", T::NAME), [id])?;
+    Ok(deleted > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone as _, Utc};
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{
+        EnvStatus, IssueStatus, JobCheckpoint, JobParams, JobStatus, MrStatus, RequirementStatus,
+        Severity,
+    };
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    /// Stores `record`, loads it back by id and compares both as JSON, since
+    /// the record types don't implement `PartialEq`.
+    fn round_trip<T: Table + Serialize>(store: &Store, id: &str, record: T) {
+        let repository = store.repository::<T>();
+        repository.upsert(&record).unwrap();
+        let loaded = repository.get(id).unwrap().expect("record was stored");
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&record).unwrap());
+        assert_eq!(repository.count().unwrap(), 1);
+    }
+
+    fn issue(id: &str, assignee: &str) -> Issue {
+        Issue {
+            id: id.to_string(),
+            title: "登录页面显示异常".to_string(),
+            severity: Severity::Serious,
+            status: IssueStatus::Closed,
+            created_at: at(1, 9),
+            resolved_at: Some(at(3, 18)),
+            assignee: assignee.to_string(),
+            updated_at: Some(at(3, 18)),
+            reopen_count: 2,
+        }
+    }
+
+    #[test]
+    fn every_table_round_trips() {
+        let store = Store::open_in_memory().unwrap();
+        round_trip(&store, "MR-1", MergeRequest {
+            id: "MR-1".to_string(),
+            title: "修复登录".to_string(),
+            author: "张三".to_string(),
+            created_at: at(1, 9),
+            add_lines: 120,
+            del_lines: 30,
+            status: MrStatus::Merged,
+            updated_at: Some(at(2, 10)),
+            repository: "web".to_string(),
+            merged_at: Some(at(2, 10)),
+        });
+        round_trip(&store, "DTS-1", issue("DTS-1", "李四"));
+        round_trip(&store, "REQ-1", Requirement {
+            id: "REQ-1".to_string(),
+            title: "单点登录".to_string(),
+            version: "v1.2.0".to_string(),
+            test_cycle_days: 5,
+            start_date: at(1, 0),
+            end_date: at(5, 0),
+            status: RequirementStatus::InProgress,
+            owner: "王五".to_string(),
+            updated_at: None,
+        });
+        round_trip(&store, "REL-1", Release {
+            id: "REL-1".to_string(),
+            name: "v1.2.0".to_string(),
+            release_date: Some(at(20, 0)),
+            description: "三月版本".to_string(),
+        });
+        round_trip(&store, "TC-1", TestCase {
+            id: "TC-1".to_string(),
+            name: "登录成功".to_string(),
+            status: "失败".to_string(),
+            error_msg: Some("超时".to_string()),
+        });
+        round_trip(&store, "ENV-01", VirtualEnv {
+            id: "ENV-01".to_string(),
+            name: "测试环境 01".to_string(),
+            status: EnvStatus::Occupied,
+            owner: Some("张三".to_string()),
+            lease_until: Some(at(1, 12)),
+            reason: Some("回归".to_string()),
+        });
+        round_trip(&store, "JOB-1", Job {
+            id: "JOB-1".to_string(),
+            name: "冒烟测试".to_string(),
+            status: JobStatus::Running,
+            progress: 0.5,
+            params: Some(JobParams {
+                branch: "main".to_string(),
+                env_id: "ENV-01".to_string(),
+                suite: "smoke".to_string(),
+                tags: vec!["登录".to_string()],
+            }),
+            checkpoint: Some(JobCheckpoint {
+                id: "CP-1".to_string(),
+                total: 10,
+                finished: 5,
+                failed_cases: vec!["TC-1".to_string()],
+            }),
+            resumed_from: Some("JOB-0".to_string()),
+            updated_at: Some(at(1, 10)),
+        });
+        round_trip(&store, "/tmp/用例.xlsx", ExcelData {
+            id: "/tmp/用例.xlsx".to_string(),
+            name: "用例.xlsx".to_string(),
+            file_type: "Excel".to_string(),
+            size: "12.0 KB".to_string(),
+            created_at: "2024-03-01 09:00".to_string(),
+            description: "用例".to_string(),
+        });
+    }
+
+    #[test]
+    fn upsert_replaces_the_record_with_the_same_id() {
+        let store = Store::open_in_memory().unwrap();
+        store.issues().upsert(&issue("DTS-1", "张三")).unwrap();
+        store.issues().upsert(&issue("DTS-1", "李四")).unwrap();
+
+        assert_eq!(store.issues().count().unwrap(), 1);
+        assert_eq!(store.issues().get("DTS-1").unwrap().unwrap().assignee, "李四");
+    }
+
+    #[test]
+    fn delete_all_counts_the_records_that_existed() {
+        let store = Store::open_in_memory().unwrap();
+        store.issues().upsert_all(&[issue("DTS-1", "张三"), issue("DTS-2", "李四")]).unwrap();
+
+        let ids = ["DTS-1".to_string(), "DTS-3".to_string()];
+        assert_eq!(store.issues().delete_all(&ids).unwrap(), 1);
+        let left: Vec<String> = store.issues().all().unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(left, ["DTS-2"]);
+        assert!(!store.issues().delete("DTS-1").unwrap());
+    }
+
+    #[test]
+    fn transaction_rolls_back_on_error() {
+        let store = Store::open_in_memory().unwrap();
+        store.issues().upsert(&issue("DTS-1", "张三")).unwrap();
+
+        let result: Result<()> = store.transaction(|tx| {
+            tx.upsert(&issue("DTS-2", "李四"))?;
+            tx.delete::<Issue>("DTS-1")?;
+            anyhow::bail!("导入失败")
+        });
+        assert_eq!(result.unwrap_err().to_string(), "导入失败");
+        let ids: Vec<String> = store.issues().all().unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(ids, ["DTS-1"]);
+    }
+
+    #[test]
+    fn people_are_sorted_without_duplicates_or_blanks() {
+        let store = Store::open_in_memory().unwrap();
+        store
+            .issues()
+            .upsert_all(&[issue("DTS-1", "张三"), issue("DTS-2", " "), issue("DTS-3", "李四")])
+            .unwrap();
+        store
+            .merge_requests()
+            .upsert(&MergeRequest {
+                id: "MR-1".to_string(),
+                title: "修复登录".to_string(),
+                author: "张三".to_string(),
+                created_at: at(1, 9),
+                add_lines: 0,
+                del_lines: 0,
+                status: MrStatus::Open,
+                updated_at: None,
+                repository: String::new(),
+                merged_at: None,
+            })
+            .unwrap();
+
+        let mut expected = vec!["张三".to_string(), "李四".to_string()];
+        expected.sort();
+        assert_eq!(store.people().unwrap(), expected);
+    }
+}
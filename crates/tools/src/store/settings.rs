@@ -0,0 +1,29 @@
+use anyhow::Result;
+use rusqlite::{OptionalExtension, params};
+
+use super::Store;
+
+/// Small per-user UI settings such as the last selected tab, kept as plain
+/// strings under a dotted key like `hive.tab`.
+impl Store {
+    pub fn setting(&self, owner: &str, key: &str) -> Result<Option<String>> {
+        let value = self
+            .conn()
+            .query_row(
+                "SELECT value FROM settings WHERE owner = ?1 AND key = ?2",
+                params![owner, key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value)
+    }
+
+    pub fn set_setting(&self, owner: &str, key: &str, value: &str) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO settings (owner, key, value) VALUES (?1, ?2, ?3)
+             ON CONFLICT (owner, key) DO UPDATE SET value = excluded.value",
+            params![owner, key, value],
+        )?;
+        Ok(())
+    }
+}
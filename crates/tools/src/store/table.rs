@@ -0,0 +1,289 @@
+use rusqlite::{
+    Row, ToSql,
+    types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef},
+};
+
+use super::Table;
+use crate::{
+    Coded as _, EnvStatus, ExcelData, Issue, IssueStatus, Job, JobCheckpoint, JobParams, JobStatus,
+    MergeRequest, MrStatus, Release, Requirement, RequirementStatus, Severity, TestCase,
+    VirtualEnv,
+};
+
+/// Stores a [`Coded`](crate::Coded) value as its code. Reading also accepts the localized label,
+/// which is what rows written before the enums existed contain.
+macro_rules! impl_sql_for_coded {
+    ($($ty:ty),*) => {$(
+        impl ToSql for $ty {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                Ok(ToSqlOutput::from(self.code()))
+            }
+        }
+
+        impl FromSql for $ty {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                value.as_str()?.parse::<$ty>().map_err(|err| FromSqlError::Other(Box::new(err)))
+            }
+        }
+    )*};
+}
+
+impl_sql_for_coded!(IssueStatus, Severity, RequirementStatus, EnvStatus, JobStatus, MrStatus);
+
+/// Stores a value that has no columns of its own as JSON text.
+macro_rules! impl_sql_as_json {
+    ($($ty:ty),*) => {$(
+        impl ToSql for $ty {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                serde_json::to_string(self)
+                    .map(ToSqlOutput::from)
+                    .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))
+            }
+        }
+
+        impl FromSql for $ty {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                serde_json::from_str(value.as_str()?).map_err(|err| FromSqlError::Other(Box::new(err)))
+            }
+        }
+    )*};
+}
+
+impl_sql_as_json!(JobParams, JobCheckpoint);
+
+impl Table for MergeRequest {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "author",
+        "created_at",
+        "add_lines",
+        "del_lines",
+        "status",
+        "updated_at",
+        "repository",
+        "merged_at",
+    ];
+    const NAME: &'static str = "merge_requests";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![
+            &self.id,
+            &self.title,
+            &self.author,
+            &self.created_at,
+            &self.add_lines,
+            &self.del_lines,
+            &self.status,
+            &self.updated_at,
+            &self.repository,
+            &self.merged_at,
+        ]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            author: row.get(2)?,
+            created_at: row.get(3)?,
+            add_lines: row.get(4)?,
+            del_lines: row.get(5)?,
+            status: row.get(6)?,
+            updated_at: row.get(7)?,
+            repository: row.get(8)?,
+            merged_at: row.get(9)?,
+        })
+    }
+}
+
+impl Table for Issue {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "severity",
+        "status",
+        "created_at",
+        "resolved_at",
+        "assignee",
+        "updated_at",
+        "reopen_count",
+    ];
+    const NAME: &'static str = "issues";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![
+            &self.id,
+            &self.title,
+            &self.severity,
+            &self.status,
+            &self.created_at,
+            &self.resolved_at,
+            &self.assignee,
+            &self.updated_at,
+            &self.reopen_count,
+        ]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            severity: row.get(2)?,
+            status: row.get(3)?,
+            created_at: row.get(4)?,
+            resolved_at: row.get(5)?,
+            assignee: row.get(6)?,
+            updated_at: row.get(7)?,
+            reopen_count: row.get(8)?,
+        })
+    }
+}
+
+impl Table for Requirement {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "title",
+        "version",
+        "test_cycle_days",
+        "start_date",
+        "end_date",
+        "status",
+        "owner",
+        "updated_at",
+    ];
+    const NAME: &'static str = "requirements";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![
+            &self.id,
+            &self.title,
+            &self.version,
+            &self.test_cycle_days,
+            &self.start_date,
+            &self.end_date,
+            &self.status,
+            &self.owner,
+            &self.updated_at,
+        ]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            version: row.get(2)?,
+            test_cycle_days: row.get(3)?,
+            start_date: row.get(4)?,
+            end_date: row.get(5)?,
+            status: row.get(6)?,
+            owner: row.get(7)?,
+            updated_at: row.get(8)?,
+        })
+    }
+}
+
+impl Table for Release {
+    const COLUMNS: &'static [&'static str] = &["id", "name", "release_date", "description"];
+    const NAME: &'static str = "releases";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![&self.id, &self.name, &self.release_date, &self.description]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            release_date: row.get(2)?,
+            description: row.get(3)?,
+        })
+    }
+}
+
+impl Table for TestCase {
+    const COLUMNS: &'static [&'static str] = &["id", "name", "status", "error_msg"];
+    const NAME: &'static str = "test_cases";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![&self.id, &self.name, &self.status, &self.error_msg]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self { id: row.get(0)?, name: row.get(1)?, status: row.get(2)?, error_msg: row.get(3)? })
+    }
+}
+
+impl Table for VirtualEnv {
+    const COLUMNS: &'static [&'static str] =
+        &["id", "name", "status", "owner", "lease_until", "reason"];
+    const NAME: &'static str = "virtual_envs";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![&self.id, &self.name, &self.status, &self.owner, &self.lease_until, &self.reason]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            status: row.get(2)?,
+            owner: row.get(3)?,
+            lease_until: row.get(4)?,
+            reason: row.get(5)?,
+        })
+    }
+}
+
+impl Table for Job {
+    const COLUMNS: &'static [&'static str] =
+        &["id", "name", "status", "progress", "params", "checkpoint", "resumed_from", "updated_at"];
+    const NAME: &'static str = "jobs";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![
+            &self.id,
+            &self.name,
+            &self.status,
+            &self.progress,
+            &self.params,
+            &self.checkpoint,
+            &self.resumed_from,
+            &self.updated_at,
+        ]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            status: row.get(2)?,
+            progress: row.get(3)?,
+            params: row.get(4)?,
+            checkpoint: row.get(5)?,
+            resumed_from: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
+}
+
+impl Table for ExcelData {
+    const COLUMNS: &'static [&'static str] =
+        &["id", "name", "file_type", "size", "created_at", "description"];
+    const NAME: &'static str = "excel_files";
+
+    fn values(&self) -> Vec<&dyn ToSql> {
+        vec![&self.id, &self.name, &self.file_type, &self.size, &self.created_at, &self.description]
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            file_type: row.get(2)?,
+            size: row.get(3)?,
+            created_at: row.get(4)?,
+            description: row.get(5)?,
+        })
+    }
+}
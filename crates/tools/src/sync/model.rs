@@ -0,0 +1,431 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EndpointConfig, EnvEndpoints, FetchQuery, Fetchable, Issue, JobEndpoints, MergeRequest, Record,
+    RequestError, RequestTool, Requirement, Store, Table, Transaction,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncSource {
+    Dts,
+    CodeHub,
+    Requirement,
+}
+
+impl SyncSource {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Dts => "dts",
+            Self::CodeHub => "codehub",
+            Self::Requirement => "requirement",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Updated,
+}
+
+impl ChangeKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Created => "新增",
+            Self::Updated => "修改",
+        }
+    }
+
+    fn from_code(code: &str) -> Self {
+        if code == "created" { Self::Created } else { Self::Updated }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncChange {
+    pub record_id: String,
+    pub title: String,
+    pub kind: ChangeKind,
+}
+
+/// The outcome of one sync run of a single source.
+#[derive(Debug, Clone)]
+pub struct SyncReport {
+    pub source: SyncSource,
+    pub synced_at: DateTime<Utc>,
+    pub fetched: usize,
+    pub changes: Vec<SyncChange>,
+}
+
+impl SyncReport {
+    pub fn created(&self) -> usize {
+        self.changes.iter().filter(|change| change.kind == ChangeKind::Created).count()
+    }
+
+    pub fn updated(&self) -> usize {
+        self.changes.iter().filter(|change| change.kind == ChangeKind::Updated).count()
+    }
+}
+
+/// Which endpoints to sync and how often, read from a JSON file.
+/// A source without an endpoint is simply not synced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub interval_minutes: u64,
+    pub dts: Option<EndpointConfig>,
+    pub codehub: Option<EndpointConfig>,
+    pub requirement: Option<EndpointConfig>,
+    /// The Hive job service, which jobs are launched on and polled from.
+    pub jobs: Option<JobEndpoints>,
+    /// The Hive environment service, which environment holds are shared
+    /// through.
+    pub envs: Option<EnvEndpoints>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            interval_minutes: 30,
+            dts: None,
+            codehub: None,
+            requirement: None,
+            jobs: None,
+            envs: None,
+        }
+    }
+}
+
+impl SyncConfig {
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let Ok(json) = std::fs::read(path) else {
+            return Self::default();
+        };
+        serde_json::from_slice(&json).unwrap_or_else(|err| {
+            tracing::error!("Invalid sync config {}: {}", path.display(), err);
+            Self::default()
+        })
+    }
+
+    pub fn endpoint(&self, source: SyncSource) -> Option<&EndpointConfig> {
+        match source {
+            SyncSource::Dts => self.dts.as_ref(),
+            SyncSource::CodeHub => self.codehub.as_ref(),
+            SyncSource::Requirement => self.requirement.as_ref(),
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_minutes.max(1) * 60)
+    }
+}
+
+/// A record type that is pulled from a remote system into the store.
+pub trait Syncable: Fetchable + Table + Record + Serialize {
+    const SOURCE: SyncSource;
+
+    /// Called in the merge transaction after `self` was written over
+    /// `previous`, the local record it replaced, if any.
+    fn merged(&self, _previous: Option<&Self>, _tx: &Transaction) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Syncable for Issue {
+    const SOURCE: SyncSource = SyncSource::Dts;
+
+    fn merged(&self, previous: Option<&Self>, tx: &Transaction) -> Result<()> {
+        self.record_merge(previous, tx)
+    }
+}
+
+impl Syncable for MergeRequest {
+    const SOURCE: SyncSource = SyncSource::CodeHub;
+}
+
+impl Syncable for Requirement {
+    const SOURCE: SyncSource = SyncSource::Requirement;
+}
+
+/// Pulls remote records into the store, writing only what changed.
+#[derive(Clone)]
+pub struct SyncTool {
+    store: Store,
+    request: RequestTool,
+}
+
+impl SyncTool {
+    pub fn new(store: Store, request: RequestTool) -> Self {
+        Self { store, request }
+    }
+
+    /// Fetch the records changed since the last sync of `T` and merge them.
+    /// The first sync, and every sync of an endpoint that can't filter by
+    /// modification date, fetches everything.
+    pub async fn sync<T: Syncable>(
+        &self,
+        endpoint: &EndpointConfig,
+    ) -> Result<SyncReport, RequestError> {
+        let query = self.query::<T>(endpoint)?;
+        let remote = self.request.fetch::<T>(endpoint, &query).await?;
+        Ok(self.merge(remote)?)
+    }
+
+    /// The query for the next sync of `T` from `endpoint`.
+    fn query<T: Syncable>(&self, endpoint: &EndpointConfig) -> Result<FetchQuery> {
+        if endpoint.filters.updated_since.is_none() {
+            return Ok(FetchQuery::default());
+        }
+        // Whole days, so that nothing changed on the day of the last sync is
+        // missed; what was already merged is skipped by `merge`.
+        let updated_since = self
+            .store
+            .last_sync(T::SOURCE)?
+            .map(|report| report.synced_at.with_timezone(&Local).date_naive());
+        Ok(FetchQuery { updated_since, ..FetchQuery::default() })
+    }
+
+    /// Sync `source` from its endpoint in `config`, `None` if it has none.
+    pub async fn sync_source(
+        &self,
+        source: SyncSource,
+        config: &SyncConfig,
+    ) -> Option<Result<SyncReport, RequestError>> {
+        let endpoint = config.endpoint(source)?;
+        Some(match source {
+            SyncSource::Dts => self.sync::<Issue>(endpoint).await,
+            SyncSource::CodeHub => self.sync::<MergeRequest>(endpoint).await,
+            SyncSource::Requirement => self.sync::<Requirement>(endpoint).await,
+        })
+    }
+
+    /// Merge `remote` into the store by id.
+    ///
+    /// A remote record replaces the local one when it is newer, or equally
+    /// old but different (for systems that don't report `updated_at`). A
+    /// local record edited after the remote one is kept. Records missing
+    /// remotely are left alone.
+    pub fn merge<T: Syncable>(&self, remote: Vec<T>) -> Result<SyncReport> {
+        let local: HashMap<String, T> = self
+            .store
+            .repository::<T>()
+            .all()?
+            .into_iter()
+            .map(|record| (record.id().to_string(), record))
+            .collect();
+
+        let fetched = remote.len();
+        let changed: Vec<(T, ChangeKind)> = remote
+            .into_iter()
+            .filter_map(|record| {
+                let kind = match local.get(record.id()) {
+                    None => ChangeKind::Created,
+                    Some(existing) if is_newer(&record, existing) => ChangeKind::Updated,
+                    Some(_) => return None,
+                };
+                Some((record, kind))
+            })
+            .collect();
+
+        let report = SyncReport {
+            source: T::SOURCE,
+            synced_at: Utc::now(),
+            fetched,
+            changes: changed
+                .iter()
+                .map(|(record, kind)| SyncChange {
+                    record_id: record.id().to_string(),
+                    title: record.title().to_string(),
+                    kind: *kind,
+                })
+                .collect(),
+        };
+
+        self.store.transaction(|tx| {
+            for (record, _) in &changed {
+                tx.upsert(record)?;
+                record.merged(local.get(record.id()), tx)?;
+            }
+            insert_report(tx.conn(), &report)?;
+            Ok(())
+        })?;
+        Ok(report)
+    }
+}
+
+fn is_newer<T: Record + Serialize>(remote: &T, local: &T) -> bool {
+    match remote.updated_at().cmp(&local.updated_at()) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => {
+            serde_json::to_value(remote).ok() != serde_json::to_value(local).ok()
+        },
+    }
+}
+
+fn insert_report(conn: &Connection, report: &SyncReport) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO sync_runs (source, synced_at, fetched, created, updated)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            report.source.code(),
+            report.synced_at,
+            report.fetched,
+            report.created(),
+            report.updated()
+        ],
+    )?;
+    let run_id = conn.last_insert_rowid();
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO sync_changes (run_id, record_id, title, change) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for change in &report.changes {
+        stmt.execute(params![run_id, change.record_id, change.title, change.kind.code()])?;
+    }
+    Ok(())
+}
+
+impl Store {
+    /// The most recent sync run of `source`, with the changes it made.
+    pub fn last_sync(&self, source: SyncSource) -> Result<Option<SyncReport>> {
+        let conn = self.conn();
+        let Some((run_id, synced_at, fetched)) = conn
+            .query_row(
+                "SELECT id, synced_at, fetched FROM sync_runs
+                 WHERE source = ?1 ORDER BY synced_at DESC, id DESC LIMIT 1",
+                [source.code()],
+                |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT record_id, title, change FROM sync_changes WHERE run_id = ?1 ORDER BY rowid",
+        )?;
+        let changes = stmt
+            .query_map([run_id], |row| {
+                Ok(SyncChange {
+                    record_id: row.get(0)?,
+                    title: row.get(1)?,
+                    kind: ChangeKind::from_code(&row.get::<_, String>(2)?),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Some(SyncReport { source, synced_at, fetched, changes }))
+    }
+
+    /// Ids of the records a sync of `source` ever created or updated.
+    pub fn synced_ids(&self, source: SyncSource) -> Result<HashSet<String>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT DISTINCT c.record_id FROM sync_changes c
+             JOIN sync_runs r ON r.id = c.run_id WHERE r.source = ?1",
+        )?;
+        let ids = stmt
+            .query_map([source.code()], |row| row.get(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{FilterParams, IssueStatus, Severity};
+
+    fn sync_tool() -> SyncTool {
+        SyncTool::new(Store::open_in_memory().unwrap(), RequestTool::new().unwrap())
+    }
+
+    fn issue(created_at: DateTime<Utc>, status: IssueStatus) -> Issue {
+        Issue {
+            id: "DTS001".to_string(),
+            title: "登录页面显示异常".to_string(),
+            severity: Severity::Serious,
+            status,
+            created_at,
+            resolved_at: None,
+            assignee: "张三".to_string(),
+            updated_at: Some(created_at),
+            reopen_count: 0,
+        }
+    }
+
+    fn endpoint(updated_since: Option<&str>) -> EndpointConfig {
+        EndpointConfig {
+            filters: FilterParams {
+                updated_since: updated_since.map(str::to_string),
+                ..FilterParams::default()
+            },
+            ..EndpointConfig::default()
+        }
+    }
+
+    #[test]
+    fn incremental_sync_asks_for_records_modified_since_the_last_sync() {
+        let sync = sync_tool();
+        let endpoint = endpoint(Some("modified_since"));
+        assert!(sync.query::<Issue>(&endpoint).unwrap().updated_since.is_none());
+
+        let report = sync.merge(vec![issue(Utc::now(), IssueStatus::Submitted)]).unwrap();
+        let query = sync.query::<Issue>(&endpoint).unwrap();
+        let day = report.synced_at.with_timezone(&Local).date_naive();
+        assert_eq!(query.updated_since, Some(day));
+        // Not the creation date filter, which would skip older records.
+        assert_eq!(
+            endpoint.filters.params(&query),
+            [("modified_since".to_string(), day.format("%Y-%m-%d").to_string())]
+        );
+    }
+
+    #[test]
+    fn endpoint_without_a_modified_filter_is_fetched_in_full() {
+        let sync = sync_tool();
+        let endpoint = endpoint(None);
+        sync.merge(vec![issue(Utc::now(), IssueStatus::Submitted)]).unwrap();
+
+        let query = sync.query::<Issue>(&endpoint).unwrap();
+        assert!(query.since.is_none() && query.updated_since.is_none());
+        assert!(endpoint.filters.params(&query).is_empty());
+    }
+
+    #[test]
+    fn older_record_modified_after_the_last_sync_is_merged() {
+        let sync = sync_tool();
+        let created_at = Utc::now() - Duration::days(10);
+        sync.merge(vec![issue(created_at, IssueStatus::Submitted)]).unwrap();
+
+        let mut modified = issue(created_at, IssueStatus::Fixing);
+        modified.updated_at = Some(Utc::now());
+        let report = sync.merge(vec![modified]).unwrap();
+
+        assert_eq!(report.updated(), 1);
+        assert_eq!(report.created(), 0);
+        let stored = sync.store.issues().get("DTS001").unwrap().unwrap();
+        assert_eq!(stored.status, IssueStatus::Fixing);
+        assert_eq!(stored.created_at, created_at);
+    }
+}
@@ -1,6 +1,7 @@
 use gpui::{
-    AnyView, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
-    StatefulInteractiveElement, Styled, Subscription, Window, div, prelude::FluentBuilder, px,
+    AnyView, App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement,
+    Render, StatefulInteractiveElement, Styled, Subscription, Window, div, prelude::FluentBuilder,
+    px,
 };
 use gpui_component::{
     ActiveTheme, IconName,
@@ -10,7 +11,7 @@ use gpui_component::{
     v_flex,
 };
 
-use super::view::{CodeHubView, DtsView, ExcelView, HiveView, RequirementView};
+use super::view::{CodeHubView, DtsView, ExcelView, HiveView, ReportView, RequirementView};
 
 struct StoryItem {
     name: &'static str,
@@ -46,7 +47,7 @@ impl TasksApp {
         };
 
         if let Some(init_story) = init_story {
-            this.set_active_story(init_story);
+            this.set_active_story(init_story, cx);
         }
 
         let subscription = cx.subscribe(&this.search_input, |this, _, event, cx| {
@@ -96,14 +97,32 @@ impl TasksApp {
                 IconName::ListChecks,
                 RequirementView::view(window, cx).into(),
             ),
+            StoryItem::new(
+                "Report",
+                "MR、问题单与需求趋势图表",
+                IconName::ChartPie,
+                ReportView::view(window, cx).into(),
+            ),
         ]
     }
 
-    fn set_active_story(&mut self, name: &str) {
-        if let Some(index) =
+    /// Activate the story called `name`. A path after a `/` deep links into
+    /// the story, e.g. `hive/envs` opens the Hive virtual environment tab.
+    fn set_active_story(&mut self, name: &str, cx: &mut App) {
+        let (name, path) = name.split_once('/').unwrap_or((name, ""));
+        let Some(index) =
             self.stories.iter().position(|story| story.name.eq_ignore_ascii_case(name))
-        {
-            self.active_index = Some(index);
+        else {
+            return;
+        };
+        self.active_index = Some(index);
+
+        if path.is_empty() {
+            return;
+        }
+        match self.stories[index].view.clone().downcast::<HiveView>() {
+            Ok(hive) => hive.update(cx, |hive, cx| hive.open_path(path, cx)),
+            Err(_) => tracing::warn!("Story {} has no deep links, ignoring {}", name, path),
         }
     }
 }
@@ -4,6 +4,11 @@ mod themes;
 mod title_bar;
 mod utils;
 pub mod view;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+};
+
 use gpui::{
     Action, AnyElement, AnyView, App, AppContext, Bounds, Context, Div, Entity, FocusHandle,
     Focusable, Global, InteractiveElement, IntoElement, KeyBinding, ParentElement, Pixels, Render,
@@ -19,6 +24,7 @@ use gpui_component::{
     v_flex,
 };
 use serde::Deserialize;
+use tools::{DeployBackend, LeaseTool, RequestTool, Store, SyncConfig, SyncTool};
 use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
 
 use crate::title_bar::AppTitleBar;
@@ -51,12 +57,84 @@ actions!(ui, [
     ToggleListActiveHighlight
 ]);
 
+const APP_DIR: &str = "tasks-mine";
+const STORE_FILE: &str = "tasks-mine.db";
+const SESSION_FILE: &str = "session.json";
+const SYNC_CONFIG_FILE: &str = "sync.json";
+/// `name` in the per-user data directory, e.g. `~/.local/share/tasks-mine`.
+fn data_file(name: &str) -> PathBuf {
+    app_file(dirs::data_dir(), name)
+}
+
+/// `name` in the per-user config directory, e.g. `~/.config/tasks-mine`.
+fn config_file(name: &str) -> PathBuf {
+    app_file(dirs::config_dir(), name)
+}
+
+/// Falls back to the home directory, then the temp directory, on systems
+/// without the per-user directory.
+fn app_file(base: Option<PathBuf>, name: &str) -> PathBuf {
+    let base = base.unwrap_or_else(|| {
+        let fallback = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+        tracing::warn!("No per-user directory on this system, using {}", fallback.display());
+        fallback
+    });
+    base.join(APP_DIR).join(name)
+}
+
 pub struct AppState {
     pub invisible_panels: Entity<Vec<SharedString>>,
+    pub store: Store,
+    pub request: RequestTool,
+    pub sync: SyncTool,
+    pub sync_config: Arc<SyncConfig>,
+    /// Environment holds, shared through the Hive environment service when
+    /// `sync_config` has one.
+    pub leases: LeaseTool,
+    /// The OS user, which per-user settings such as saved filters are keyed by.
+    pub user: String,
+    /// Where Hive deployments run, `None` when no backend is configured.
+    /// Only the mock backend exists so far, behind the `mock-deploy`
+    /// feature; a real one is plugged in here.
+    pub deploy_backend: Option<Arc<dyn DeployBackend>>,
 }
 impl AppState {
     fn init(cx: &mut App) {
-        let state = Self { invisible_panels: cx.new(|_| Vec::new()) };
+        let store = Store::open(data_file(STORE_FILE)).unwrap_or_else(|err| {
+            tracing::error!("Failed to open store, data will not be persisted: {:?}", err);
+            Store::open_in_memory().expect("failed to open in-memory store")
+        });
+        let request = RequestTool::with_session_file(data_file(SESSION_FILE))
+            .or_else(|err| {
+                tracing::error!("Failed to load session, logins will not be kept: {:?}", err);
+                RequestTool::new()
+            })
+            .expect("failed to create HTTP client");
+        let sync = SyncTool::new(store.clone(), request.clone());
+        let sync_config = Arc::new(SyncConfig::load(config_file(SYNC_CONFIG_FILE)));
+        let leases = LeaseTool::new(store.clone(), request.clone(), sync_config.envs.clone());
+        // Nothing is deploying yet, so environments still marked as deploying
+        // were left so by an earlier run that ended mid-deployment.
+        match store.reset_deploys() {
+            Ok(envs) if !envs.is_empty() => {
+                tracing::info!("Reset {} environments stuck deploying", envs.len())
+            },
+            Ok(_) => {},
+            Err(err) => tracing::error!("Failed to reset stuck deployments: {}", err),
+        }
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "default".to_string());
+        let state = Self {
+            invisible_panels: cx.new(|_| Vec::new()),
+            store,
+            request,
+            sync,
+            sync_config,
+            leases,
+            user,
+            deploy_backend: deploy_backend(),
+        };
         cx.set_global::<AppState>(state);
     }
 
@@ -69,6 +147,16 @@ impl AppState {
     }
 }
 
+#[cfg(feature = "mock-deploy")]
+fn deploy_backend() -> Option<Arc<dyn DeployBackend>> {
+    Some(Arc::new(tools::MockDeployBackend::default()))
+}
+
+#[cfg(not(feature = "mock-deploy"))]
+fn deploy_backend() -> Option<Arc<dyn DeployBackend>> {
+    None
+}
+
 pub fn create_new_window<F, E>(title: &str, crate_view_fn: F, cx: &mut App)
 where
     E: Into<AnyView>,
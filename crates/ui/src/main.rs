@@ -5,7 +5,7 @@ use tasks_mine::app::TasksApp;
 fn main() {
     let app = Application::new().with_assets(Assets);
 
-    // Parse `cargo run -- <story_name>`
+    // Parse `cargo run -- <story_name>[/<path>]`, e.g. `hive/envs`
     let name = std::env::args().nth(1);
 
     app.run(move |cx| {
@@ -0,0 +1,18 @@
+use chrono::NaiveDate;
+use gpui_component::calendar::Date;
+
+/// The picked range of a range date picker, if both ends are set.
+pub fn picked_range(date: &Date) -> Option<(NaiveDate, NaiveDate)> {
+    match *date {
+        Date::Range(Some(start), Some(end)) => Some((start, end)),
+        _ => None,
+    }
+}
+
+/// The picked day of a single date picker.
+pub fn picked_date(date: &Date) -> Option<NaiveDate> {
+    match *date {
+        Date::Single(date) => date,
+        _ => None,
+    }
+}
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Duration, Local, Utc};
+
+/// Human-readable span such as "2天3小时" or "45分钟", keeping only the two
+/// largest units.
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}分钟", minutes),
+        (0, _) => format!("{}小时{}分钟", hours, minutes),
+        _ => format!("{}天{}小时", days, hours),
+    }
+}
+
+/// A timestamp in local time, to the minute.
+pub fn format_datetime(datetime: DateTime<Utc>) -> String {
+    datetime.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
@@ -1 +1,4 @@
-
+mod date;
+mod format;
+pub use date::*;
+pub use format::*;
@@ -0,0 +1,130 @@
+use gpui::{
+    App, Hsla, IntoElement, ParentElement, RenderOnce, SharedString, Styled, Window, div, px, rgb,
+};
+use gpui_component::{
+    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    button::*,
+    chart::{AreaChart, BarChart},
+    h_flex, v_flex,
+};
+use tools::{Chart, ChartKind, series_color};
+
+use crate::view::export_chart;
+
+/// At most this many x-axis ticks are labelled.
+const MAX_TICKS: usize = 12;
+
+/// One x-axis category of a [`Chart`], with a value per series.
+#[derive(Clone)]
+struct ChartRow {
+    label: SharedString,
+    values: Vec<f64>,
+}
+
+/// A titled chart with a legend and PNG/SVG export buttons.
+#[derive(IntoElement)]
+pub struct ChartCard {
+    id: SharedString,
+    chart: Chart,
+}
+
+impl ChartCard {
+    pub fn new(id: impl Into<SharedString>, chart: Chart) -> Self {
+        Self { id: id.into(), chart }
+    }
+
+    fn rows(&self) -> Vec<ChartRow> {
+        self.chart
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| ChartRow {
+                label: label.clone().into(),
+                values: self
+                    .chart
+                    .series
+                    .iter()
+                    .map(|series| series.values.get(index).copied().unwrap_or_default())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn color(index: usize) -> Hsla {
+        rgb(series_color(index)).into()
+    }
+}
+
+impl RenderOnce for ChartCard {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        let rows = self.rows();
+        let tick_margin = rows.len().div_ceil(MAX_TICKS).max(1);
+
+        // 多个序列的柱状图按序列并排显示，折线图叠加在同一坐标系中
+        let plot = match self.chart.kind {
+            ChartKind::Bar => h_flex()
+                .size_full()
+                .gap_4()
+                .children((0..self.chart.series.len()).map(|index| {
+                    div().flex_1().h_full().child(
+                        BarChart::new(rows.clone())
+                            .x(|row: &ChartRow| row.label.clone())
+                            .y(move |row: &ChartRow| row.values[index])
+                            .fill(move |_| Self::color(index))
+                            .tick_margin(tick_margin),
+                    )
+                }))
+                .into_any_element(),
+            ChartKind::Line => {
+                let mut chart = AreaChart::new(rows.clone())
+                    .x(|row: &ChartRow| row.label.clone())
+                    .tick_margin(tick_margin);
+                for index in 0..self.chart.series.len() {
+                    chart = chart
+                        .y(move |row: &ChartRow| row.values[index])
+                        .stroke(Self::color(index))
+                        .fill(Self::color(index).opacity(0.1));
+                }
+                chart.into_any_element()
+            },
+        };
+
+        let export_button = |extension: &'static str| {
+            let chart = self.chart.clone();
+            Button::new(SharedString::from(format!("{}-{}", self.id, extension)))
+                .ghost()
+                .small()
+                .label(extension.to_uppercase())
+                .icon(Icon::new(IconName::Download))
+                .on_click(move |_, window, cx| export_chart(chart.clone(), extension, window, cx))
+        };
+
+        v_flex()
+            .w_full()
+            .p_4()
+            .gap_3()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_lg()
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .child(div().font_semibold().child(self.chart.title.clone()))
+                    .child(
+                        h_flex().gap_1().child(export_button("png")).child(export_button("svg")),
+                    ),
+            )
+            .child(h_flex().gap_4().text_sm().children(self.chart.series.iter().enumerate().map(
+                |(index, series)| {
+                    h_flex()
+                        .gap_1()
+                        .items_center()
+                        .child(div().size(px(10.)).rounded_sm().bg(Self::color(index)))
+                        .child(series.name.clone())
+                },
+            )))
+            .child(div().w_full().h(px(240.)).child(plot))
+    }
+}
@@ -0,0 +1,317 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, Window, div, prelude::FluentBuilder, rgb,
+};
+use gpui_component::{
+    WindowExt,
+    button::*,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+use tools::{
+    Coded as _, EndpointConfig, MergeRequest, MrDraft, MrFormError, MrStatus, RequestError,
+    RequestTool, Store,
+};
+
+use crate::{
+    AppState,
+    view::{FormSaved, PersonInput, RecordForm, field_error, form_field, status_select},
+};
+
+/// Dialog body for creating an MR, or editing one when `existing` is set.
+pub struct MrForm {
+    store: Store,
+    request: RequestTool,
+    existing: Option<MergeRequest>,
+    /// The CodeHub endpoint, only when it accepts new MRs.
+    endpoint: Option<EndpointConfig>,
+    id_input: Entity<InputState>,
+    title_input: Entity<InputState>,
+    author_input: Entity<PersonInput>,
+    repository_input: Entity<InputState>,
+    add_lines_input: Entity<InputState>,
+    del_lines_input: Entity<InputState>,
+    status_select: Entity<SelectState<Vec<SharedString>>>,
+    draft: MrDraft,
+    push: bool,
+    errors: Vec<MrFormError>,
+    push_error: Option<String>,
+    push_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<FormSaved<MergeRequest>> for MrForm {}
+
+impl RecordForm for MrForm {
+    type Record = MergeRequest;
+
+    const NOUN: &'static str = " MR";
+    const WIDTH: f32 = 600.;
+
+    fn new(existing: Option<MergeRequest>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let draft = existing.as_ref().map_or_else(MrDraft::new, MrDraft::edit);
+        let mut input = |placeholder: &'static str, value: &str| {
+            cx.new(|cx| {
+                let mut input = InputState::new(window, cx).placeholder(placeholder);
+                input.set_value(value.to_string(), window, cx);
+                input
+            })
+        };
+        let id_input = input("留空则生成本地编号", &draft.id);
+        let title_input = input("MR 标题", &draft.title);
+        let repository_input = input("仓库名称，可不填", &draft.repository);
+        let add_lines_input = input("0", &draft.add_lines);
+        let del_lines_input = input("0", &draft.del_lines);
+        let author_input = cx
+            .new(|cx| PersonInput::new("输入姓名或从建议中选择", draft.author.clone(), window, cx));
+        let statuses = MrDraft::statuses(existing.as_ref());
+        let status_select = status_select(&statuses, draft.status, window, cx);
+
+        let mut subscriptions: Vec<Subscription> =
+            [&id_input, &title_input, &repository_input, &add_lines_input, &del_lines_input]
+                .into_iter()
+                .map(|input| cx.subscribe_in(input, window, Self::on_input_event))
+                .collect();
+        subscriptions.push(cx.subscribe_in(
+            &status_select,
+            window,
+            |this, _, event: &SelectEvent<Vec<SharedString>>, _, cx| {
+                let SelectEvent::Confirm(value) = event;
+                this.draft.status = value.as_deref().and_then(MrStatus::parse);
+                cx.notify();
+            },
+        ));
+
+        let state = AppState::global(cx);
+        let endpoint =
+            state.sync_config.codehub.clone().filter(|codehub| codehub.create_url.is_some());
+
+        Self {
+            store: state.store.clone(),
+            request: state.request.clone(),
+            existing,
+            endpoint,
+            id_input,
+            title_input,
+            author_input,
+            repository_input,
+            add_lines_input,
+            del_lines_input,
+            status_select,
+            draft,
+            push: false,
+            errors: Vec::new(),
+            push_error: None,
+            push_task: None,
+            _subscriptions: subscriptions,
+        }
+    }
+}
+
+impl MrForm {
+    fn on_input_event(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value().to_string();
+            if state == &self.id_input {
+                self.draft.id = value;
+            } else if state == &self.title_input {
+                self.draft.title = value;
+            } else if state == &self.repository_input {
+                self.draft.repository = value;
+            } else if state == &self.add_lines_input {
+                self.draft.add_lines = value;
+            } else if state == &self.del_lines_input {
+                self.draft.del_lines = value;
+            }
+            cx.notify();
+        }
+    }
+
+    fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.push_task.is_some() {
+            return;
+        }
+        self.draft.author = self.author_input.read(cx).value(cx);
+        let mr = match self.draft.validate(self.existing.as_ref(), |id| self.mr_exists(id)) {
+            Ok(mr) => mr,
+            Err(errors) => {
+                self.errors = errors;
+                cx.notify();
+                return;
+            },
+        };
+        self.errors.clear();
+        self.push_error = None;
+
+        let endpoint = self.endpoint.clone().filter(|_| self.push && self.existing.is_none());
+        let Some(endpoint) = endpoint else {
+            self.save(mr, window, cx);
+            return;
+        };
+
+        // 先提交到 CodeHub，成功后再以 CodeHub 分配的编号保存到本地
+        let request = self.request.clone();
+        self.push_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = request.create(&endpoint, &mr).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.push_task = None;
+                match result {
+                    Ok(remote_id) if this.mr_exists(&remote_id) => {
+                        tracing::error!("Remote MR id {} already exists locally", remote_id);
+                        this.push_error = Some(format!(
+                            "已提交到 CodeHub，但返回的编号 {} 在本地已存在，未保存到本地",
+                            remote_id
+                        ));
+                        cx.notify();
+                    },
+                    Ok(remote_id) => {
+                        let mut mr = mr;
+                        mr.id = remote_id;
+                        this.save(mr, window, cx);
+                    },
+                    Err(err @ RequestError::Schema { .. }) => {
+                        // 已在 CodeHub 创建，本地不保存，下次同步时拉取，避免重复
+                        tracing::error!("Pushed MR has no usable remote id: {}", err);
+                        this.push_error = Some(format!(
+                            "已提交到 CodeHub，但未能读取编号，未保存到本地，同步后可见: {}",
+                            err
+                        ));
+                        cx.notify();
+                    },
+                    Err(err) => {
+                        tracing::error!("Failed to push MR: {}", err);
+                        this.push_error = Some(format!("提交到 CodeHub 失败，未保存: {}", err));
+                        cx.notify();
+                    },
+                }
+            });
+        }));
+        cx.notify();
+    }
+
+    fn mr_exists(&self, id: &str) -> bool {
+        self.store.merge_requests().get(id).map(|mr| mr.is_some()).unwrap_or_else(|err| {
+            tracing::error!("Failed to look up MR {}: {:?}", id, err);
+            false
+        })
+    }
+
+    fn save(&mut self, mr: MergeRequest, window: &mut Window, cx: &mut Context<Self>) {
+        struct MrSaved;
+        let message = match self.store.merge_requests().upsert(&mr) {
+            Ok(()) => {
+                let message = format!("已保存 MR #{}", mr.id);
+                cx.emit(FormSaved(mr));
+                window.close_dialog(cx);
+                message
+            },
+            Err(err) => {
+                tracing::error!("Failed to save MR {}: {:?}", mr.id, err);
+                format!("保存 MR 失败: {}", err)
+            },
+        };
+        window.push_notification(Notification::new().message(message).id::<MrSaved>(), cx);
+    }
+
+    fn field(
+        &self,
+        label: &'static str,
+        required: bool,
+        input: impl IntoElement,
+        cx: &App,
+    ) -> impl IntoElement {
+        form_field(label, required, field_error(&self.errors, label), input, cx)
+    }
+}
+
+impl Render for MrForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let creating = self.existing.is_none();
+        let pushing = self.push_task.is_some();
+
+        v_flex()
+            .w_full()
+            .gap_3()
+            .child(self.field("ID", false, Input::new(&self.id_input).disabled(!creating), cx))
+            .child(self.field("标题", true, Input::new(&self.title_input), cx))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "作者",
+                        true,
+                        self.author_input.clone(),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "仓库",
+                        false,
+                        Input::new(&self.repository_input),
+                        cx,
+                    ))),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "新增行数",
+                        false,
+                        Input::new(&self.add_lines_input),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "删除行数",
+                        false,
+                        Input::new(&self.del_lines_input),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "状态",
+                        true,
+                        Select::new(&self.status_select).placeholder("选择状态"),
+                        cx,
+                    ))),
+            )
+            .when(creating, |this| {
+                let label = if self.endpoint.is_some() {
+                    "同时提交到 CodeHub"
+                } else {
+                    "同时提交到 CodeHub（未配置创建地址）"
+                };
+                this.child(
+                    Checkbox::new("push-mr")
+                        .label(label)
+                        .checked(self.push)
+                        .disabled(self.endpoint.is_none())
+                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                            this.push = *checked;
+                            cx.notify();
+                        })),
+                )
+            })
+            .when_some(self.push_error.clone(), |this, error| {
+                this.child(div().text_sm().text_color(rgb(0xef4444)).child(error))
+            })
+            .child(
+                h_flex().w_full().justify_end().child(
+                    Button::new("save-mr-btn")
+                        .primary()
+                        .label(if pushing { "正在提交..." } else { "保存" })
+                        .disabled(pushing)
+                        .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
+                ),
+            )
+    }
+}
@@ -1,2 +1,4 @@
+mod form;
 mod ui;
+pub use form::*;
 pub use ui::*;
@@ -1,111 +1,105 @@
-use std::{fs::File, io::prelude::*};
+use std::collections::HashSet;
 
-use chrono::Days;
+use chrono::{Days, NaiveDate};
 use gpui::{
     App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
-    Styled, Subscription, Window, div, rgb,
+    SharedString, StatefulInteractiveElement, Styled, Subscription, Window, div, px, rgb,
 };
 use gpui_component::{
     ActiveTheme, Icon, IconName, Sizable, StyledExt, WindowExt,
     button::*,
+    checkbox::Checkbox,
     date_picker::{DatePicker, DatePickerEvent, DatePickerState},
     h_flex,
     input::{Input, InputEvent, InputState},
+    notification::Notification,
     v_flex,
 };
-use tools::MergeRequest;
+use tools::{CodeHubTool, Exportable as _, MergeRequest, Record as _, Store, SyncSource, Workload};
+
+use super::MrForm;
+use crate::{
+    AppState,
+    utils::{format_duration, picked_range},
+    view::{SyncEvent, SyncPanel, export_to_excel, import_from_excel, open_form},
+};
+
+/// How many days the workload period covers when the view opens.
+const DEFAULT_PERIOD_DAYS: u64 = 28;
 
 pub struct CodeHubView {
+    store: Store,
     mrs: Vec<MergeRequest>,
+    sync_panel: Entity<SyncPanel>,
+    date_range: Option<(NaiveDate, NaiveDate)>,
     date_range_picker: Entity<DatePickerState>,
-    date_picker_value: Option<String>,
     search_input: Entity<InputState>,
     search_value: Option<String>,
+    /// Ids of the MRs ticked for a bulk delete.
+    selected: HashSet<String>,
+    /// The last deleted MRs, restored by the undo button of the notification.
+    deleted: Vec<MergeRequest>,
     _subscriptions: Vec<Subscription>,
 }
 
 impl CodeHubView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let now = chrono::Local::now().naive_local().date();
+        let period_start = now - Days::new(DEFAULT_PERIOD_DAYS - 1);
         let date_range_picker = cx.new(|cx| {
             let mut picker = DatePickerState::new(window, cx);
-            picker.set_date((now, now.checked_add_days(Days::new(4)).unwrap()), window, cx);
+            picker.set_date((period_start, now), window, cx);
             picker
         });
         let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("搜索 MR..."));
+        let sync_panel = SyncPanel::view(SyncSource::CodeHub, cx);
 
-        // 添加示例数据
-        let mrs = vec![
-            MergeRequest {
-                id: "1".to_string(),
-                title: "添加用户认证功能".to_string(),
-                author: "张三".to_string(),
-                created_at: "2026-01-21 10:00:00".to_string(),
-                add_lines: 120,
-                del_lines: 30,
-                status: "merged".to_string(),
-            },
-            MergeRequest {
-                id: "2".to_string(),
-                title: "修复登录页面 bug".to_string(),
-                author: "李四".to_string(),
-                created_at: "2026-01-25 10:00:00".to_string(),
-                add_lines: 45,
-                del_lines: 15,
-                status: "merged".to_string(),
-            },
-            MergeRequest {
-                id: "3".to_string(),
-                title: "优化数据库查询性能".to_string(),
-                author: "王五".to_string(),
-                created_at: "2026-01-26 10:00:00".to_string(),
-                add_lines: 80,
-                del_lines: 25,
-                status: "open".to_string(),
-            },
-        ];
         // 添加订阅
         let subscriptions = vec![
             cx.subscribe_in(&search_input, window, Self::on_input_event),
-            cx.subscribe(&date_range_picker, move |this, _, ev, _| match ev {
+            cx.subscribe(&date_range_picker, |this, _, ev, cx| match ev {
                 DatePickerEvent::Change(date) => {
-                    // Some("2026-01-21 - 2026-01-23")
-                    this.date_picker_value = date.format("%Y-%m-%d").map(|s| s.to_string());
-                    if let Some(date_range) = &this.date_picker_value {
-                        let dates: Vec<&str> = date_range.split(" - ").collect();
-                        if dates.len() == 2 {
-                            let start_date =
-                                chrono::NaiveDate::parse_from_str(dates[0], "%Y-%m-%d")
-                                    .unwrap_or(now);
-                            let end_date = chrono::NaiveDate::parse_from_str(dates[1], "%Y-%m-%d")
-                                .unwrap_or(now);
-                            // 过滤 MR 列表
-                            this.mrs.retain(|mr| {
-                                match chrono::NaiveDateTime::parse_from_str(
-                                    &mr.created_at,
-                                    "%Y-%m-%d %H:%M:%S",
-                                ) {
-                                    Ok(mr_datetime) => {
-                                        let mr_date = mr_datetime.date();
-                                        mr_date >= start_date && mr_date <= end_date
-                                    },
-                                    Err(_) => true,
-                                }
-                            });
-                        }
-                    }
+                    // 清空日期时不按日期过滤
+                    this.date_range = picked_range(date);
+                    this.reload();
+                    cx.notify();
                 },
             }),
+            cx.subscribe(&sync_panel, |this, _, _: &SyncEvent, cx| {
+                this.reload();
+                cx.notify();
+            }),
         ];
 
-        Self {
-            mrs,
+        let mut this = Self {
+            store: AppState::global(cx).store.clone(),
+            mrs: Vec::new(),
+            sync_panel,
+            date_range: Some((period_start, now)),
             date_range_picker,
-            date_picker_value: None,
             search_input,
             search_value: None,
+            selected: HashSet::new(),
+            deleted: Vec::new(),
             _subscriptions: subscriptions,
+        };
+        this.reload();
+        this
+    }
+
+    fn reload(&mut self) {
+        self.mrs = self.store.merge_requests().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load merge requests: {:?}", err);
+            Vec::new()
+        });
+        if let Some((start_date, end_date)) = self.date_range {
+            self.mrs.retain(|mr| {
+                let mr_date = mr.created_at.with_timezone(&chrono::Local).date_naive();
+                mr_date >= start_date && mr_date <= end_date
+            });
         }
+        let mrs = &self.mrs;
+        self.selected.retain(|id| mrs.iter().any(|mr| mr.id == *id));
     }
 
     pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
@@ -134,35 +128,76 @@ impl CodeHubView {
         };
     }
 
-    fn calculate_stats(&self) -> (i32, i32, usize) {
-        let total_additions: i32 = self.mrs.iter().map(|mr| mr.add_lines).sum();
-        let total_deletions: i32 = self.mrs.iter().map(|mr| mr.del_lines).sum();
-        let total_count = self.mrs.len();
-        (total_additions, total_deletions, total_count)
+    /// Ask for confirmation, then delete the MRs with `ids`.
+    fn confirm_delete(&mut self, ids: Vec<String>, window: &mut Window, cx: &mut Context<Self>) {
+        if ids.is_empty() {
+            return;
+        }
+        let message = match ids.as_slice() {
+            [id] => format!("确定删除 MR #{}？", id),
+            _ => format!("确定删除选中的 {} 个 MR？", ids.len()),
+        };
+        let view = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view.clone();
+            let ids = ids.clone();
+            dialog.title("删除 MR").confirm().child(message.clone()).on_ok(move |_, window, cx| {
+                _ = view.update(cx, |this, cx| this.delete(&ids, window, cx));
+                true
+            })
+        });
+    }
+
+    fn delete(&mut self, ids: &[String], window: &mut Window, cx: &mut Context<Self>) {
+        struct MrDeleted;
+        let records: Vec<MergeRequest> =
+            self.mrs.iter().filter(|mr| ids.contains(&mr.id)).cloned().collect();
+        let note = match self.store.merge_requests().delete_all(ids) {
+            Ok(count) => {
+                self.deleted = records;
+                let view = cx.entity().downgrade();
+                Notification::new().message(format!("已删除 {} 个 MR", count)).action(
+                    move |_, _, _| {
+                        let view = view.clone();
+                        Button::new("undo-delete-mr").ghost().small().label("撤销").on_click(
+                            move |_, _, cx| {
+                                _ = view.update(cx, |this, cx| this.undo_delete(cx));
+                            },
+                        )
+                    },
+                )
+            },
+            Err(err) => {
+                tracing::error!("Failed to delete merge requests: {:?}", err);
+                Notification::new().message(format!("删除 MR 失败: {}", err))
+            },
+        };
+        window.push_notification(note.id::<MrDeleted>(), cx);
+        self.reload();
+        cx.notify();
+    }
+
+    fn undo_delete(&mut self, cx: &mut Context<Self>) {
+        let deleted = std::mem::take(&mut self.deleted);
+        if let Err(err) = self.store.merge_requests().upsert_all(&deleted) {
+            tracing::error!("Failed to restore deleted merge requests: {:?}", err);
+            self.deleted = deleted;
+        }
+        self.reload();
+        cx.notify();
+    }
+
+    /// 根据搜索查询过滤MR（日期过滤已在 reload 中完成）
+    fn filtered_mrs(&self, cx: &App) -> Vec<&MergeRequest> {
+        let search_query = self.search_input.read(cx).value().trim().to_lowercase();
+        self.mrs.iter().filter(|mr: &&MergeRequest| mr.matches(&search_query)).collect()
     }
 }
 
 impl Render for CodeHubView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let search_query = self.search_input.read(cx).value().trim().to_lowercase();
-
-        // 根据搜索查询过滤MR（日期过滤已在 on_date_picker_change 中完成）
-        let filtered_mrs: Vec<&MergeRequest> = self
-            .mrs
-            .iter()
-            .filter(|mr: &&MergeRequest| {
-                // 搜索过滤
-                search_query.is_empty()
-                    || mr.title.to_lowercase().contains(&search_query)
-                    || mr.author.to_lowercase().contains(&search_query)
-                    || mr.id.contains(&search_query)
-            })
-            .collect();
-
-        // 计算过滤后的统计数据
-        let total_additions: i32 = filtered_mrs.iter().map(|mr| mr.add_lines).sum();
-        let total_deletions: i32 = filtered_mrs.iter().map(|mr| mr.del_lines).sum();
-        let total_count = filtered_mrs.len();
+        let filtered_mrs = self.filtered_mrs(cx);
 
         v_flex()
             .size_full()
@@ -184,20 +219,61 @@ impl Render for CodeHubView {
                         ),
                 ),
             )
+            .child(self.sync_panel.clone())
             .child(
                 // 统计卡片区
+                // 计算过滤后的统计数据
+                h_flex().w_full().gap_4().children(
+                    MergeRequest::summary(&filtered_mrs)
+                        .into_iter()
+                        .map(|(label, value)| self.stat_card(label, &value.to_string(), cx)),
+                ),
+            )
+            .children(self.date_range.map(|(start, end)| {
+                // 工作量分布
+                let report = CodeHubTool::workload(&filtered_mrs, start, end);
                 h_flex()
                     .w_full()
                     .gap_4()
-                    .child(self.stat_card("MR 数量", &total_count.to_string(), cx))
-                    .child(self.stat_card("新增行数", &format!("+{}", total_additions), cx))
-                    .child(self.stat_card("删除行数", &format!("-{}", total_deletions), cx))
-                    .child(self.stat_card(
-                        "净变化",
-                        &format!("{:+}", total_additions + total_deletions),
+                    .items_start()
+                    .child(self.render_workload_table(
+                        "workload-author",
+                        "按人员",
+                        report.by_author,
                         cx,
-                    )),
-            )
+                    ))
+                    .child(
+                        self.render_workload_table(
+                            "workload-repo",
+                            "按仓库",
+                            report
+                                .by_repository
+                                .into_iter()
+                                .map(|(repo, w)| {
+                                    let repo = if repo.is_empty() {
+                                        "未指定仓库".into()
+                                    } else {
+                                        repo
+                                    };
+                                    (repo, w)
+                                })
+                                .collect(),
+                            cx,
+                        ),
+                    )
+                    .child(
+                        self.render_workload_table(
+                            "workload-week",
+                            "按周",
+                            report
+                                .by_week
+                                .into_iter()
+                                .map(|(week, w)| (format!("{} 周", week.format("%m-%d")), w))
+                                .collect(),
+                            cx,
+                        ),
+                    )
+            }))
             .child(
                 // 搜索区
                 v_flex()
@@ -216,19 +292,64 @@ impl Render for CodeHubView {
                             .w_full()
                             .gap_4()
                             .items_end()
-                            .child(v_flex().gap_1().child(DatePicker::new(&self.date_range_picker)))
+                            .child(
+                                v_flex().gap_1().child(
+                                    DatePicker::new(&self.date_range_picker).cleanable(true),
+                                ),
+                            )
                             .child(
                                 Button::new("filter-btn")
                                     .primary()
                                     .label("查询")
                                     .icon(Icon::new(IconName::Search))
-                                    .on_click(cx.listener(|this, _, _window, cx| {
-                                        // 使用 update 方法来访问 DatePickerState 的可变引用
-                                        this.date_range_picker.update(cx, |_picker_state, _cx| {
-                                            // 在这里可以访问 picker_state 的方法
-                                            println!("Picker state in update: [DatePickerState]");
-                                            // 尝试调用可能存在的方法来获取日期范围
-                                        });
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.reload();
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                Button::new("refresh-btn")
+                                    .ghost()
+                                    .icon(Icon::new(IconName::RefreshCw))
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.sync_panel.update(cx, |panel, cx| panel.sync_now(cx));
+                                    })),
+                            )
+                            .child(
+                                Button::new("add-mr-btn")
+                                    .ghost()
+                                    .label("添加 MR")
+                                    .icon(Icon::new(IconName::Plus))
+                                    .on_click(cx.listener(|_, _, window, cx| {
+                                        open_form::<MrForm, _>(
+                                            None,
+                                            |this: &mut Self, _, _| this.reload(),
+                                            window,
+                                            cx,
+                                        );
+                                    })),
+                            )
+                            .child(
+                                Button::new("import-mr-btn")
+                                    .ghost()
+                                    .label("导入 MR")
+                                    .icon(Icon::new(IconName::Upload))
+                                    .on_click(cx.listener(|_, _, window, cx| {
+                                        import_from_excel::<MergeRequest, _>(
+                                            "导入 MR",
+                                            |this: &mut Self, _| this.reload(),
+                                            window,
+                                            cx,
+                                        );
+                                    })),
+                            )
+                            .child(
+                                Button::new("export-mr-btn")
+                                    .ghost()
+                                    .label("导出")
+                                    .icon(Icon::new(IconName::Download))
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.handle_export(window, cx);
                                     })),
                             ),
                     ),
@@ -239,7 +360,7 @@ impl Render for CodeHubView {
                     .flex_1()
                     .w_full()
                     .gap_2()
-                    .child(div().text_lg().font_semibold().child("MR 列表"))
+                    .child(self.render_list_header(&filtered_mrs, cx))
                     .child(
                         div()
                             .w_full()
@@ -290,6 +411,91 @@ impl CodeHubView {
             .child(div().text_2xl().font_bold().text_color(cx.theme().foreground).child(value))
     }
 
+    fn render_workload_table(
+        &self,
+        id: &'static str,
+        title: &'static str,
+        rows: Vec<(String, Workload)>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let cell = |width: f32| div().w(px(width)).flex_shrink_0().overflow_hidden();
+        let header = h_flex()
+            .gap_2()
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .child(cell(96.).child(title))
+            .child(cell(40.).child("MR"))
+            .child(cell(100.).child("增/删"))
+            .child(cell(72.).child("合入/待合"))
+            .child(div().flex_1().child("合入中位时长"));
+
+        v_flex()
+            .flex_1()
+            .p_3()
+            .gap_1()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_lg()
+            .child(header)
+            .child(v_flex().id(id).max_h(px(180.)).overflow_y_scroll().gap_1().text_sm().children(
+                rows.into_iter().map(|(name, workload)| {
+                    h_flex()
+                        .gap_2()
+                        .child(cell(96.).child(name))
+                        .child(cell(40.).child(workload.mr_count.to_string()))
+                        .child(
+                            cell(100.).child(format!(
+                                "+{} / -{}",
+                                workload.add_lines, workload.del_lines
+                            )),
+                        )
+                        .child(cell(72.).child(format!("{} / {}", workload.merged, workload.open)))
+                        .child(div().flex_1().text_color(cx.theme().muted_foreground).child(
+                            workload.median_time_to_merge.map_or("-".into(), format_duration),
+                        ))
+                }),
+            ))
+    }
+
+    fn render_list_header(&self, mrs: &[&MergeRequest], cx: &Context<Self>) -> impl IntoElement {
+        let ids: Vec<String> = mrs.iter().map(|mr| mr.id.clone()).collect();
+        let all_selected = !ids.is_empty() && ids.iter().all(|id| self.selected.contains(id));
+        // 只删除当前筛选结果中勾选的 MR，被搜索隐藏的勾选不受影响
+        let to_delete: Vec<String> =
+            ids.iter().filter(|id| self.selected.contains(*id)).cloned().collect();
+        h_flex()
+            .w_full()
+            .gap_3()
+            .items_center()
+            .child(div().text_lg().font_semibold().child("MR 列表"))
+            .child(
+                Checkbox::new("select-all-mrs")
+                    .label("全选")
+                    .checked(all_selected)
+                    .disabled(ids.is_empty())
+                    .on_click(cx.listener(move |this, checked: &bool, _, cx| {
+                        if *checked {
+                            this.selected.extend(ids.iter().cloned());
+                        } else {
+                            this.selected.retain(|id| !ids.contains(id));
+                        }
+                        cx.notify();
+                    })),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("delete-selected-mrs")
+                    .ghost()
+                    .small()
+                    .label(format!("删除所选 ({})", to_delete.len()))
+                    .icon(Icon::new(IconName::Trash))
+                    .disabled(to_delete.is_empty())
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.confirm_delete(to_delete.clone(), window, cx);
+                    })),
+            )
+    }
+
     fn render_mr_list(&self, mrs: Vec<&MergeRequest>, cx: &Context<Self>) -> impl IntoElement {
         v_flex().w_full().gap_2().children(mrs.into_iter().map(|mr| self.render_mr_item(mr, cx)))
     }
@@ -304,6 +510,19 @@ impl CodeHubView {
             .border_color(cx.theme().border)
             .rounded_md()
             .hover(|this| this.bg(cx.theme().background))
+            .child({
+                let id = mr.id.clone();
+                Checkbox::new(SharedString::from(format!("select-{}", mr.id)))
+                    .checked(self.selected.contains(&mr.id))
+                    .on_click(cx.listener(move |this, checked: &bool, _, cx| {
+                        if *checked {
+                            this.selected.insert(id.clone());
+                        } else {
+                            this.selected.remove(&id);
+                        }
+                        cx.notify();
+                    }))
+            })
             .child(
                 v_flex()
                     .flex_1()
@@ -322,8 +541,11 @@ impl CodeHubView {
                             .child(format!("#{}", mr.id))
                             .child("•")
                             .child(mr.author.clone())
+                            .children(
+                                (!mr.repository.is_empty()).then(|| format!("• {}", mr.repository)),
+                            )
                             .child("•")
-                            .child(mr.created_at.clone()),
+                            .child(mr.created_at.format("%Y-%m-%d %H:%M").to_string()),
                     ),
             )
             .child(
@@ -356,56 +578,36 @@ impl CodeHubView {
                         Button::new(format!("edit-{}", mr.id))
                             .small()
                             .ghost()
-                            .icon(Icon::new(IconName::PenTool)),
+                            .icon(Icon::new(IconName::PenTool))
+                            .on_click(cx.listener({
+                                let mr = mr.clone();
+                                move |_, _, window, cx| {
+                                    open_form::<MrForm, _>(
+                                        Some(mr.clone()),
+                                        |this: &mut Self, _, _| this.reload(),
+                                        window,
+                                        cx,
+                                    );
+                                }
+                            })),
                     )
                     .child(
                         Button::new(format!("delete-{}", mr.id))
                             .small()
                             .ghost()
-                            .icon(Icon::new(IconName::Trash)),
+                            .icon(Icon::new(IconName::Trash))
+                            .on_click(cx.listener({
+                                let id = mr.id.clone();
+                                move |this, _, window, cx| {
+                                    this.confirm_delete(vec![id.clone()], window, cx);
+                                }
+                            })),
                     ),
             )
     }
 
-    fn handle_open_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        // 这里可以实现文件选择对话框
-        // 由于gpui框架可能没有直接的文件对话框API
-        // 这里我们使用一个简单的示例，创建一个测试文件
-
-        let test_file_path = "test_mr_data.txt";
-
-        // 创建测试文件
-        match File::create(test_file_path) {
-            Ok(mut file) => {
-                writeln!(file, "MR 数据导出").unwrap();
-                writeln!(file, "=============").unwrap();
-
-                for mr in &self.mrs {
-                    writeln!(file, "ID: {}", mr.id).unwrap();
-                    writeln!(file, "标题: {}", mr.title).unwrap();
-                    writeln!(file, "作者: {}", mr.author).unwrap();
-                    writeln!(file, "创建时间: {}", mr.created_at).unwrap();
-                    writeln!(file, "新增行数: +{}", mr.add_lines).unwrap();
-                    writeln!(file, "删除行数: -{}", mr.del_lines).unwrap();
-                    writeln!(file, "状态: {}", mr.status).unwrap();
-                    writeln!(file, "--------------").unwrap();
-                }
-
-                // 显示成功通知
-                struct FileSaved;
-                let note = gpui_component::notification::Notification::new()
-                    .message(format!("文件已保存到: {}", test_file_path))
-                    .id::<FileSaved>();
-                window.push_notification(note, cx);
-            },
-            Err(e) => {
-                // 显示错误通知
-                struct FileError;
-                let note = gpui_component::notification::Notification::new()
-                    .message(format!("保存文件失败: {}", e))
-                    .id::<FileError>();
-                window.push_notification(note, cx);
-            },
-        }
+    fn handle_export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mrs = self.filtered_mrs(cx).into_iter().cloned().collect();
+        export_to_excel::<MergeRequest>(mrs, "MR 数据导出.xlsx", window, cx);
     }
 }
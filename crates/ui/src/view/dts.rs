@@ -1,89 +1,125 @@
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use gpui::{
     App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
-    Styled, Subscription, Window, div, rgb,
+    SharedString, Styled, Subscription, Window, div, px, rgb,
 };
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    ActiveTheme, Icon, IconName, Selectable, Sizable, StyledExt, WindowExt,
     button::*,
+    calendar::Date,
+    date_picker::{DatePicker, DatePickerEvent, DatePickerState},
     h_flex,
     input::{Input, InputEvent, InputState},
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
     v_flex,
 };
-use serde::{Deserialize, Serialize};
+use tools::{
+    Coded as _, DtsTool, Exportable as _, Issue, IssueFilter, IssueStats, IssueStatus, SavedFilter,
+    Severity, Store, SyncSource,
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Issue {
-    pub id: String,
-    pub title: String,
-    pub severity: String,
-    pub status: String,
-    pub created_at: DateTime<Utc>,
-    pub resolved_at: Option<DateTime<Utc>>,
-    pub assignee: String,
-}
+use crate::{
+    AppState,
+    utils::{format_duration, picked_range},
+    view::{
+        ChartCard, IssueDetailPanel, IssueForm, SyncEvent, SyncPanel, export_to_excel,
+        import_from_excel, open_form,
+    },
+};
 
 pub struct DtsView {
+    store: Store,
+    user: String,
     issues: Vec<Issue>,
-    filter_status: String,
+    sync_panel: Entity<SyncPanel>,
+    filter: IssueFilter,
     search_input: Entity<InputState>,
-    search_value: Option<String>,
+    assignee_input: Entity<InputState>,
+    created_picker: Entity<DatePickerState>,
+    resolved_picker: Entity<DatePickerState>,
+    saved_filters: Vec<SavedFilter<IssueFilter>>,
+    saved_filter_select: Entity<SelectState<Vec<SharedString>>>,
+    selected_filter: Option<String>,
+    filter_name_input: Entity<InputState>,
     _subscriptions: Vec<Subscription>,
 }
 
 impl DtsView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("搜索问题单..."));
+        let assignee_input = cx.new(|cx| InputState::new(window, cx).placeholder("责任人"));
+        let filter_name_input = cx.new(|cx| InputState::new(window, cx).placeholder("筛选名称"));
+        // Seeding an empty range puts the pickers in range mode, which is
+        // what `picked_range` reads.
+        let range_picker = |window: &mut Window, cx: &mut Context<Self>| {
+            cx.new(|cx| {
+                let mut picker = DatePickerState::new(window, cx);
+                picker.set_date(Date::Range(None, None), window, cx);
+                picker
+            })
+        };
+        let created_picker = range_picker(window, cx);
+        let resolved_picker = range_picker(window, cx);
+        let saved_filter_select = cx.new(|cx| SelectState::new(Vec::new(), None, window, cx));
 
-        // 添加测试数据
-        let issues = vec![
-            Issue {
-                id: "DTS001".to_string(),
-                title: "登录页面显示异常".to_string(),
-                severity: "严重".to_string(),
-                status: "提交".to_string(),
-                created_at: Utc::now(),
-                resolved_at: None,
-                assignee: "张三".to_string(),
-            },
-            Issue {
-                id: "DTS002".to_string(),
-                title: "数据库连接超时".to_string(),
-                severity: "严重".to_string(),
-                status: "提交".to_string(),
-                created_at: Utc::now(),
-                resolved_at: None,
-                assignee: "李四".to_string(),
-            },
-            Issue {
-                id: "DTS003".to_string(),
-                title: "按钮样式不对齐".to_string(),
-                severity: "一般".to_string(),
-                status: "提交".to_string(),
-                created_at: Utc::now(),
-                resolved_at: Some(Utc::now()),
-                assignee: "王五".to_string(),
-            },
-            Issue {
-                id: "DTS004".to_string(),
-                title: "文本输入框光标位置错误".to_string(),
-                severity: "轻微".to_string(),
-                status: "提交".to_string(),
-                created_at: Utc::now(),
-                resolved_at: None,
-                assignee: "赵六".to_string(),
-            },
-        ];
+        let sync_panel = SyncPanel::view(SyncSource::Dts, cx);
 
-        let subscriptions = vec![cx.subscribe_in(&search_input, window, Self::on_input_event)];
+        let subscriptions = vec![
+            cx.subscribe_in(&search_input, window, Self::on_input_event),
+            cx.subscribe_in(&assignee_input, window, Self::on_input_event),
+            cx.subscribe(&created_picker, |this, _, ev, cx| match ev {
+                DatePickerEvent::Change(date) => {
+                    this.filter.created = picked_range(date);
+                    cx.notify();
+                },
+            }),
+            cx.subscribe(&resolved_picker, |this, _, ev, cx| match ev {
+                DatePickerEvent::Change(date) => {
+                    this.filter.resolved = picked_range(date);
+                    cx.notify();
+                },
+            }),
+            cx.subscribe_in(
+                &saved_filter_select,
+                window,
+                |this, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    let SelectEvent::Confirm(name) = event;
+                    let saved = name.as_ref().and_then(|name| {
+                        this.saved_filters.iter().find(|saved| saved.name == name.as_ref())
+                    });
+                    if let Some(saved) = saved {
+                        this.selected_filter = Some(saved.name.clone());
+                        this.apply_filter(saved.filter.clone(), window, cx);
+                    }
+                },
+            ),
+            cx.subscribe(&sync_panel, |this, _, _: &SyncEvent, cx| {
+                this.reload();
+                cx.notify();
+            }),
+        ];
 
-        Self {
-            issues,
-            filter_status: "all".to_string(),
+        let state = AppState::global(cx);
+        let mut this = Self {
+            store: state.store.clone(),
+            user: state.user.clone(),
+            issues: Vec::new(),
+            sync_panel,
+            filter: IssueFilter::default(),
             search_input,
-            search_value: None,
+            assignee_input,
+            created_picker,
+            resolved_picker,
+            saved_filters: Vec::new(),
+            saved_filter_select,
+            selected_filter: None,
+            filter_name_input,
             _subscriptions: subscriptions,
-        }
+        };
+        this.reload();
+        this.reload_saved_filters(window, cx);
+        this
     }
 
     pub fn view(_window: &mut Window, cx: &mut App) -> Entity<Self> {
@@ -99,40 +135,99 @@ impl DtsView {
     ) {
         match event {
             InputEvent::Change => {
-                let text = state.read(cx).value();
+                let text = state.read(cx).value().to_string();
                 if state == &self.search_input {
-                    self.search_value = Some(text.into());
+                    self.filter.text = text;
+                } else if state == &self.assignee_input {
+                    self.filter.assignee = text;
                 }
+                cx.notify();
             },
             _ => {},
         };
     }
 
-    fn calculate_stats(&self) -> (usize, usize, usize) {
-        let submitted = self.issues.iter().filter(|i| i.status == "提交").count();
-        let resolved = self.issues.iter().filter(|i| i.resolved_at.is_some()).count();
-        let total = self.issues.len();
-        (submitted, resolved, total)
+    fn reload(&mut self) {
+        self.issues = self.store.issues().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load issues: {:?}", err);
+            Vec::new()
+        });
+    }
+
+    fn reload_saved_filters(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.saved_filters =
+            self.store.saved_filters::<Issue, _>(&self.user).unwrap_or_else(|err| {
+                tracing::error!("Failed to load saved issue filters: {:?}", err);
+                Vec::new()
+            });
+        let names = self.saved_filters.iter().map(|saved| saved.name.clone().into()).collect();
+        self.saved_filter_select.update(cx, |select, cx| select.set_items(names, window, cx));
+    }
+
+    /// 将筛选条件同步到各输入控件
+    fn apply_filter(&mut self, filter: IssueFilter, window: &mut Window, cx: &mut Context<Self>) {
+        let range = |range: Option<_>| match range {
+            Some((start, end)) => Date::Range(Some(start), Some(end)),
+            None => Date::Range(None, None),
+        };
+        self.search_input.update(cx, |input, cx| input.set_value(filter.text.clone(), window, cx));
+        self.assignee_input
+            .update(cx, |input, cx| input.set_value(filter.assignee.clone(), window, cx));
+        self.created_picker
+            .update(cx, |picker, cx| picker.set_date(range(filter.created), window, cx));
+        self.resolved_picker
+            .update(cx, |picker, cx| picker.set_date(range(filter.resolved), window, cx));
+        self.filter = filter;
+        cx.notify();
+    }
+
+    fn save_filter(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct SaveFilter;
+        let name = self.filter_name_input.read(cx).value().trim().to_string();
+        let message = if name.is_empty() {
+            "请先填写筛选名称".to_string()
+        } else {
+            match self.store.save_filter::<Issue, _>(&self.user, &name, &self.filter) {
+                Ok(()) => {
+                    self.selected_filter = Some(name.clone());
+                    self.reload_saved_filters(window, cx);
+                    format!("已保存筛选: {}", name)
+                },
+                Err(err) => {
+                    tracing::error!("Failed to save issue filter {}: {:?}", name, err);
+                    format!("保存筛选失败: {}", err)
+                },
+            }
+        };
+        window.push_notification(Notification::new().message(message).id::<SaveFilter>(), cx);
+    }
+
+    fn delete_filter(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(name) = self.selected_filter.take() else {
+            return;
+        };
+        if let Err(err) = self.store.delete_filter::<Issue>(&self.user, &name) {
+            tracing::error!("Failed to delete issue filter {}: {:?}", name, err);
+        }
+        self.reload_saved_filters(window, cx);
+        cx.notify();
+    }
+
+    /// 按筛选栏的全部条件过滤问题单
+    fn filtered_issues(&self) -> Vec<&Issue> {
+        self.issues.iter().filter(|issue| self.filter.matches(issue)).collect()
+    }
+
+    fn handle_export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let issues = self.filtered_issues().into_iter().cloned().collect();
+        export_to_excel::<Issue>(issues, "问题单导出.xlsx", window, cx);
     }
 }
 
 impl Render for DtsView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let search_query = self.search_input.read(cx).value().trim().to_lowercase();
-
-        // 根据搜索查询过滤问题单
-        let filtered_issues: Vec<&Issue> = self
-            .issues
-            .iter()
-            .filter(|issue: &&Issue| {
-                search_query.is_empty()
-                    || issue.title.to_lowercase().contains(&search_query)
-                    || issue.assignee.to_lowercase().contains(&search_query)
-                    || issue.id.to_lowercase().contains(&search_query)
-            })
-            .collect();
-
-        let (submitted, resolved, total) = self.calculate_stats_filtered(&filtered_issues);
+        let filtered_issues = self.filtered_issues();
+        let stats = DtsTool::stats(&filtered_issues, Utc::now());
 
         v_flex()
             .size_full()
@@ -150,22 +245,20 @@ impl Render for DtsView {
                     ),
                 ),
             )
+            .child(self.sync_panel.clone())
             .child(
                 // 统计卡片区
-                h_flex()
-                    .w_full()
-                    .gap_4()
-                    .child(self.stat_card("总计问题单", &total.to_string(), cx))
-                    .child(self.stat_card("已提交", &submitted.to_string(), cx))
-                    .child(self.stat_card("已回归", &resolved.to_string(), cx))
-                    .child(self.stat_card(
-                        "回归率",
-                        &format!(
-                            "{:.1}%",
-                            if total > 0 { (resolved as f64 / total as f64) * 100.0 } else { 0.0 }
-                        ),
-                        cx,
-                    )),
+                h_flex().w_full().gap_4().children(
+                    Issue::summary(&filtered_issues)
+                        .into_iter()
+                        .map(|(label, value)| self.stat_card(label, &value.to_string(), cx)),
+                ),
+            )
+            .child(
+                // 解决时长与账龄
+                h_flex().w_full().gap_4().items_start().child(self.render_mttr(&stats, cx)).child(
+                    div().flex_1().child(ChartCard::new("issue-aging", stats.aging_chart())),
+                ),
             )
             .child(
                 // 操作区
@@ -184,10 +277,49 @@ impl Render for DtsView {
                         Button::new("add-issue-btn")
                             .primary()
                             .label("添加问题单")
-                            .icon(Icon::new(IconName::Plus)),
+                            .icon(Icon::new(IconName::Plus))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                open_form::<IssueForm, _>(
+                                    None,
+                                    |this: &mut Self, _, _| this.reload(),
+                                    window,
+                                    cx,
+                                );
+                            })),
+                    )
+                    .child(
+                        Button::new("import-issue-btn")
+                            .ghost()
+                            .label("导入问题单")
+                            .icon(Icon::new(IconName::Upload))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                import_from_excel::<Issue, _>(
+                                    "导入问题单",
+                                    |this: &mut Self, _| this.reload(),
+                                    window,
+                                    cx,
+                                );
+                            })),
+                    )
+                    .child(
+                        Button::new("export-issue-btn")
+                            .ghost()
+                            .label("导出")
+                            .icon(Icon::new(IconName::Download))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.handle_export(window, cx);
+                            })),
                     )
-                    .child(Button::new("refresh-btn").ghost().icon(Icon::new(IconName::RefreshCw))),
+                    .child(
+                        Button::new("refresh-btn")
+                            .ghost()
+                            .icon(Icon::new(IconName::RefreshCw))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.sync_panel.update(cx, |panel, cx| panel.sync_now(cx));
+                            })),
+                    ),
             )
+            .child(self.render_filter_bar(cx))
             .child(
                 // 问题单列表
                 v_flex()
@@ -243,11 +375,127 @@ impl DtsView {
             .child(div().text_2xl().font_bold().text_color(cx.theme().foreground).child(value))
     }
 
-    fn calculate_stats_filtered(&self, issues: &[&Issue]) -> (usize, usize, usize) {
-        let submitted = issues.iter().filter(|i| i.status == "提交").count();
-        let resolved = issues.iter().filter(|i| i.resolved_at.is_some()).count();
-        let total = issues.len();
-        (submitted, resolved, total)
+    fn render_filter_bar(&self, cx: &Context<Self>) -> impl IntoElement {
+        let status_chips = IssueStatus::ALL.iter().map(|&status| {
+            let selected = self.filter.statuses.contains(&status);
+            Button::new(SharedString::from(format!("filter-status-{:?}", status)))
+                .small()
+                .ghost()
+                .selected(selected)
+                .label(status.label())
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    IssueFilter::toggle(&mut this.filter.statuses, status);
+                    cx.notify();
+                }))
+        });
+        let severity_chips = Severity::ALL.iter().map(|&severity| {
+            let selected = self.filter.severities.contains(&severity);
+            Button::new(SharedString::from(format!("filter-severity-{:?}", severity)))
+                .small()
+                .ghost()
+                .selected(selected)
+                .label(severity.label())
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    IssueFilter::toggle(&mut this.filter.severities, severity);
+                    cx.notify();
+                }))
+        });
+        let label = |text: &'static str| {
+            div().w(px(64.)).text_sm().text_color(cx.theme().muted_foreground).child(text)
+        };
+
+        v_flex()
+            .w_full()
+            .p_3()
+            .gap_2()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_lg()
+            .child(h_flex().gap_1().items_center().child(label("状态")).children(status_chips))
+            .child(
+                h_flex().gap_1().items_center().child(label("严重程度")).children(severity_chips),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(label("责任人"))
+                    .child(
+                        div().w(px(160.)).child(Input::new(&self.assignee_input).cleanable(true)),
+                    )
+                    .child(div().text_sm().child("提交日期"))
+                    .child(DatePicker::new(&self.created_picker).cleanable(true))
+                    .child(div().text_sm().child("解决日期"))
+                    .child(DatePicker::new(&self.resolved_picker).cleanable(true))
+                    .child(
+                        Button::new("clear-issue-filter-btn")
+                            .ghost()
+                            .small()
+                            .label("清空")
+                            .disabled(self.filter.is_empty())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.selected_filter = None;
+                                this.apply_filter(IssueFilter::default(), window, cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(label("保存的筛选"))
+                    .child(
+                        div()
+                            .w(px(200.))
+                            .child(Select::new(&self.saved_filter_select).placeholder("选择筛选")),
+                    )
+                    .child(
+                        Button::new("delete-issue-filter-btn")
+                            .ghost()
+                            .small()
+                            .label("删除")
+                            .icon(Icon::new(IconName::Trash))
+                            .disabled(self.selected_filter.is_none())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.delete_filter(window, cx);
+                            })),
+                    )
+                    .child(div().w(px(160.)).child(Input::new(&self.filter_name_input)))
+                    .child(
+                        Button::new("save-issue-filter-btn")
+                            .small()
+                            .label("保存筛选")
+                            .icon(Icon::new(IconName::Plus))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.save_filter(window, cx);
+                            })),
+                    ),
+            )
+    }
+
+    fn render_mttr(&self, stats: &IssueStats, cx: &Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(px(320.))
+            .p_4()
+            .gap_2()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_lg()
+            .child(div().font_semibold().child("平均解决时长"))
+            .children(stats.mttr.iter().map(|mttr| {
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .text_sm()
+                    .child(div().w(px(64.)).child(self.severity_badge(mttr.severity, cx)))
+                    .child(div().flex_1().child(mttr.mean.map_or("-".to_string(), format_duration)))
+                    .child(
+                        div()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format!("{} 个已解决", mttr.resolved)),
+                    )
+            }))
     }
 
     fn render_issue_list(&self, issues: Vec<&Issue>, cx: &Context<Self>) -> impl IntoElement {
@@ -281,7 +529,7 @@ impl DtsView {
                                     .text_color(cx.theme().foreground)
                                     .child(issue.title.clone()),
                             )
-                            .child(self.severity_badge(&issue.severity, cx)),
+                            .child(self.severity_badge(issue.severity, cx)),
                     )
                     .child(
                         h_flex()
@@ -307,24 +555,42 @@ impl DtsView {
                         rgb(0xf59e0b)
                     })
                     .text_sm()
-                    .child(issue.status.clone()),
+                    .child(issue.status.label()),
+            )
+            .child(
+                Button::new(format!("edit-{}", issue.id)).small().ghost().label("编辑").on_click(
+                    cx.listener({
+                        let issue = issue.clone();
+                        move |_, _, window, cx| {
+                            open_form::<IssueForm, _>(
+                                Some(issue.clone()),
+                                |this: &mut Self, _, _| this.reload(),
+                                window,
+                                cx,
+                            );
+                        }
+                    }),
+                ),
             )
             .child(
                 Button::new(format!("view-{}", issue.id))
                     .small()
                     .ghost()
-                    .icon(Icon::new(IconName::ExternalLink)),
+                    .icon(Icon::new(IconName::ExternalLink))
+                    .on_click({
+                        let issue_id = issue.id.clone();
+                        move |_, window, cx| IssueDetailPanel::open(issue_id.clone(), window, cx)
+                    }),
             )
     }
 
-    fn severity_badge(&self, severity: &str, _cx: &Context<Self>) -> impl IntoElement {
+    fn severity_badge(&self, severity: Severity, _cx: &Context<Self>) -> impl IntoElement {
         let (bg_color, text_color) = match severity {
-            "严重" => (rgb(0xef4444), rgb(0xef4444)),
-            "一般" => (rgb(0xf59e0b), rgb(0xf59e0b)),
-            "轻微" => (rgb(0x3b82f6), rgb(0x3b82f6)),
-            _ => (rgb(0x6b7280), rgb(0x6b7280)),
+            Severity::Fatal | Severity::Serious => (rgb(0xef4444), rgb(0xef4444)),
+            Severity::Normal => (rgb(0xf59e0b), rgb(0xf59e0b)),
+            Severity::Minor => (rgb(0x3b82f6), rgb(0x3b82f6)),
+            Severity::Suggestion => (rgb(0x6b7280), rgb(0x6b7280)),
         };
-        let severity = severity.to_string();
 
         div()
             .px_2()
@@ -333,6 +599,6 @@ impl DtsView {
             .bg(bg_color)
             .text_color(text_color)
             .text_xs()
-            .child(severity)
+            .child(severity.label())
     }
 }
@@ -1,31 +1,37 @@
+use std::{ops::Range, path::PathBuf};
+
 use gpui::{
-    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, Styled, Subscription,
-    Window, div,
+    App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement,
+    PathPromptOptions, Pixels, Render, StatefulInteractiveElement, Styled, Subscription, Task,
+    Window, div, px, uniform_list,
 };
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    ActiveTheme, Icon, IconName, Sizable, StyledExt, WindowExt,
     button::*,
     h_flex,
     input::{Input, InputEvent, InputState},
+    notification::Notification,
     v_flex,
 };
-use serde::{Deserialize, Serialize};
+use tools::{
+    ExcelData, ExcelError, ExcelTool, Issue, MergeRequest, Record as _, Requirement, Sheet, Store,
+    VirtualEnv, Workbook, format_file_size,
+};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExcelData {
-    pub id: String,
-    pub name: String,
-    pub file_type: String,
-    pub size: String,
-    pub created_at: String,
-    pub description: String,
-}
+use crate::{AppState, view::download_template};
+
+const ROW_NUMBER_WIDTH: Pixels = px(56.);
+const COLUMN_WIDTH: Pixels = px(160.);
 
 pub struct ExcelView {
-    file_path: String,
+    store: Store,
     data_list: Vec<ExcelData>,
+    workbook: Option<Workbook>,
+    sheet: Option<Sheet>,
+    loading: bool,
     search_input: Entity<InputState>,
     search_value: Option<String>,
+    _load_task: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -38,51 +44,30 @@ impl ExcelView {
         let search_input =
             cx.new(|cx| InputState::new(window, cx).placeholder("搜索 Excel 文件..."));
 
-        // 添加测试数据
-        let data_list = vec![
-            ExcelData {
-                id: "EXCEL001".to_string(),
-                name: "Q1销售数据".to_string(),
-                file_type: "xlsx".to_string(),
-                size: "2.5 MB".to_string(),
-                created_at: "2025-01-20".to_string(),
-                description: "第一季度销售统计".to_string(),
-            },
-            ExcelData {
-                id: "EXCEL002".to_string(),
-                name: "员工信息表".to_string(),
-                file_type: "xlsx".to_string(),
-                size: "1.2 MB".to_string(),
-                created_at: "2025-01-22".to_string(),
-                description: "公司员工基本信息".to_string(),
-            },
-            ExcelData {
-                id: "EXCEL003".to_string(),
-                name: "项目预算".to_string(),
-                file_type: "xls".to_string(),
-                size: "0.8 MB".to_string(),
-                created_at: "2025-01-25".to_string(),
-                description: "2025年项目预算表".to_string(),
-            },
-            ExcelData {
-                id: "EXCEL004".to_string(),
-                name: "库存清单".to_string(),
-                file_type: "xlsx".to_string(),
-                size: "3.1 MB".to_string(),
-                created_at: "2025-01-26".to_string(),
-                description: "仓库库存统计".to_string(),
-            },
-        ];
-
         let subscriptions = vec![cx.subscribe_in(&search_input, window, Self::on_input_event)];
 
-        Self {
-            file_path: String::new(),
-            data_list,
+        let mut this = Self {
+            store: AppState::global(cx).store.clone(),
+            data_list: Vec::new(),
+            workbook: None,
+            sheet: None,
+            loading: false,
             search_input,
             search_value: None,
+            _load_task: None,
             _subscriptions: subscriptions,
-        }
+        };
+        this.reload();
+        this
+    }
+
+    /// Load the recently opened files, most recently modified first.
+    fn reload(&mut self) {
+        self.data_list = self.store.excel_files().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load excel files: {:?}", err);
+            Vec::new()
+        });
+        self.data_list.sort_by(|a, b| b.created_at.cmp(&a.created_at));
     }
 
     fn on_input_event(
@@ -102,6 +87,93 @@ impl ExcelView {
             _ => {},
         };
     }
+
+    fn prompt_open_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("选择 Excel 文件".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = paths.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            _ = this.update_in(cx, |this, window, cx| this.open_file(path, window, cx));
+        })
+        .detach();
+    }
+
+    /// Read the workbook and its first sheet off the main thread.
+    fn open_file(&mut self, path: PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        let task = cx.background_spawn(async move {
+            let workbook = ExcelTool::open(&path)?;
+            let sheet = match workbook.sheet_names.first() {
+                Some(name) => Some(workbook.read_sheet(name)?),
+                None => None,
+            };
+            Ok::<_, ExcelError>((workbook, sheet))
+        });
+        self.load(task, window, cx);
+    }
+
+    fn select_sheet(&mut self, name: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(workbook) = self.workbook.clone() else {
+            return;
+        };
+        let task = cx.background_spawn(async move {
+            let sheet = workbook.read_sheet(&name)?;
+            Ok::<_, ExcelError>((workbook, Some(sheet)))
+        });
+        self.load(task, window, cx);
+    }
+
+    fn load(
+        &mut self,
+        task: Task<Result<(Workbook, Option<Sheet>), ExcelError>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.loading = true;
+        cx.notify();
+
+        self._load_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = task.await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.loading = false;
+                match result {
+                    Ok((workbook, sheet)) => {
+                        if let Err(err) =
+                            this.store.excel_files().upsert(&ExcelData::from(&workbook))
+                        {
+                            tracing::error!("Failed to remember excel file: {:?}", err);
+                        }
+                        this.reload();
+                        this.workbook = Some(workbook);
+                        this.sheet = sheet;
+                    },
+                    Err(err) => {
+                        struct ExcelOpenError;
+                        let note = Notification::new()
+                            .message(format!("读取 Excel 失败: {}", err))
+                            .id::<ExcelOpenError>();
+                        window.push_notification(note, cx);
+                    },
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn close_file(&mut self, cx: &mut Context<Self>) {
+        self.workbook = None;
+        self.sheet = None;
+        cx.notify();
+    }
 }
 
 impl Render for ExcelView {
@@ -113,10 +185,8 @@ impl Render for ExcelView {
             .data_list
             .iter()
             .filter(|data: &&ExcelData| {
-                search_query.is_empty()
-                    || data.name.to_lowercase().contains(&search_query)
+                data.matches(&search_query)
                     || data.description.to_lowercase().contains(&search_query)
-                    || data.id.to_lowercase().contains(&search_query)
             })
             .collect();
 
@@ -157,7 +227,11 @@ impl Render for ExcelView {
                         Button::new("read-excel-btn")
                             .primary()
                             .label("选择文件读取")
-                            .icon(Icon::new(IconName::FolderOpen)),
+                            .icon(Icon::new(IconName::FolderOpen))
+                            .loading(self.loading)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.prompt_open_file(window, cx);
+                            })),
                     )
                     .child(
                         Button::new("write-excel-btn")
@@ -167,12 +241,50 @@ impl Render for ExcelView {
                     ),
             )
             .child(
-                // 数据列表
-                v_flex()
+                // 模板下载
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div().text_sm().text_color(cx.theme().muted_foreground).child("模板下载"),
+                    )
+                    .child(
+                        Button::new("requirement-template-btn")
+                            .small()
+                            .ghost()
+                            .label("需求模板")
+                            .on_click(|_, window, cx| download_template::<Requirement>(window, cx)),
+                    )
+                    .child(
+                        Button::new("issue-template-btn")
+                            .small()
+                            .ghost()
+                            .label("问题单模板")
+                            .on_click(|_, window, cx| download_template::<Issue>(window, cx)),
+                    )
+                    .child(
+                        Button::new("mr-template-btn").small().ghost().label("MR 模板").on_click(
+                            |_, window, cx| download_template::<MergeRequest>(window, cx),
+                        ),
+                    )
+                    .child(
+                        Button::new("env-template-btn")
+                            .small()
+                            .ghost()
+                            .label("虚拟环境模板")
+                            .on_click(|_, window, cx| download_template::<VirtualEnv>(window, cx)),
+                    ),
+            )
+            .child(match (&self.workbook, &self.sheet) {
+                (Some(workbook), sheet) => {
+                    self.render_preview(workbook, sheet.as_ref(), cx).into_any_element()
+                },
+                (None, _) => v_flex()
                     .flex_1()
                     .w_full()
                     .gap_2()
-                    .child(div().text_lg().font_semibold().child("Excel 文件列表"))
+                    .child(div().text_lg().font_semibold().child("最近打开的文件"))
                     .child(
                         div()
                             .w_full()
@@ -202,8 +314,9 @@ impl Render for ExcelView {
                             } else {
                                 div().child(self.render_data_list(filtered_data, cx))
                             }),
-                    ),
-            )
+                    )
+                    .into_any_element(),
+            })
     }
 }
 impl ExcelView {
@@ -212,6 +325,7 @@ impl ExcelView {
     }
 
     fn render_data_item(&self, data: &ExcelData, cx: &Context<Self>) -> impl IntoElement {
+        let path = PathBuf::from(&data.id);
         h_flex()
             .w_full()
             .p_3()
@@ -235,7 +349,7 @@ impl ExcelView {
                             .gap_2()
                             .text_sm()
                             .text_color(cx.theme().muted_foreground)
-                            .child(format!("#{}", data.id))
+                            .child(data.id.clone())
                             .child("•")
                             .child(data.description.clone())
                             .child("•")
@@ -263,20 +377,169 @@ impl ExcelView {
                     ),
             )
             .child(
+                h_flex().gap_1().child(
+                    Button::new(format!("view-{}", data.id))
+                        .small()
+                        .ghost()
+                        .icon(Icon::new(IconName::Eye))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.open_file(path.clone(), window, cx);
+                        })),
+                ),
+            )
+    }
+
+    fn render_preview(
+        &self,
+        workbook: &Workbook,
+        sheet: Option<&Sheet>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let modified = workbook
+            .modified
+            .map(|modified| modified.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+
+        v_flex()
+            .flex_1()
+            .w_full()
+            .gap_2()
+            .child(
+                // 文件信息
                 h_flex()
-                    .gap_1()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(div().text_lg().font_semibold().child(workbook.file_name()))
                     .child(
-                        Button::new(format!("view-{}", data.id))
-                            .small()
-                            .ghost()
-                            .icon(Icon::new(IconName::Eye)),
+                        h_flex()
+                            .flex_1()
+                            .gap_2()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(workbook.file_type())
+                            .child("•")
+                            .child(format_file_size(workbook.size))
+                            .child("•")
+                            .child(format!("修改于 {}", modified))
+                            .children(sheet.map(|sheet| {
+                                format!("• {} 行 × {} 列", sheet.rows.len(), sheet.headers.len())
+                            })),
                     )
                     .child(
-                        Button::new(format!("download-{}", data.id))
+                        Button::new("close-excel-btn")
                             .small()
                             .ghost()
-                            .icon(Icon::new(IconName::Download)),
+                            .icon(Icon::new(IconName::Close))
+                            .on_click(cx.listener(|this, _, _, cx| this.close_file(cx))),
                     ),
             )
+            .child(
+                // 工作表切换
+                h_flex().gap_1().flex_wrap().children(workbook.sheet_names.iter().enumerate().map(
+                    |(ix, name)| {
+                        let selected = sheet.is_some_and(|sheet| &sheet.name == name);
+                        let button = Button::new(("sheet-tab", ix)).small().label(name.clone());
+                        let button = if selected { button.primary() } else { button.ghost() };
+                        let name = name.clone();
+                        button.on_click(cx.listener(move |this, _, window, cx| {
+                            this.select_sheet(name.clone(), window, cx);
+                        }))
+                    },
+                )),
+            )
+            .child(
+                div()
+                    .id("sheet-preview")
+                    .flex_1()
+                    .w_full()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded_md()
+                    .overflow_x_scroll()
+                    .child(match sheet {
+                        Some(sheet) => self.render_sheet(sheet, cx).into_any_element(),
+                        None => div()
+                            .p_4()
+                            .text_color(cx.theme().muted_foreground)
+                            .child("该文件没有工作表")
+                            .into_any_element(),
+                    }),
+            )
+    }
+
+    /// Header and column types stay fixed; only the visible data rows are
+    /// laid out, so large sheets scroll smoothly.
+    fn render_sheet(&self, sheet: &Sheet, cx: &Context<Self>) -> impl IntoElement {
+        let width = ROW_NUMBER_WIDTH + COLUMN_WIDTH * sheet.headers.len() as f32;
+
+        v_flex()
+            .h_full()
+            .w(width)
+            .child(
+                h_flex()
+                    .bg(cx.theme().muted)
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .font_semibold()
+                    .child(div().w(ROW_NUMBER_WIDTH).flex_shrink_0())
+                    .children(sheet.headers.iter().zip(&sheet.column_types).map(|(header, ty)| {
+                        v_flex()
+                            .w(COLUMN_WIDTH)
+                            .flex_shrink_0()
+                            .px_2()
+                            .py_1()
+                            .child(div().truncate().child(header.clone()))
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(ty.label()),
+                            )
+                    })),
+            )
+            .child(
+                uniform_list(
+                    "sheet-rows",
+                    sheet.rows.len(),
+                    cx.processor(|this, range: Range<usize>, _window, cx| {
+                        let Some(sheet) = &this.sheet else {
+                            return Vec::new();
+                        };
+                        range
+                            .map(|ix| {
+                                h_flex()
+                                    .border_b_1()
+                                    .border_color(cx.theme().border)
+                                    .text_sm()
+                                    .child(
+                                        div()
+                                            .w(ROW_NUMBER_WIDTH)
+                                            .flex_shrink_0()
+                                            .px_2()
+                                            .py_1()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child((sheet.first_row + ix).to_string()),
+                                    )
+                                    .children((0..sheet.headers.len()).map(|column| {
+                                        div()
+                                            .w(COLUMN_WIDTH)
+                                            .flex_shrink_0()
+                                            .px_2()
+                                            .py_1()
+                                            .truncate()
+                                            .child(
+                                                sheet.rows[ix]
+                                                    .get(column)
+                                                    .map(ToString::to_string)
+                                                    .unwrap_or_default(),
+                                            )
+                                    }))
+                            })
+                            .collect()
+                    }),
+                )
+                .flex_1(),
+            )
     }
 }
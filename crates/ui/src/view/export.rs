@@ -0,0 +1,76 @@
+use std::{fmt, path::Path};
+
+use gpui::{App, AppContext as _, Window};
+use gpui_component::{WindowExt as _, notification::Notification};
+use tools::{Chart, ChartTool, ExcelTool, Exportable, Template};
+
+/// Write `records` to an .xlsx file chosen by the user.
+pub(crate) fn export_to_excel<T>(
+    records: Vec<T>,
+    file_name: &str,
+    window: &mut Window,
+    cx: &mut App,
+) where
+    T: Exportable + Send + 'static,
+{
+    save_file(
+        file_name,
+        move |path| {
+            let records: Vec<&T> = records.iter().collect();
+            ExcelTool::export(path, &records)
+        },
+        window,
+        cx,
+    );
+}
+
+/// Write the import template of `T` to a file chosen by the user.
+pub(crate) fn download_template<T: Template>(window: &mut Window, cx: &mut App) {
+    let file_name = format!("{}导入模板.xlsx", T::TEMPLATE_NAME);
+    save_file(&file_name, |path| ExcelTool::write_template::<T>(path), window, cx);
+}
+
+/// Save `chart` as an image; `extension` picks the format.
+pub(crate) fn export_chart(chart: Chart, extension: &str, window: &mut Window, cx: &mut App) {
+    let file_name = format!("{}.{}", chart.title, extension);
+    save_file(&file_name, move |path| ChartTool::export(&chart, path), window, cx);
+}
+
+/// Ask where to save, then run `write` in the background and report the
+/// outcome as a notification.
+fn save_file<E: fmt::Debug + fmt::Display + Send + 'static>(
+    file_name: &str,
+    write: impl FnOnce(&Path) -> Result<(), E> + Send + 'static,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let directory = std::env::current_dir().unwrap_or_default();
+    let path = cx.prompt_for_new_path(&directory, Some(file_name));
+
+    window
+        .spawn(cx, async move |cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+            let result = cx
+                .background_spawn({
+                    let path = path.clone();
+                    async move { write(&path) }
+                })
+                .await;
+
+            _ = cx.update(|window, cx| {
+                struct ExportResult;
+                let message = match result {
+                    Ok(()) => format!("文件已保存到: {}", path.display()),
+                    Err(err) => {
+                        tracing::error!("Failed to write {}: {:?}", path.display(), err);
+                        format!("导出失败: {}", err)
+                    },
+                };
+                let note = Notification::new().message(message).id::<ExportResult>();
+                window.push_notification(note, cx);
+            });
+        })
+        .detach();
+}
@@ -0,0 +1,175 @@
+use std::fmt::Display;
+
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Window, div, prelude::FluentBuilder, px, rgb,
+};
+use gpui_component::{
+    ActiveTheme, IndexPath, Sizable, WindowExt,
+    button::*,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    select::SelectState,
+    v_flex,
+};
+use tools::{Coded, FormError, Record};
+
+use crate::AppState;
+
+/// How many known people are suggested below the input.
+const MAX_SUGGESTIONS: usize = 6;
+
+/// A name input that suggests the people the store knows of while typing.
+pub struct PersonInput {
+    input: Entity<InputState>,
+    people: Vec<String>,
+    _subscription: Subscription,
+}
+
+impl PersonInput {
+    pub fn new(
+        placeholder: &'static str,
+        value: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder(placeholder);
+            input.set_value(value, window, cx);
+            input
+        });
+        let subscription = cx.subscribe(&input, |_, _, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        });
+        let people = AppState::global(cx).store.people().unwrap_or_else(|err| {
+            tracing::error!("Failed to load people: {:?}", err);
+            Vec::new()
+        });
+        Self { input, people, _subscription: subscription }
+    }
+
+    pub fn value(&self, cx: &App) -> String {
+        self.input.read(cx).value().to_string()
+    }
+
+    fn suggestions(&self, cx: &App) -> Vec<String> {
+        let value = self.value(cx);
+        let query = value.trim().to_lowercase();
+        if query.is_empty() || self.people.contains(&value) {
+            return Vec::new();
+        }
+        self.people
+            .iter()
+            .filter(|person| person.to_lowercase().contains(&query))
+            .take(MAX_SUGGESTIONS)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Render for PersonInput {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let suggestions = self.suggestions(cx);
+        v_flex().gap_1().child(Input::new(&self.input)).when(!suggestions.is_empty(), |this| {
+            this.child(h_flex().gap_1().flex_wrap().children(suggestions.into_iter().map(
+                |person| {
+                    Button::new(SharedString::from(format!("person-{}", person)))
+                        .ghost()
+                        .xsmall()
+                        .label(person.clone())
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.input.update(cx, |input, cx| {
+                                input.set_value(person.clone(), window, cx)
+                            });
+                            cx.notify();
+                        }))
+                },
+            )))
+        })
+    }
+}
+
+/// A labelled form row with a required marker and the field's validation
+/// error, if any, below the input.
+pub fn form_field(
+    label: &'static str,
+    required: bool,
+    error: Option<String>,
+    input: impl IntoElement,
+    cx: &App,
+) -> impl IntoElement {
+    v_flex()
+        .w_full()
+        .gap_1()
+        .text_color(cx.theme().foreground)
+        .child(
+            h_flex()
+                .gap_1()
+                .text_sm()
+                .child(label)
+                .when(required, |this| this.child(div().text_color(rgb(0xef4444)).child("*"))),
+        )
+        .child(input)
+        .when_some(error, |this, error| {
+            this.child(div().text_xs().text_color(rgb(0xef4444)).child(error))
+        })
+}
+
+/// A dropdown of the labels of `values`, with `selected` preselected.
+pub fn status_select<S: Coded, V>(
+    values: &[S],
+    selected: Option<S>,
+    window: &mut Window,
+    cx: &mut Context<V>,
+) -> Entity<SelectState<Vec<SharedString>>> {
+    let labels = values.iter().map(|value| SharedString::from(value.label())).collect();
+    let selected = selected
+        .and_then(|selected| values.iter().position(|value| *value == selected))
+        .map(IndexPath::new);
+    cx.new(|cx| SelectState::new(labels, selected, window, cx))
+}
+
+/// The validation error `errors` holds for the field labelled `label`.
+pub fn field_error<E: FormError + Display>(errors: &[E], label: &'static str) -> Option<String> {
+    errors.iter().find(|error| error.field() == label).map(ToString::to_string)
+}
+
+/// Emitted by a [`RecordForm`] once its record is saved.
+pub struct FormSaved<T>(pub T);
+
+/// A dialog body that creates a record, or edits one when given.
+pub trait RecordForm: Render + EventEmitter<FormSaved<Self::Record>> {
+    type Record: Record + 'static;
+    /// What the form edits, as it reads after "添加" or "编辑", e.g. "问题单"
+    /// or " MR".
+    const NOUN: &'static str;
+    /// Dialog width in pixels.
+    const WIDTH: f32;
+
+    fn new(existing: Option<Self::Record>, window: &mut Window, cx: &mut Context<Self>) -> Self;
+}
+
+/// Open form `F` in a dialog; `on_saved` runs on the opening view once the
+/// record is saved.
+pub fn open_form<F: RecordForm, V: 'static>(
+    existing: Option<F::Record>,
+    on_saved: fn(&mut V, &F::Record, &mut Context<V>),
+    window: &mut Window,
+    cx: &mut Context<V>,
+) {
+    let title = match &existing {
+        Some(record) => format!("编辑{} #{}", F::NOUN, record.id()),
+        None => format!("添加{}", F::NOUN),
+    };
+    let form = cx.new(|cx| F::new(existing, window, cx));
+    cx.subscribe(&form, move |this, _, FormSaved(record): &FormSaved<F::Record>, cx| {
+        on_saved(this, record, cx);
+        cx.notify();
+    })
+    .detach();
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog.title(title.clone()).width(px(F::WIDTH)).child(form.clone())
+    });
+}
@@ -1,48 +1,120 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, TimeDelta, Utc};
 use gpui::{
-    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, Styled, Subscription,
-    Window, div, prelude::FluentBuilder, px, relative, rgb,
+    App, AppContext, Context, Entity, Hsla, InteractiveElement, IntoElement, ParentElement, Render,
+    ScrollHandle, SharedString, StatefulInteractiveElement, Styled, Subscription, Task, Window,
+    div, prelude::FluentBuilder, px, relative, rgb,
 };
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    ActiveTheme, Icon, IconName, Sizable, StyledExt, WindowExt,
     button::*,
     h_flex,
     input::{Input, InputEvent, InputState},
+    notification::Notification,
+    tab::{Tab, TabBar},
     v_flex,
 };
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TestCase {
-    pub id: String,
-    pub name: String,
-    pub status: String,
-    pub error_msg: Option<String>,
-}
+use tools::{
+    Coded as _, DeployOutcome, DeployStep, Deployment, EnvStatus, FailureAnalysis, FailureCluster,
+    HiveTool, Job, JobEndpoints, JobError, JobProgress, JobResume, JobStatus, LeaseTool,
+    Record as _, RequestError, RequestTool, Reservation, ReservationError, StepState, Store,
+    TestCase, VirtualEnv,
+};
+
+use crate::{
+    AppState,
+    utils::format_datetime,
+    view::{export_to_excel, import_from_excel, open_job_form, open_reservation_form},
+};
+
+/// Setting key the selected tab is remembered under.
+const TAB_SETTING: &str = "hive.tab";
+
+/// How often leases are checked for expiry.
+const LEASE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long before our lease runs out we are warned, in minutes.
+const LEASE_WARNING_MINUTES: i64 = 10;
+
+/// How often queued and running jobs are polled on the job service.
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many of a job's failed cases the resume dialog lists.
+const MAX_LISTED_CASES: usize = 10;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VirtualEnv {
-    pub id: String,
-    pub name: String,
-    pub status: String, // 空闲、占用、部署中
-    pub owner: Option<String>,
+/// How often running deployments are polled for progress and logs.
+const DEPLOY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The tabs of [`HiveView`]. The code names a tab in the settings and on the
+/// command line, e.g. `hive/envs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HiveTab {
+    #[default]
+    FailedCases,
+    VirtualEnvs,
+    Jobs,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Job {
-    pub id: String,
-    pub name: String,
-    pub status: String,
-    pub progress: f32,
+impl HiveTab {
+    pub const ALL: &'static [Self] = &[Self::FailedCases, Self::VirtualEnvs, Self::Jobs];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::FailedCases => "cases",
+            Self::VirtualEnvs => "envs",
+            Self::Jobs => "jobs",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::FailedCases => "失败用例",
+            Self::VirtualEnvs => "虚拟环境",
+            Self::Jobs => "工程 Job",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|tab| tab.code().eq_ignore_ascii_case(code.trim()))
+    }
 }
 
 pub struct HiveView {
-    active_tab: usize,
+    store: Store,
+    request: RequestTool,
+    leases: LeaseTool,
+    user: String,
+    /// The job service, `None` when not configured.
+    job_endpoints: Option<JobEndpoints>,
+    active_tab: HiveTab,
     failed_cases: Vec<TestCase>,
+    /// 最近一次失败用例分析的聚类结果
+    analysis: Option<FailureAnalysis>,
+    /// 只看该签名聚类下的用例
+    selected_cluster: Option<String>,
     virtual_envs: Vec<VirtualEnv>,
     jobs: Vec<Job>,
+    /// 虚拟环境页中选中的环境
+    selected_env: Option<String>,
+    /// 已提醒过即将到期的租约，避免重复提醒
+    warned_leases: HashSet<(String, DateTime<Utc>)>,
+    /// 本次启动以来各环境最近一次部署，按环境 ID
+    deployments: HashMap<String, Deployment>,
+    deploy_log_scroll: ScrollHandle,
+    /// 工程 Job 页中选中的 Job
+    selected_job: Option<String>,
     search_input: Entity<InputState>,
     search_value: Option<String>,
     _subscriptions: Vec<Subscription>,
+    _lease_task: Task<()>,
+    _deploy_task: Option<Task<()>>,
+    _job_task: Option<Task<()>>,
+    resume_task: Option<Task<()>>,
+    lease_change_task: Option<Task<()>>,
 }
 
 impl HiveView {
@@ -53,75 +125,569 @@ impl HiveView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("搜索失败用例..."));
 
-        // 添加测试数据
-        let failed_cases = vec![
-            TestCase {
-                id: "TC001".to_string(),
-                name: "用户登录功能测试".to_string(),
-                status: "失败".to_string(),
-                error_msg: Some("连接超时".to_string()),
-            },
-            TestCase {
-                id: "TC002".to_string(),
-                name: "数据导出功能测试".to_string(),
-                status: "失败".to_string(),
-                error_msg: Some("内存不足".to_string()),
-            },
-            TestCase {
-                id: "TC003".to_string(),
-                name: "支付流程测试".to_string(),
-                status: "失败".to_string(),
-                error_msg: Some("网络错误".to_string()),
-            },
-        ];
-
-        let virtual_envs = vec![
-            VirtualEnv {
-                id: "ENV001".to_string(),
-                name: "测试环境1".to_string(),
-                status: "空闲".to_string(),
-                owner: None,
-            },
-            VirtualEnv {
-                id: "ENV002".to_string(),
-                name: "测试环境2".to_string(),
-                status: "占用".to_string(),
-                owner: Some("张三".to_string()),
-            },
-            VirtualEnv {
-                id: "ENV003".to_string(),
-                name: "测试环境3".to_string(),
-                status: "部署中".to_string(),
-                owner: None,
-            },
-        ];
-
-        let jobs = vec![
-            Job {
-                id: "JOB001".to_string(),
-                name: "自动化测试Job".to_string(),
-                status: "运行中".to_string(),
-                progress: 0.65,
-            },
-            Job {
-                id: "JOB002".to_string(),
-                name: "性能测试Job".to_string(),
-                status: "运行中".to_string(),
-                progress: 0.42,
-            },
-        ];
-
         let subscriptions = vec![cx.subscribe_in(&search_input, window, Self::on_input_event)];
 
-        Self {
-            active_tab: 0,
-            failed_cases,
-            virtual_envs,
-            jobs,
+        let state = AppState::global(cx);
+        let store = state.store.clone();
+        let request = state.request.clone();
+        let leases = state.leases.clone();
+        let user = state.user.clone();
+        let job_endpoints = state.sync_config.jobs.clone();
+        let active_tab = store
+            .setting(&user, TAB_SETTING)
+            .unwrap_or_else(|err| {
+                tracing::error!("Failed to load Hive tab: {:?}", err);
+                None
+            })
+            .and_then(|code| HiveTab::parse(&code))
+            .unwrap_or_default();
+
+        let lease_task = {
+            let leases = leases.clone();
+            cx.spawn_in(window, async move |this, cx| {
+                // 打开时先同步一次环境服务上的占用情况
+                loop {
+                    let expired = leases.refresh(Utc::now()).await;
+                    if this
+                        .update_in(cx, |this, window, cx| this.check_leases(expired, window, cx))
+                        .is_err()
+                    {
+                        break;
+                    }
+                    cx.background_executor().timer(LEASE_CHECK_INTERVAL).await;
+                }
+            })
+        };
+
+        let job_task = job_endpoints.clone().map(|endpoints| {
+            let request = request.clone();
+            cx.spawn_in(window, async move |this, cx| {
+                loop {
+                    cx.background_executor().timer(JOB_POLL_INTERVAL).await;
+                    let Ok(active) = this.update_in(cx, |this, _, _| this.active_jobs()) else {
+                        break;
+                    };
+                    let mut results = Vec::new();
+                    for id in active {
+                        let result = request.job_progress(&endpoints, &id).await;
+                        results.push((id, result));
+                    }
+                    if this
+                        .update_in(cx, |this, window, cx| {
+                            this.apply_job_progress(results, window, cx)
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+        });
+
+        let mut this = Self {
+            store,
+            request,
+            leases,
+            user,
+            job_endpoints,
+            active_tab,
+            failed_cases: Vec::new(),
+            analysis: None,
+            selected_cluster: None,
+            virtual_envs: Vec::new(),
+            jobs: Vec::new(),
+            selected_env: None,
+            warned_leases: HashSet::new(),
+            deployments: HashMap::new(),
+            deploy_log_scroll: ScrollHandle::new(),
+            selected_job: None,
             search_input,
             search_value: None,
             _subscriptions: subscriptions,
+            _lease_task: lease_task,
+            _deploy_task: None,
+            _job_task: job_task,
+            resume_task: None,
+            lease_change_task: None,
+        };
+        this.reload();
+        this
+    }
+
+    fn reload(&mut self) {
+        self.failed_cases = self
+            .store
+            .test_cases()
+            .all()
+            .map(|cases| cases.into_iter().filter(TestCase::is_failed).collect())
+            .unwrap_or_else(|err| {
+                tracing::error!("Failed to load test cases: {:?}", err);
+                Vec::new()
+            });
+        self.virtual_envs = self.store.virtual_envs().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load virtual environments: {:?}", err);
+            Vec::new()
+        });
+        self.jobs = self.store.jobs().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load jobs: {:?}", err);
+            Vec::new()
+        });
+    }
+
+    /// Switch to `tab` and remember it for the next start.
+    pub fn select_tab(&mut self, tab: HiveTab, cx: &mut Context<Self>) {
+        self.active_tab = tab;
+        if let Err(err) = self.store.set_setting(&self.user, TAB_SETTING, tab.code()) {
+            tracing::error!("Failed to save Hive tab: {:?}", err);
+        }
+        cx.notify();
+    }
+
+    /// Open the part of the view named by a deep link path such as `envs`.
+    pub fn open_path(&mut self, path: &str, cx: &mut Context<Self>) {
+        match HiveTab::parse(path) {
+            Some(tab) => self.select_tab(tab, cx),
+            None => tracing::warn!("Unknown Hive tab in deep link: {}", path),
+        }
+    }
+
+    /// 提示刷新租约时到期释放的环境，并在我们的租约快到期时提醒一次
+    fn check_leases(
+        &mut self,
+        expired: Result<Vec<VirtualEnv>, ReservationError>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        struct LeaseExpired;
+        struct LeaseExpiring;
+        let now = Utc::now();
+        let expired = expired.unwrap_or_else(|err| {
+            tracing::error!("Failed to refresh leases: {}", err);
+            Vec::new()
+        });
+        self.reload();
+
+        let mine: Vec<&str> = expired
+            .iter()
+            .filter(|env| env.owner.as_deref() == Some(self.user.as_str()))
+            .map(|env| env.name.as_str())
+            .collect();
+        if !mine.is_empty() {
+            let message = format!("环境 {} 租期已到，已自动释放", mine.join("、"));
+            window.push_notification(Notification::new().message(message).id::<LeaseExpired>(), cx);
+        }
+
+        let expiring: Vec<VirtualEnv> = HiveTool::expiring_leases(
+            &self.virtual_envs,
+            &self.user,
+            now,
+            TimeDelta::minutes(LEASE_WARNING_MINUTES),
+        )
+        .into_iter()
+        .filter(|env| {
+            env.lease_until.is_some_and(|until| self.warned_leases.insert((env.id.clone(), until)))
+        })
+        .cloned()
+        .collect();
+        for env in expiring {
+            let until = env.lease_until.map(format_datetime).unwrap_or_default();
+            let view = cx.entity().downgrade();
+            let env_id = env.id.clone();
+            window.push_notification(
+                Notification::new()
+                    .message(format!("你占用的环境 {} 将于 {} 到期", env.name, until))
+                    .id1::<LeaseExpiring>(SharedString::from(env.id.clone()))
+                    .action(move |_, _, _| {
+                        let view = view.clone();
+                        let env_id = env_id.clone();
+                        Button::new("extend-lease").ghost().small().label("续期 1 小时").on_click(
+                            move |_, window, cx| {
+                                _ = view
+                                    .update(cx, |this, cx| this.extend_lease(&env_id, window, cx));
+                            },
+                        )
+                    }),
+                cx,
+            );
+        }
+        cx.notify();
+    }
+
+    fn extend_lease(&mut self, env_id: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(env) = self.virtual_envs.iter().find(|env| env.id == env_id) else {
+            return;
+        };
+        // 续期从原到期时间顺延，而不是从现在算起
+        let now = Utc::now();
+        let remaining = env.lease_until.map_or(TimeDelta::zero(), |until| until - now);
+        let reason = env.reason.clone().unwrap_or_default();
+        let (leases, env_id, user) = (self.leases.clone(), env_id.to_string(), self.user.clone());
+        self.change_lease(
+            async move {
+                leases.reserve(&env_id, &user, &reason, remaining + TimeDelta::hours(1), now).await
+            },
+            window,
+            cx,
+        );
+    }
+
+    fn release_selected_env(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(env_id) = self.selected_env.clone() else {
+            return;
+        };
+        let (leases, user) = (self.leases.clone(), self.user.clone());
+        self.change_lease(
+            async move { leases.release(&env_id, &user, Utc::now()).await },
+            window,
+            cx,
+        );
+    }
+
+    /// 在环境服务上续期或释放，完成后刷新列表并提示错误
+    fn change_lease(
+        &mut self,
+        change: impl Future<Output = Result<VirtualEnv, ReservationError>> + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.lease_change_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = change.await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.finish_lease_change(result.map(|_| ()), window, cx)
+            });
+        }));
+    }
+
+    fn finish_lease_change(
+        &mut self,
+        result: Result<(), ReservationError>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        struct LeaseError;
+        if let Err(err) = result {
+            tracing::error!("Failed to change lease: {}", err);
+            window.push_notification(
+                Notification::new().message(err.to_string()).id::<LeaseError>(),
+                cx,
+            );
+        }
+        self.reload();
+        cx.notify();
+    }
+
+    fn deploy_selected_env(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(env_id) = self.selected_env.clone() else {
+            return;
+        };
+        if self.deployments.get(&env_id).is_some_and(Deployment::is_running) {
+            return;
+        }
+        let Some(backend) = AppState::global(cx).deploy_backend.clone() else {
+            return;
+        };
+        self.begin_deploy(
+            env_id,
+            move |this, env| {
+                this.deployments.insert(env.id.clone(), HiveTool::deploy(backend, env));
+            },
+            window,
+            cx,
+        );
+    }
+
+    /// 从失败或取消的步骤继续部署，之前成功的步骤不再执行
+    fn retry_deployment(&mut self, env_id: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.deployments.get(env_id).is_some_and(|d| d.resume_step().is_some()) {
+            return;
+        }
+        let Some(backend) = AppState::global(cx).deploy_backend.clone() else {
+            return;
+        };
+        self.begin_deploy(
+            env_id.to_string(),
+            move |this, env| {
+                if let Some(deployment) = this.deployments.get_mut(&env.id) {
+                    deployment.retry(backend);
+                }
+            },
+            window,
+            cx,
+        );
+    }
+
+    /// 先刷新环境服务上的占用，确认或取得占用后再标记为部署中，然后由 `start`
+    /// 开始部署。本地的空闲状态可能已过时，不能直接据此部署
+    fn begin_deploy(
+        &mut self,
+        env_id: String,
+        start: impl FnOnce(&mut Self, VirtualEnv) + 'static,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (leases, user) = (self.leases.clone(), self.user.clone());
+        self.lease_change_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = leases.begin_deploy(&env_id, &user, Utc::now()).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                let result = result.map(|env| {
+                    start(this, env);
+                    this.watch_deployments(window, cx);
+                });
+                this.finish_lease_change(result, window, cx);
+            });
+        }));
+    }
+
+    /// 结束部署，并归还为部署在环境服务上取得的占用，完成后刷新列表
+    fn finish_deploy(&mut self, env_id: String, cx: &mut Context<Self>) {
+        let leases = self.leases.clone();
+        cx.spawn(async move |this, cx| {
+            if let Err(err) = leases.finish_deploy(&env_id).await {
+                tracing::error!("Failed to finish deployment of {}: {}", env_id, err);
+            }
+            _ = this.update(cx, |this, cx| {
+                this.reload();
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// 强制结束卡在部署中的环境，恢复为空闲或原来的占用状态
+    fn reset_selected_env(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct EnvReset;
+        let Some(env_id) = self.selected_env.clone() else {
+            return;
+        };
+        if self.deployments.get(&env_id).is_some_and(Deployment::is_running) {
+            return;
+        }
+        let leases = self.leases.clone();
+        self.lease_change_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = leases.finish_deploy(&env_id).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                if let Ok(env) = &result {
+                    window.push_notification(
+                        Notification::new()
+                            .message(format!("已重置环境 {}", env.name))
+                            .id::<EnvReset>(),
+                        cx,
+                    );
+                }
+                this.finish_lease_change(result.map(|_| ()), window, cx);
+            });
+        }));
+    }
+
+    fn cancel_deployment(&mut self, env_id: &str, cx: &mut Context<Self>) {
+        if let Some(deployment) = self.deployments.get(env_id) {
+            deployment.cancel();
+        }
+        cx.notify();
+    }
+
+    /// Poll the running deployments until all of them have finished.
+    fn watch_deployments(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self._deploy_task = Some(cx.spawn_in(window, async move |this, cx| {
+            loop {
+                cx.background_executor().timer(DEPLOY_POLL_INTERVAL).await;
+                match this.update_in(cx, |this, window, cx| this.poll_deployments(window, cx)) {
+                    Ok(true) => {},
+                    _ => break,
+                }
+            }
+        }));
+    }
+
+    /// 收取部署进度和日志，部署结束时恢复环境状态并提示结果。返回是否仍有部署在进行
+    fn poll_deployments(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        struct DeployFinished;
+        let mut changed = false;
+        let mut finished = Vec::new();
+        for (env_id, deployment) in &mut self.deployments {
+            let was_running = deployment.is_running();
+            if deployment.poll() {
+                changed = true;
+                if was_running && !deployment.is_running() {
+                    finished.push(env_id.clone());
+                }
+            }
+        }
+        for env_id in finished {
+            self.finish_deploy(env_id.clone(), cx);
+            let Some(deployment) = self.deployments.get(&env_id) else {
+                continue;
+            };
+            let name = &deployment.env.name;
+            let message = match deployment.outcome() {
+                Some(DeployOutcome::Failed(step, message)) => {
+                    format!("环境 {} 部署失败：{}（{}）", name, step.label(), message)
+                },
+                Some(DeployOutcome::Cancelled(step)) => {
+                    format!("环境 {} 部署已在{}时取消", name, step.label())
+                },
+                _ => format!("环境 {} 部署完成", name),
+            };
+            window.push_notification(
+                Notification::new()
+                    .message(message)
+                    .id1::<DeployFinished>(SharedString::from(env_id.clone())),
+                cx,
+            );
+        }
+        if changed {
+            self.deploy_log_scroll.scroll_to_bottom();
+            cx.notify();
+        }
+        self.deployments.values().any(Deployment::is_running)
+    }
+
+    fn active_jobs(&self) -> Vec<String> {
+        self.jobs.iter().filter(|job| job.is_active()).map(|job| job.id.clone()).collect()
+    }
+
+    /// 写入轮询到的 Job 进度，Job 结束时提示结果
+    fn apply_job_progress(
+        &mut self,
+        results: Vec<(String, Result<JobProgress, RequestError>)>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        struct JobFinished;
+        let now = Utc::now();
+        for (id, result) in results {
+            let progress = match result {
+                Ok(progress) => progress,
+                Err(err) => {
+                    tracing::warn!("Failed to poll job {}: {}", id, err);
+                    continue;
+                },
+            };
+            let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) else {
+                continue;
+            };
+            if !job.apply_progress(progress, now) {
+                continue;
+            }
+            if let Err(err) = self.store.jobs().upsert(job) {
+                tracing::error!("Failed to save job {}: {:?}", job.id, err);
+            }
+            if !job.is_active() {
+                // 测试结果不在这里记下：本地用例同步后才是这次的结果，分析失败用例时会记下
+                window.push_notification(
+                    Notification::new()
+                        .message(format!("Job {} {}", job.name, job.status.label()))
+                        .id1::<JobFinished>(SharedString::from(job.id.clone())),
+                    cx,
+                );
+            }
+        }
+        cx.notify();
+    }
+
+    fn selected_job(&self) -> Option<&Job> {
+        let id = self.selected_job.as_ref()?;
+        self.jobs.iter().find(|job| &job.id == id)
+    }
+
+    /// 确认后从检查点续跑选中 Job 的失败用例
+    fn confirm_resume_job(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(job) = self.selected_job().cloned() else {
+            return;
+        };
+        let plan = match HiveTool::resume_plan(&job) {
+            Ok(plan) => plan,
+            Err(err) => return self.finish_job_change(Err(err), window, cx),
+        };
+        let mut message = format!(
+            "从检查点 {} 续跑 Job {} 的 {} 个失败用例：",
+            plan.checkpoint,
+            job.name,
+            plan.cases.len()
+        );
+        message.push_str(
+            &plan.cases.iter().take(MAX_LISTED_CASES).cloned().collect::<Vec<_>>().join("、"),
+        );
+        if plan.cases.len() > MAX_LISTED_CASES {
+            message.push_str(" 等");
         }
+        let view = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view.clone();
+            let job = job.clone();
+            let plan = plan.clone();
+            dialog.title("续跑 Job").confirm().child(message.clone()).on_ok(move |_, window, cx| {
+                let job = job.clone();
+                let plan = plan.clone();
+                _ = view.update(cx, |this, cx| this.resume_job(job, plan, window, cx));
+                true
+            })
+        });
+    }
+
+    fn resume_job(
+        &mut self,
+        job: Job,
+        plan: JobResume,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(endpoints) = self.job_endpoints.clone() else {
+            return self.finish_job_change(Err(JobError::NotConfigured), window, cx);
+        };
+        let request = self.request.clone();
+        self.resume_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = request.resume_job(&endpoints, &job, &plan).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.resume_task = None;
+                let result = result.map_err(JobError::from).and_then(|resumed| {
+                    this.store.insert_job(&resumed)?;
+                    Ok(resumed)
+                });
+                match result {
+                    Ok(resumed) => {
+                        this.selected_job = Some(resumed.id.clone());
+                        this.finish_job_change(Ok(()), window, cx);
+                    },
+                    Err(err) => this.finish_job_change(Err(err), window, cx),
+                }
+            });
+        }));
+        cx.notify();
+    }
+
+    fn finish_job_change(
+        &mut self,
+        result: Result<(), JobError>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        struct JobChangeError;
+        if let Err(err) = result {
+            tracing::error!("Failed to change job: {}", err);
+            window.push_notification(
+                Notification::new().message(err.to_string()).id::<JobChangeError>(),
+                cx,
+            );
+        }
+        self.reload();
+        cx.notify();
+    }
+
+    fn selected_env(&self) -> Option<&VirtualEnv> {
+        let id = self.selected_env.as_ref()?;
+        self.virtual_envs.iter().find(|env| &env.id == id)
+    }
+
+    fn open_env_history(&self, env: &VirtualEnv, window: &mut Window, cx: &mut App) {
+        let reservations = self.store.env_reservations(&env.id).unwrap_or_else(|err| {
+            tracing::error!("Failed to load reservations of {}: {:?}", env.id, err);
+            Vec::new()
+        });
+        let title = SharedString::from(format!("{} 占用记录", env.name));
+        window.open_sheet(cx, move |sheet, _, cx| {
+            sheet.title(title.clone()).size(px(480.)).child(render_reservations(&reservations, cx))
+        });
     }
 
     fn on_input_event(
@@ -141,11 +707,77 @@ impl HiveView {
             _ => {},
         };
     }
+
+    /// 根据搜索查询过滤失败用例
+    fn filtered_cases(&self, cx: &App) -> Vec<&TestCase> {
+        let search_query = self.search_input.read(cx).value().trim().to_lowercase();
+        self.failed_cases
+            .iter()
+            .filter(|case| {
+                self.selected_cluster
+                    .as_ref()
+                    .is_none_or(|signature| &case.signature() == signature)
+            })
+            .filter(|case: &&TestCase| {
+                case.matches(&search_query)
+                    || case
+                        .error_msg
+                        .as_ref()
+                        .map_or(false, |msg| msg.to_lowercase().contains(&search_query))
+            })
+            .collect()
+    }
+
+    /// 按错误签名聚类失败用例，并与上次分析对比
+    fn analyze_failures(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct AnalysisError;
+        self.reload();
+        // 通过的用例也要记下，不稳定用例只认记录过的通过
+        let analysis = self
+            .store
+            .test_cases()
+            .all()
+            .and_then(|cases| self.store.analyze_failures(&cases, Utc::now()));
+        match analysis {
+            Ok(analysis) => {
+                let signatures: HashSet<&str> =
+                    analysis.clusters.iter().map(|cluster| cluster.signature.as_str()).collect();
+                if self.selected_cluster.as_deref().is_some_and(|s| !signatures.contains(s)) {
+                    self.selected_cluster = None;
+                }
+                self.analysis = Some(analysis);
+            },
+            Err(err) => {
+                tracing::error!("Failed to analyze failures: {:?}", err);
+                window.push_notification(
+                    Notification::new()
+                        .message(format!("分析失败用例失败: {}", err))
+                        .id::<AnalysisError>(),
+                    cx,
+                );
+            },
+        }
+        cx.notify();
+    }
+
+    fn toggle_cluster(&mut self, signature: &str, cx: &mut Context<Self>) {
+        if self.selected_cluster.as_deref() == Some(signature) {
+            self.selected_cluster = None;
+        } else {
+            self.selected_cluster = Some(signature.to_string());
+        }
+        cx.notify();
+    }
+
+    fn handle_export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let cases = self.filtered_cases(cx).into_iter().cloned().collect();
+        export_to_excel::<TestCase>(cases, "失败用例分析.xlsx", window, cx);
+    }
 }
 
 impl Render for HiveView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let _active_tab = self.active_tab;
+        let active_index = HiveTab::ALL.iter().position(|tab| *tab == self.active_tab);
 
         v_flex()
             .size_full()
@@ -167,10 +799,24 @@ impl Render for HiveView {
                         ),
                 ),
             )
+            .child(
+                TabBar::new("hive-tabs")
+                    .w_full()
+                    .selected_index(active_index.unwrap_or_default())
+                    .on_click(cx.listener(|this, index: &usize, _, cx| {
+                        if let Some(tab) = HiveTab::ALL.get(*index) {
+                            this.select_tab(*tab, cx);
+                        }
+                    }))
+                    .children(HiveTab::ALL.iter().map(|tab| Tab::new().label(tab.label()))),
+            )
             .child(
                 // 内容区
-                // 简化实现，只保留失败用例分析功能
-                self.render_failed_cases(window, cx),
+                match self.active_tab {
+                    HiveTab::FailedCases => self.render_failed_cases(window, cx).into_any_element(),
+                    HiveTab::VirtualEnvs => self.render_virtual_envs(window, cx).into_any_element(),
+                    HiveTab::Jobs => self.render_jobs(window, cx).into_any_element(),
+                },
             )
     }
 }
@@ -178,21 +824,7 @@ impl Render for HiveView {
 impl HiveView {
     fn render_failed_cases(&self, _window: &mut Window, cx: &Context<Self>) -> impl IntoElement {
         let search_query = self.search_input.read(cx).value().trim().to_lowercase();
-
-        // 根据搜索查询过滤失败用例
-        let filtered_cases: Vec<&TestCase> = self
-            .failed_cases
-            .iter()
-            .filter(|case: &&TestCase| {
-                search_query.is_empty()
-                    || case.name.to_lowercase().contains(&search_query)
-                    || case.id.to_lowercase().contains(&search_query)
-                    || case
-                        .error_msg
-                        .as_ref()
-                        .map_or(false, |msg| msg.to_lowercase().contains(&search_query))
-            })
-            .collect();
+        let filtered_cases = self.filtered_cases(cx);
 
         v_flex()
             .flex_1()
@@ -213,15 +845,24 @@ impl HiveView {
                         Button::new("analyze-btn")
                             .primary()
                             .label("分析失败用例")
-                            .icon(Icon::new(IconName::Search)),
+                            .icon(Icon::new(IconName::Search))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.analyze_failures(window, cx);
+                            })),
                     )
                     .child(
                         Button::new("export-analysis-btn")
                             .ghost()
                             .label("导出分析报告")
-                            .icon(Icon::new(IconName::Download)),
+                            .icon(Icon::new(IconName::Download))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.handle_export(window, cx);
+                            })),
                     ),
             )
+            .when_some(self.analysis.as_ref(), |this, analysis| {
+                this.child(self.render_clusters(analysis, cx))
+            })
             .child(
                 div()
                     .w_full()
@@ -254,7 +895,123 @@ impl HiveView {
             )
     }
 
+    fn render_clusters(&self, analysis: &FailureAnalysis, cx: &Context<Self>) -> impl IntoElement {
+        let summary = match analysis.previous_run_at {
+            Some(previous) => format!(
+                "{} 个用例失败，归为 {} 类，与 {} 的分析对比",
+                analysis.clusters.iter().map(FailureCluster::count).sum::<usize>(),
+                analysis.clusters.len(),
+                format_datetime(previous)
+            ),
+            None => format!(
+                "{} 个用例失败，归为 {} 类，首次分析",
+                analysis.clusters.iter().map(FailureCluster::count).sum::<usize>(),
+                analysis.clusters.len()
+            ),
+        };
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .gap_2()
+                    .child(div().font_semibold().child("失败聚类"))
+                    .child(div().text_sm().text_color(cx.theme().muted_foreground).child(summary))
+                    .child(div().flex_1())
+                    .when(self.selected_cluster.is_some(), |this| {
+                        this.child(
+                            Button::new("show-all-cases")
+                                .ghost()
+                                .xsmall()
+                                .label("显示全部用例")
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.selected_cluster = None;
+                                    cx.notify();
+                                })),
+                        )
+                    }),
+            )
+            .children(analysis.clusters.iter().enumerate().map(|(index, cluster)| {
+                let selected = self.selected_cluster.as_ref() == Some(&cluster.signature);
+                let signature = cluster.signature.clone();
+                h_flex()
+                    .id(SharedString::from(format!("cluster-{}", index)))
+                    .w_full()
+                    .p_2()
+                    .gap_3()
+                    .items_start()
+                    .bg(cx.theme().background)
+                    .border_1()
+                    .border_color(if selected { cx.theme().primary } else { cx.theme().border })
+                    .rounded_md()
+                    .cursor_pointer()
+                    .on_click(
+                        cx.listener(move |this, _, _, cx| this.toggle_cluster(&signature, cx)),
+                    )
+                    .child(
+                        div()
+                            .min_w(px(40.0))
+                            .text_center()
+                            .font_bold()
+                            .text_color(rgb(0xef4444))
+                            .child(cluster.count().to_string()),
+                    )
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .gap_1()
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .items_center()
+                                    .child(div().text_sm().font_semibold().child(
+                                        if cluster.signature.is_empty() {
+                                            "（无错误信息）".to_string()
+                                        } else {
+                                            cluster.signature.clone()
+                                        },
+                                    ))
+                                    .when(cluster.new, |this| {
+                                        this.child(cluster_marker("新增", rgb(0xef4444).into()))
+                                    })
+                                    .when(cluster.is_flaky(), |this| {
+                                        this.child(cluster_marker(
+                                            format!("不稳定 {}", cluster.flaky_cases.len()),
+                                            rgb(0xf59e0b).into(),
+                                        ))
+                                    }),
+                            )
+                            .when_some(cluster.example(), |this, example| {
+                                this.child(
+                                    div().text_xs().text_color(cx.theme().muted_foreground).child(
+                                        example.lines().next().unwrap_or_default().to_string(),
+                                    ),
+                                )
+                            }),
+                    )
+            }))
+    }
+
     fn render_virtual_envs(&self, _window: &mut Window, cx: &Context<Self>) -> impl IntoElement {
+        let selected = self.selected_env();
+        let selected_mine = selected.is_some_and(|env| {
+            env.status == EnvStatus::Occupied && env.owner.as_deref() == Some(self.user.as_str())
+        });
+        let has_backend = AppState::global(cx).deploy_backend.is_some();
+        let can_deploy = has_backend
+            && selected.is_some_and(|env| match env.status {
+                EnvStatus::Idle => true,
+                EnvStatus::Occupied => env.owner.as_deref() == Some(self.user.as_str()),
+                EnvStatus::Deploying => false,
+            });
+        let deployment = selected.and_then(|env| self.deployments.get(&env.id));
+        // 部署中却没有在本窗口运行的部署，说明部署已中断，允许强制重置
+        let stuck = selected.is_some_and(|env| env.status == EnvStatus::Deploying)
+            && !deployment.is_some_and(Deployment::is_running);
+
         v_flex()
             .flex_1()
             .w_full()
@@ -266,21 +1023,80 @@ impl HiveView {
                     .child(
                         Button::new("occupy-env-btn")
                             .primary()
-                            .label("占用环境")
-                            .icon(Icon::new(IconName::Lock)),
+                            .label(if selected_mine { "续期环境" } else { "占用环境" })
+                            .icon(Icon::new(IconName::Lock))
+                            .disabled(selected.is_none())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                if let Some(env) = this.selected_env().cloned() {
+                                    open_reservation_form(
+                                        env,
+                                        |this: &mut Self, _, _| this.reload(),
+                                        window,
+                                        cx,
+                                    );
+                                }
+                            })),
                     )
                     .child(
                         Button::new("release-env-btn")
                             .ghost()
                             .label("释放环境")
-                            .icon(Icon::new(IconName::Unlock)),
+                            .icon(Icon::new(IconName::Unlock))
+                            .disabled(!selected_mine)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.release_selected_env(window, cx);
+                            })),
                     )
                     .child(
                         Button::new("deploy-env-btn")
                             .ghost()
                             .label("部署环境")
-                            .icon(Icon::new(IconName::Rocket)),
-                    ),
+                            .icon(Icon::new(IconName::Rocket))
+                            .disabled(!can_deploy)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.deploy_selected_env(window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("import-env-btn")
+                            .ghost()
+                            .label("导入环境")
+                            .icon(Icon::new(IconName::Upload))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                import_from_excel::<VirtualEnv, _>(
+                                    "导入虚拟环境",
+                                    |this: &mut Self, _| this.reload(),
+                                    window,
+                                    cx,
+                                );
+                            })),
+                    )
+                    .when(!has_backend, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child("未配置部署后端"),
+                        )
+                    })
+                    .when(stuck, |this| {
+                        this.child(
+                            Button::new("reset-env-btn")
+                                .ghost()
+                                .label("重置环境")
+                                .icon(Icon::new(IconName::RotateCw))
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.reset_selected_env(window, cx);
+                                })),
+                        )
+                    })
+                    .child(div().flex_1())
+                    .child(div().text_sm().text_color(cx.theme().muted_foreground).child(
+                        match selected {
+                            Some(env) => format!("已选择 {}", env.name),
+                            None => "点击环境卡片选择环境".to_string(),
+                        },
+                    )),
             )
             .child(div().w_full().h_full().child(if self.virtual_envs.is_empty() {
                 v_flex()
@@ -297,9 +1113,111 @@ impl HiveView {
             } else {
                 div().child(self.render_env_grid(cx))
             }))
+            .when_some(deployment, |this, deployment| {
+                this.child(self.render_deploy_panel(deployment, cx))
+            })
+    }
+
+    fn render_deploy_panel(&self, deployment: &Deployment, cx: &Context<Self>) -> impl IntoElement {
+        let env_id = deployment.env.id.clone();
+        let running = deployment.is_running();
+        let resume_step = deployment.resume_step().filter(|_| !running);
+
+        v_flex()
+            .w_full()
+            .p_3()
+            .gap_3()
+            .bg(cx.theme().background)
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_md()
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(div().font_semibold().child(format!("{} 部署日志", deployment.env.name)))
+                    .child(div().flex_1())
+                    .when(running, |this| {
+                        this.child(
+                            Button::new("cancel-deploy-btn")
+                                .ghost()
+                                .small()
+                                .label("取消部署")
+                                .icon(Icon::new(IconName::CircleX))
+                                .on_click(cx.listener({
+                                    let env_id = env_id.clone();
+                                    move |this, _, _, cx| this.cancel_deployment(&env_id, cx)
+                                })),
+                        )
+                    })
+                    .when_some(resume_step, |this, step| {
+                        this.child(
+                            Button::new("retry-deploy-btn")
+                                .primary()
+                                .small()
+                                .label(format!("从{}重试", step.label()))
+                                .icon(Icon::new(IconName::Redo))
+                                .on_click(cx.listener({
+                                    let env_id = env_id.clone();
+                                    move |this, _, window, cx| {
+                                        this.retry_deployment(&env_id, window, cx)
+                                    }
+                                })),
+                        )
+                    }),
+            )
+            .child(h_flex().w_full().gap_2().children(deployment.steps.iter().map(
+                |(step, state)| {
+                    let (color, text) = match state {
+                        StepState::Pending => (cx.theme().muted_foreground, "等待"),
+                        StepState::Running => (rgb(0x3b82f6).into(), "进行中"),
+                        StepState::Succeeded => (rgb(0x22c55e).into(), "成功"),
+                        StepState::Failed(_) => (rgb(0xef4444).into(), "失败"),
+                        StepState::Cancelled => (rgb(0xf59e0b).into(), "已取消"),
+                    };
+                    h_flex()
+                        .flex_1()
+                        .gap_2()
+                        .items_center()
+                        .px_2()
+                        .py_1()
+                        .border_1()
+                        .border_color(color)
+                        .rounded_md()
+                        .text_sm()
+                        .child(div().w(px(8.0)).h(px(8.0)).rounded_full().bg(color))
+                        .child(step.label())
+                        .child(div().text_color(color).child(text))
+                },
+            )))
+            .child(
+                div()
+                    .id("deploy-log")
+                    .w_full()
+                    .h(px(200.0))
+                    .p_2()
+                    .bg(cx.theme().muted)
+                    .rounded_md()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.deploy_log_scroll)
+                    .font_family("monospace")
+                    .text_xs()
+                    .children(deployment.logs.iter().map(|log| {
+                        div().child(format!(
+                            "{} [{}] {}",
+                            log.at.with_timezone(&Local).format("%H:%M:%S"),
+                            log.step.label(),
+                            log.line
+                        ))
+                    })),
+            )
     }
 
     fn render_jobs(&self, _window: &mut Window, cx: &Context<Self>) -> impl IntoElement {
+        let can_resume = self.selected_job().is_some_and(|job| HiveTool::resume_plan(job).is_ok());
+        let resuming = self.resume_task.is_some();
+
         v_flex()
             .flex_1()
             .w_full()
@@ -312,13 +1230,27 @@ impl HiveView {
                         Button::new("start-job-btn")
                             .primary()
                             .label("拉起 Job")
-                            .icon(Icon::new(IconName::Play)),
+                            .icon(Icon::new(IconName::Play))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                open_job_form(
+                                    |this: &mut Self, job, _| {
+                                        this.selected_job = Some(job.id.clone());
+                                        this.reload();
+                                    },
+                                    window,
+                                    cx,
+                                );
+                            })),
                     )
                     .child(
                         Button::new("continue-job-btn")
                             .ghost()
-                            .label("续跑 Job")
-                            .icon(Icon::new(IconName::RotateCw)),
+                            .label(if resuming { "正在续跑..." } else { "续跑 Job" })
+                            .icon(Icon::new(IconName::RotateCw))
+                            .disabled(!can_resume || resuming)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.confirm_resume_job(window, cx);
+                            })),
                     )
                     .child(
                         Button::new("analyze-failures-btn")
@@ -346,11 +1278,7 @@ impl HiveView {
                                     .size_16()
                                     .text_color(cx.theme().muted_foreground),
                             )
-                            .child(
-                                div()
-                                    .text_color(cx.theme().muted_foreground)
-                                    .child("暂无运行中的 Job"),
-                            )
+                            .child(div().text_color(cx.theme().muted_foreground).child("暂无 Job"))
                     } else {
                         div().child(self.render_job_list(cx))
                     }),
@@ -392,13 +1320,29 @@ impl HiveView {
 
     fn render_env_grid(&self, cx: &Context<Self>) -> impl IntoElement {
         div().w_full().grid().grid_cols(3).gap_4().children(self.virtual_envs.iter().map(|env| {
+            let selected = self.selected_env.as_ref() == Some(&env.id);
+            let env_id = env.id.clone();
+            let running_step: Option<DeployStep> =
+                self.deployments.get(&env.id).and_then(|deployment| {
+                    deployment
+                        .steps
+                        .iter()
+                        .find(|(_, state)| *state == StepState::Running)
+                        .map(|(step, _)| *step)
+                });
             v_flex()
+                .id(SharedString::from(format!("env-{}", env.id)))
                 .p_4()
                 .gap_2()
                 .bg(cx.theme().background)
                 .border_1()
-                .border_color(cx.theme().border)
+                .border_color(if selected { cx.theme().primary } else { cx.theme().border })
                 .rounded_lg()
+                .cursor_pointer()
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    this.selected_env = Some(env_id.clone());
+                    cx.notify();
+                }))
                 .child(div().font_semibold().child(env.name.clone()))
                 .child(
                     div()
@@ -410,14 +1354,20 @@ impl HiveView {
                     h_flex()
                         .gap_2()
                         .items_center()
-                        .child(div().w(px(8.0)).h(px(8.0)).rounded_full().bg(
-                            match env.status.as_str() {
-                                "空闲" => rgb(0x22c55e),
-                                "占用" => rgb(0xf59e0b),
-                                _ => rgb(0x3b82f6),
-                            },
-                        ))
-                        .child(div().text_sm().child(env.status.clone())),
+                        .child(div().w(px(8.0)).h(px(8.0)).rounded_full().bg(match env.status {
+                            EnvStatus::Idle => rgb(0x22c55e),
+                            EnvStatus::Occupied => rgb(0xf59e0b),
+                            EnvStatus::Deploying => rgb(0x3b82f6),
+                        }))
+                        .child(div().text_sm().child(env.status.label()))
+                        .when_some(running_step, |this, step| {
+                            this.child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(format!("正在{}", step.label())),
+                            )
+                        }),
                 )
                 .when_some(env.owner.as_ref(), |this, owner| {
                     this.child(
@@ -427,48 +1377,177 @@ impl HiveView {
                             .child(format!("使用者: {}", owner)),
                     )
                 })
+                .when_some(env.lease_until, |this, until| {
+                    this.child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format!("租期至: {}", format_datetime(until))),
+                    )
+                })
+                .when_some(env.reason.as_ref(), |this, reason| {
+                    this.child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format!("原因: {}", reason)),
+                    )
+                })
+                .child(
+                    h_flex().justify_end().child(
+                        Button::new(SharedString::from(format!("env-history-{}", env.id)))
+                            .ghost()
+                            .xsmall()
+                            .label("占用记录")
+                            .on_click(cx.listener({
+                                let env = env.clone();
+                                move |this, _, window, cx| {
+                                    cx.stop_propagation();
+                                    this.open_env_history(&env, window, cx);
+                                }
+                            })),
+                    ),
+                )
         }))
     }
 
     fn render_job_list(&self, cx: &Context<Self>) -> impl IntoElement {
-        v_flex().w_full().gap_2().children(self.jobs.iter().map(|job| {
-            v_flex()
-                .w_full()
-                .p_3()
-                .gap_2()
-                .bg(cx.theme().background)
-                .border_1()
-                .border_color(cx.theme().border)
-                .rounded_md()
-                .child(
-                    h_flex()
+        let board = HiveTool::job_board(&self.jobs);
+        v_flex().w_full().gap_4().children(
+            [("排队中", board.queued), ("运行中", board.running), ("已结束", board.finished)]
+                .into_iter()
+                .filter(|(_, jobs)| !jobs.is_empty())
+                .map(|(title, jobs)| {
+                    v_flex()
                         .w_full()
-                        .items_center()
-                        .justify_between()
-                        .child(div().font_semibold().child(job.name.clone()))
+                        .gap_2()
                         .child(
                             div()
                                 .text_sm()
+                                .font_semibold()
                                 .text_color(cx.theme().muted_foreground)
-                                .child(job.status.clone()),
-                        ),
-                )
-                .child(
-                    h_flex()
-                        .w_full()
-                        .gap_2()
-                        .items_center()
-                        .child(
-                            div().flex_1().h(px(8.0)).bg(cx.theme().border).rounded_full().child(
-                                div()
-                                    .w(relative(job.progress))
-                                    .h_full()
-                                    .bg(rgb(0x3b82f6))
-                                    .rounded_full(),
-                            ),
+                                .child(format!("{} ({})", title, jobs.len())),
                         )
-                        .child(div().text_sm().child(format!("{:.0}%", job.progress * 100.0))),
+                        .children(jobs.into_iter().map(|job| self.render_job(job, cx)))
+                }),
+        )
+    }
+
+    fn render_job(&self, job: &Job, cx: &Context<Self>) -> impl IntoElement {
+        let selected = self.selected_job.as_ref() == Some(&job.id);
+        let job_id = job.id.clone();
+        let color = match job.status {
+            JobStatus::Failed => rgb(0xef4444),
+            JobStatus::Succeeded => rgb(0x22c55e),
+            JobStatus::Cancelled => rgb(0x94a3b8),
+            JobStatus::Queued | JobStatus::Running => rgb(0x3b82f6),
+        };
+        v_flex()
+            .id(SharedString::from(format!("job-{}", job.id)))
+            .w_full()
+            .p_3()
+            .gap_2()
+            .bg(cx.theme().background)
+            .border_1()
+            .border_color(if selected { cx.theme().primary } else { cx.theme().border })
+            .rounded_md()
+            .cursor_pointer()
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.selected_job = Some(job_id.clone());
+                cx.notify();
+            }))
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .child(div().font_semibold().child(job.name.clone()))
+                    .child(div().text_sm().text_color(color).child(job.status.label())),
+            )
+            .when_some(job.params.as_ref(), |this, params| {
+                this.child(
+                    div().text_xs().text_color(cx.theme().muted_foreground).child(params.summary()),
                 )
-        }))
+            })
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div().flex_1().h(px(8.0)).bg(cx.theme().border).rounded_full().child(
+                            div().w(relative(job.progress)).h_full().bg(color).rounded_full(),
+                        ),
+                    )
+                    .child(div().text_sm().child(format!("{:.0}%", job.progress * 100.0))),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .when_some(job.checkpoint.as_ref(), |this, checkpoint| {
+                        this.child(format!(
+                            "已执行 {}/{}，失败 {} 个",
+                            checkpoint.finished,
+                            checkpoint.total,
+                            checkpoint.failed_cases.len()
+                        ))
+                    })
+                    .when_some(job.resumed_from.as_ref(), |this, from| {
+                        this.child(format!("续跑自 {}", from))
+                    })
+                    .child(div().flex_1())
+                    .when_some(job.updated_at, |this, at| {
+                        this.child(format!("更新于 {}", format_datetime(at)))
+                    }),
+            )
     }
 }
+
+fn cluster_marker(label: impl Into<SharedString>, color: Hsla) -> impl IntoElement {
+    div()
+        .px_1()
+        .text_xs()
+        .text_color(color)
+        .border_1()
+        .border_color(color)
+        .rounded_sm()
+        .child(label.into())
+}
+
+fn render_reservations(reservations: &[Reservation], cx: &App) -> impl IntoElement {
+    if reservations.is_empty() {
+        return v_flex().text_sm().text_color(cx.theme().muted_foreground).child("暂无占用记录");
+    }
+    v_flex().w_full().gap_2().children(reservations.iter().map(|reservation| {
+        let end = match (reservation.released_at, reservation.lease_end) {
+            (Some(released_at), Some(end)) => {
+                format!("{}于 {}", end.label(), format_datetime(released_at))
+            },
+            _ => format!("占用中，租期至 {}", format_datetime(reservation.lease_until)),
+        };
+        v_flex()
+            .w_full()
+            .p_2()
+            .gap_1()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_md()
+            .child(
+                h_flex()
+                    .w_full()
+                    .justify_between()
+                    .text_sm()
+                    .child(div().font_semibold().child(reservation.owner.clone()))
+                    .child(
+                        div()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format_datetime(reservation.reserved_at)),
+                    ),
+            )
+            .child(div().text_sm().child(reservation.reason.clone()))
+            .child(div().text_xs().text_color(cx.theme().muted_foreground).child(end))
+    }))
+}
@@ -0,0 +1,386 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, InteractiveElement, IntoElement, ParentElement,
+    PathPromptOptions, Render, SharedString, StatefulInteractiveElement, Styled, Subscription,
+    Task, Window, div, px, rgb,
+};
+use gpui_component::{
+    ActiveTheme, IndexPath, Sizable, StyledExt, WindowExt,
+    button::*,
+    checkbox::Checkbox,
+    h_flex,
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+use tools::{
+    ColumnMapping, ExcelError, ExcelTool, ImportPreview, Importable, Sheet, Store, Template,
+    Workbook, column_name,
+};
+
+use crate::AppState;
+
+pub enum ImportEvent {
+    Imported(usize),
+}
+
+/// Maps the columns of a sheet onto `T`'s import fields and shows which
+/// rows would be rejected before anything is written.
+pub struct ImportWizard<T: Importable> {
+    store: Store,
+    workbook: Workbook,
+    sheet: Sheet,
+    mapping: ColumnMapping,
+    preview: ImportPreview<T>,
+    /// Whether rows whose id is already stored overwrite those records.
+    overwrite: bool,
+    sheet_select: Entity<SelectState<Vec<SharedString>>>,
+    column_selects: Vec<Entity<SelectState<Vec<SharedString>>>>,
+    _sheet_subscription: Subscription,
+    _subscriptions: Vec<Subscription>,
+    _load_task: Option<Task<()>>,
+}
+
+impl<T: Importable> EventEmitter<ImportEvent> for ImportWizard<T> {}
+
+impl<T: Importable + Clone + Send + 'static> ImportWizard<T> {
+    pub fn new(
+        workbook: Workbook,
+        sheet: Sheet,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let sheet_names: Vec<SharedString> =
+            workbook.sheet_names.iter().cloned().map(SharedString::from).collect();
+        let selected = workbook.sheet_names.iter().position(|name| *name == sheet.name);
+        let sheet_select =
+            cx.new(|cx| SelectState::new(sheet_names, selected.map(IndexPath::new), window, cx));
+        let sheet_subscription = cx.subscribe_in(
+            &sheet_select,
+            window,
+            |this, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                let SelectEvent::Confirm(Some(name)) = event else {
+                    return;
+                };
+                this.switch_sheet(name.to_string(), window, cx);
+            },
+        );
+
+        let mut this = Self {
+            store: AppState::global(cx).store.clone(),
+            workbook,
+            sheet: sheet.clone(),
+            // Filled in by `set_sheet` below.
+            mapping: ColumnMapping::guess(T::IMPORT_FIELDS, &[]),
+            preview: ImportPreview { headers: Vec::new(), rows: Vec::new() },
+            overwrite: false,
+            sheet_select,
+            column_selects: Vec::new(),
+            _sheet_subscription: sheet_subscription,
+            _subscriptions: Vec::new(),
+            _load_task: None,
+        };
+        this.set_sheet(sheet, window, cx);
+        this
+    }
+
+    /// Read `name` in the background and map its columns afresh.
+    fn switch_sheet(&mut self, name: String, window: &mut Window, cx: &mut Context<Self>) {
+        if name == self.sheet.name {
+            return;
+        }
+        let workbook = self.workbook.clone();
+        self._load_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let sheet = cx.background_spawn(async move { workbook.read_sheet(&name) }).await;
+            _ = this.update_in(cx, |this, window, cx| match sheet {
+                Ok(sheet) => this.set_sheet(sheet, window, cx),
+                Err(err) => push_error(format!("读取工作表失败: {}", err), window, cx),
+            });
+        }));
+    }
+
+    fn set_sheet(&mut self, sheet: Sheet, window: &mut Window, cx: &mut Context<Self>) {
+        self.mapping = ColumnMapping::guess(T::IMPORT_FIELDS, &sheet.headers);
+        self.sheet = sheet;
+        self.refresh_preview(window, cx);
+
+        // Column letters keep the options unique even if headers repeat.
+        let mut options: Vec<SharedString> = vec!["不导入".into()];
+        options.extend(self.sheet.headers.iter().enumerate().map(|(index, header)| {
+            SharedString::from(format!("{} · {}", column_name(index), header))
+        }));
+
+        let mut column_selects = Vec::new();
+        let mut subscriptions = Vec::new();
+        for field in 0..T::IMPORT_FIELDS.len() {
+            let selected = self.mapping.column(field).map_or(0, |column| column + 1);
+            let state = cx.new(|cx| {
+                SelectState::new(options.clone(), Some(IndexPath::new(selected)), window, cx)
+            });
+            let options = options.clone();
+            subscriptions.push(cx.subscribe_in(
+                &state,
+                window,
+                move |this, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    let SelectEvent::Confirm(value) = event;
+                    let column = value
+                        .as_ref()
+                        .and_then(|value| options.iter().position(|option| option == value))
+                        .and_then(|index| index.checked_sub(1));
+                    this.mapping.set(field, column);
+                    this.refresh_preview(window, cx);
+                    cx.notify();
+                },
+            ));
+            column_selects.push(state);
+        }
+        self.column_selects = column_selects;
+        self._subscriptions = subscriptions;
+        cx.notify();
+    }
+
+    /// Validate the sheet against the current mapping and the stored records.
+    fn refresh_preview(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match ImportPreview::new(&self.sheet, &self.mapping, &self.store) {
+            Ok(preview) => self.preview = preview,
+            Err(err) => {
+                tracing::error!("Failed to load stored {}: {:?}", T::NAME, err);
+                push_error(format!("读取已有记录失败: {}", err), window, cx);
+            },
+        }
+    }
+
+    fn commit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct ImportResult;
+        let message = match self.preview.commit(&self.store, self.overwrite) {
+            Ok(count) => {
+                cx.emit(ImportEvent::Imported(count));
+                window.close_dialog(cx);
+                format!("已导入 {} 条记录", count)
+            },
+            Err(err) => {
+                tracing::error!("Failed to import {}: {:?}", T::NAME, err);
+                format!("导入失败，未写入任何记录: {}", err)
+            },
+        };
+        let note = Notification::new().message(message).id::<ImportResult>();
+        window.push_notification(note, cx);
+    }
+
+    fn export_errors(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let directory = std::env::current_dir().unwrap_or_default();
+        let path = cx.prompt_for_new_path(&directory, Some("导入错误报告.xlsx"));
+        let preview = self.preview.clone();
+
+        cx.spawn_in(window, async move |_, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+            let result = cx
+                .background_spawn({
+                    let path = path.clone();
+                    async move { preview.write_error_report(&path) }
+                })
+                .await;
+
+            _ = cx.update(|window, cx| {
+                struct ErrorReport;
+                let message = match result {
+                    Ok(()) => format!("错误报告已保存到: {}", path.display()),
+                    Err(err) => format!("保存错误报告失败: {}", err),
+                };
+                let note = Notification::new().message(message).id::<ErrorReport>();
+                window.push_notification(note, cx);
+            });
+        })
+        .detach();
+    }
+}
+
+impl<T: Importable + Clone + Send + 'static> Render for ImportWizard<T> {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let missing = self.mapping.missing_required(T::IMPORT_FIELDS);
+        let valid = self.preview.valid().count();
+        let rejected = self.preview.rejected().count();
+        let conflicts = self.preview.conflicts().count();
+        let importable = self.preview.importable(self.overwrite);
+
+        v_flex()
+            .w_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("工作表")
+                    .child(div().w(px(220.)).child(Select::new(&self.sheet_select).small()))
+                    .child(format!("共 {} 行数据", self.preview.rows.len())),
+            )
+            .child(
+                // 列映射
+                h_flex().w_full().flex_wrap().gap_x_6().gap_y_2().children(
+                    T::IMPORT_FIELDS.iter().zip(&self.column_selects).map(|(field, state)| {
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(div().w(px(80.)).text_sm().child(if field.required {
+                                format!("{} *", field.label)
+                            } else {
+                                field.label.to_string()
+                            }))
+                            .child(div().w(px(220.)).child(Select::new(state).small()))
+                    }),
+                ),
+            )
+            .children((!missing.is_empty()).then(|| {
+                div()
+                    .text_sm()
+                    .text_color(rgb(0xef4444))
+                    .child(format!("以下必填字段尚未映射：{}", missing.join("、")))
+            }))
+            .child(
+                div().font_semibold().child(format!("可导入 {} 行，错误 {} 行", valid, rejected)),
+            )
+            .children((conflicts > 0).then(|| {
+                let ids = self
+                    .preview
+                    .conflicts()
+                    .filter_map(|row| row.existing.as_ref().map(|record| record.id().to_string()))
+                    .collect::<Vec<_>>()
+                    .join("、");
+                v_flex()
+                    .gap_1()
+                    .text_sm()
+                    .child(
+                        div()
+                            .text_color(rgb(0xf59e0b))
+                            .child(format!("其中 {} 行的 ID 已存在：{}", conflicts, ids)),
+                    )
+                    .child(
+                        Checkbox::new("import-overwrite")
+                            .label("覆盖已存在的记录（状态变更须符合流转规则）")
+                            .checked(self.overwrite)
+                            .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                this.overwrite = *checked;
+                                cx.notify();
+                            })),
+                    )
+            }))
+            .child(
+                // 校验结果
+                v_flex()
+                    .id("import-errors")
+                    .max_h(px(240.))
+                    .overflow_y_scroll()
+                    .gap_1()
+                    .text_sm()
+                    .children(self.preview.rejected().map(|row| {
+                        let errors = row.result.as_ref().err().map(|errors| errors.join("；"));
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_shrink_0()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(format!("第 {} 行", row.row)),
+                            )
+                            .child(
+                                div().text_color(rgb(0xef4444)).child(errors.unwrap_or_default()),
+                            )
+                    })),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .justify_end()
+                    .gap_2()
+                    .child(
+                        Button::new("export-import-errors-btn")
+                            .ghost()
+                            .label("导出错误报告")
+                            .disabled(rejected == 0)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.export_errors(window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("commit-import-btn")
+                            .primary()
+                            .label(format!("导入 {} 条", importable))
+                            .disabled(importable == 0 || !missing.is_empty())
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.commit(window, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+/// Pick a spreadsheet, then open the import wizard for the sheet named after
+/// `T`'s template, or the first sheet if there is none. Files made from an
+/// outdated or another record type's template are refused.
+/// `on_imported` runs on the calling view after rows were written.
+pub(crate) fn import_from_excel<T, V>(
+    title: &'static str,
+    on_imported: impl Fn(&mut V, &mut Context<V>) + 'static,
+    window: &mut Window,
+    cx: &mut Context<V>,
+) where
+    T: Template + Clone + Send + 'static,
+    V: 'static,
+{
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: false,
+        prompt: Some("选择要导入的 Excel 文件".into()),
+    });
+
+    cx.spawn_in(window, async move |this, cx| {
+        let Ok(Ok(Some(paths))) = paths.await else {
+            return;
+        };
+        let Some(path) = paths.into_iter().next() else {
+            return;
+        };
+        let loaded = cx
+            .background_spawn(async move {
+                let workbook = ExcelTool::open(&path)?;
+                workbook.ensure_template::<T>()?;
+                let sheet = workbook
+                    .import_sheet::<T>()
+                    .map(|name| workbook.read_sheet(name))
+                    .transpose()?;
+                Ok::<_, ExcelError>((workbook, sheet))
+            })
+            .await;
+
+        _ = this.update_in(cx, |_, window, cx| {
+            let (workbook, sheet) = match loaded {
+                Ok((workbook, Some(sheet))) => (workbook, sheet),
+                Ok((_, None)) => return push_error("文件中没有工作表".to_string(), window, cx),
+                Err(
+                    err @ (ExcelError::OutdatedTemplate { .. } | ExcelError::WrongTemplate { .. }),
+                ) => return push_error(err.to_string(), window, cx),
+                Err(err) => return push_error(format!("读取 Excel 失败: {}", err), window, cx),
+            };
+
+            let wizard = cx.new(|cx| ImportWizard::<T>::new(workbook, sheet, window, cx));
+            cx.subscribe(&wizard, move |this, _, _: &ImportEvent, cx| {
+                on_imported(this, cx);
+                cx.notify();
+            })
+            .detach();
+            window.open_dialog(cx, move |dialog, _, _| {
+                dialog.title(title).width(px(900.)).child(wizard.clone())
+            });
+        });
+    })
+    .detach();
+}
+
+fn push_error(message: String, window: &mut Window, cx: &mut App) {
+    struct ImportError;
+    window.push_notification(Notification::new().message(message).id::<ImportError>(), cx);
+}
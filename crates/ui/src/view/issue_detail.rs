@@ -0,0 +1,390 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder, px, rgb,
+};
+use gpui_component::{
+    ActiveTheme, Icon, IconName, Sizable, StyledExt, WindowExt,
+    button::*,
+    h_flex,
+    input::{Input, InputState},
+    notification::Notification,
+    v_flex,
+};
+use tools::{Coded as _, IssueDetail, IssueEvent, LinkKind, Linked, Store};
+
+use crate::{
+    AppState,
+    utils::{format_datetime, format_duration},
+};
+
+/// Everything about one issue: its fields, status history, linked MRs and
+/// requirements, and local comments.
+pub struct IssueDetailPanel {
+    store: Store,
+    user: String,
+    issue_id: String,
+    detail: Option<IssueDetail>,
+    comment_input: Entity<InputState>,
+    mr_link_input: Entity<InputState>,
+    requirement_link_input: Entity<InputState>,
+}
+
+impl IssueDetailPanel {
+    /// Open the detail sheet of `issue_id`.
+    pub fn open(issue_id: String, window: &mut Window, cx: &mut App) {
+        let title = SharedString::from(format!("问题单 #{}", issue_id));
+        let panel = cx.new(|cx| Self::new(issue_id, window, cx));
+        window.open_sheet(cx, move |sheet, _, _| {
+            sheet.title(title.clone()).size(px(560.)).child(panel.clone())
+        });
+    }
+
+    fn new(issue_id: String, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let comment_input = cx.new(|cx| {
+            InputState::new(window, cx).auto_grow(2, 6).placeholder("添加评论，仅保存在本地...")
+        });
+        let mr_link_input = cx.new(|cx| InputState::new(window, cx).placeholder("MR ID"));
+        let requirement_link_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("需求 ID"));
+
+        let state = AppState::global(cx);
+        let mut this = Self {
+            store: state.store.clone(),
+            user: state.user.clone(),
+            issue_id,
+            detail: None,
+            comment_input,
+            mr_link_input,
+            requirement_link_input,
+        };
+        this.reload();
+        this
+    }
+
+    fn reload(&mut self) {
+        self.detail = self.store.issue_detail(&self.issue_id).unwrap_or_else(|err| {
+            tracing::error!("Failed to load issue {}: {:?}", self.issue_id, err);
+            None
+        });
+    }
+
+    fn add_comment(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let body = self.comment_input.read(cx).value().trim().to_string();
+        if body.is_empty() {
+            return;
+        }
+        match self.store.add_issue_comment(&self.issue_id, &self.user, &body) {
+            Ok(_) => {
+                self.comment_input.update(cx, |input, cx| input.set_value("", window, cx));
+                self.reload();
+            },
+            Err(err) => {
+                tracing::error!("Failed to comment on issue {}: {:?}", self.issue_id, err);
+                push_error(format!("保存评论失败: {}", err), window, cx);
+            },
+        }
+        cx.notify();
+    }
+
+    fn delete_comment(&mut self, comment_id: i64, window: &mut Window, cx: &mut Context<Self>) {
+        if let Err(err) = self.store.delete_issue_comment(comment_id) {
+            tracing::error!("Failed to delete comment {}: {:?}", comment_id, err);
+            push_error(format!("删除评论失败: {}", err), window, cx);
+        }
+        self.reload();
+        cx.notify();
+    }
+
+    fn link_input(&self, kind: LinkKind) -> &Entity<InputState> {
+        match kind {
+            LinkKind::MergeRequest => &self.mr_link_input,
+            LinkKind::Requirement => &self.requirement_link_input,
+        }
+    }
+
+    fn link(&mut self, kind: LinkKind, window: &mut Window, cx: &mut Context<Self>) {
+        let input = self.link_input(kind).clone();
+        let target_id = input.read(cx).value().trim().to_string();
+        if target_id.is_empty() {
+            return;
+        }
+        match self.store.link_issue(&self.issue_id, kind, &target_id) {
+            Ok(()) => {
+                input.update(cx, |input, cx| input.set_value("", window, cx));
+                self.reload();
+            },
+            Err(err) => {
+                tracing::error!(
+                    "Failed to link {} to issue {}: {:?}",
+                    target_id,
+                    self.issue_id,
+                    err
+                );
+                push_error(format!("关联失败: {}", err), window, cx);
+            },
+        }
+        cx.notify();
+    }
+
+    fn unlink(&mut self, kind: LinkKind, target_id: &str, cx: &mut Context<Self>) {
+        if let Err(err) = self.store.unlink_issue(&self.issue_id, kind, target_id) {
+            tracing::error!(
+                "Failed to unlink {} from issue {}: {:?}",
+                target_id,
+                self.issue_id,
+                err
+            );
+        }
+        self.reload();
+        cx.notify();
+    }
+}
+
+impl Render for IssueDetailPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(detail) = &self.detail else {
+            return v_flex()
+                .id("issue-detail")
+                .size_full()
+                .items_center()
+                .justify_center()
+                .text_color(cx.theme().muted_foreground)
+                .child("问题单不存在或已被删除");
+        };
+
+        v_flex()
+            .id("issue-detail")
+            .size_full()
+            .gap_4()
+            .overflow_y_scroll()
+            .child(self.render_fields(detail, cx))
+            .child(self.render_history(detail, cx))
+            .child(self.render_links(
+                LinkKind::MergeRequest,
+                &detail.merge_requests,
+                |mr| format!("{} · {}", mr.title, mr.status.label()),
+                cx,
+            ))
+            .child(self.render_links(
+                LinkKind::Requirement,
+                &detail.requirements,
+                |req| format!("{} · {} · {}", req.title, req.version, req.status.label()),
+                cx,
+            ))
+            .child(self.render_comments(detail, cx))
+    }
+}
+
+impl IssueDetailPanel {
+    fn section(&self, title: &'static str, cx: &Context<Self>) -> gpui::Div {
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(div().font_semibold().text_color(cx.theme().foreground).child(title))
+    }
+
+    fn render_fields(&self, detail: &IssueDetail, cx: &Context<Self>) -> impl IntoElement {
+        let issue = &detail.issue;
+        let resolution = issue.resolved_at.map(|resolved_at| {
+            format!(
+                "{}（用时 {}）",
+                format_datetime(resolved_at),
+                format_duration(resolved_at - issue.created_at)
+            )
+        });
+        let fields = [
+            ("ID", issue.id.clone()),
+            ("标题", issue.title.clone()),
+            ("严重程度", issue.severity.label().to_string()),
+            ("状态", issue.status.label().to_string()),
+            ("责任人", issue.assignee.clone()),
+            ("创建时间", format_datetime(issue.created_at)),
+            ("解决时间", resolution.unwrap_or_else(|| "-".to_string())),
+            ("更新时间", issue.updated_at.map_or("-".to_string(), format_datetime)),
+            ("重开次数", issue.reopen_count.to_string()),
+        ];
+
+        self.section("基本信息", cx).children(fields.into_iter().map(|(label, value)| {
+            h_flex()
+                .w_full()
+                .gap_2()
+                .text_sm()
+                .child(div().w(px(80.)).text_color(cx.theme().muted_foreground).child(label))
+                .child(div().flex_1().text_color(cx.theme().foreground).child(value))
+        }))
+    }
+
+    fn render_history(&self, detail: &IssueDetail, cx: &Context<Self>) -> impl IntoElement {
+        // 没有同步或本地流转记录时，只能从创建时间推出初始状态
+        let events = if detail.history.is_empty() {
+            vec![IssueEvent {
+                issue_id: detail.issue.id.clone(),
+                from: None,
+                to: detail.issue.status,
+                actor: "-".to_string(),
+                at: detail.issue.created_at,
+            }]
+        } else {
+            detail.history.clone()
+        };
+        let last = events.len() - 1;
+
+        self.section("状态历史", cx).children(events.into_iter().enumerate().map(
+            |(index, event)| {
+                let change = match event.from {
+                    Some(from) => format!("{} → {}", from.label(), event.to.label()),
+                    None => format!("创建为「{}」", event.to.label()),
+                };
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .items_start()
+                    .child(
+                        v_flex()
+                            .items_center()
+                            .h_full()
+                            .child(div().mt_1().size(px(10.)).rounded_full().bg(rgb(0x3b82f6)))
+                            .when(index != last, |this| {
+                                this.child(
+                                    div().w(px(2.)).flex_1().min_h(px(24.)).bg(cx.theme().border),
+                                )
+                            }),
+                    )
+                    .child(v_flex().flex_1().pb_2().child(div().text_sm().child(change)).child(
+                        div().text_xs().text_color(cx.theme().muted_foreground).child(format!(
+                            "{} · {}",
+                            event.actor,
+                            format_datetime(event.at)
+                        )),
+                    ))
+            },
+        ))
+    }
+
+    fn render_links<T>(
+        &self,
+        kind: LinkKind,
+        links: &[Linked<T>],
+        describe: impl Fn(&T) -> String,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let title = match kind {
+            LinkKind::MergeRequest => "关联 MR",
+            LinkKind::Requirement => "关联需求",
+        };
+        let section = self.section(title, cx);
+        let section = if links.is_empty() {
+            section.child(div().text_sm().text_color(cx.theme().muted_foreground).child("暂无"))
+        } else {
+            section.children(links.iter().map(|link| {
+                let description = link
+                    .record
+                    .as_ref()
+                    .map_or("本地不存在该记录，同步后显示详情".to_string(), &describe);
+                let target_id = link.id.clone();
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .items_center()
+                    .text_sm()
+                    .child(div().font_semibold().child(link.id.clone()))
+                    .child(
+                        div().flex_1().text_color(cx.theme().muted_foreground).child(description),
+                    )
+                    .child(if link.manual {
+                        Button::new(SharedString::from(format!(
+                            "unlink-{}-{}",
+                            kind.code(),
+                            link.id
+                        )))
+                        .ghost()
+                        .xsmall()
+                        .icon(Icon::new(IconName::Close))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.unlink(kind, &target_id, cx);
+                        }))
+                        .into_any_element()
+                    } else {
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child("标题引用")
+                            .into_any_element()
+                    })
+            }))
+        };
+
+        section.child(
+            h_flex()
+                .gap_2()
+                .child(div().flex_1().child(Input::new(self.link_input(kind)).small()))
+                .child(
+                    Button::new(SharedString::from(format!("link-{}", kind.code())))
+                        .ghost()
+                        .small()
+                        .label(format!("关联{}", kind.label()))
+                        .icon(Icon::new(IconName::Plus))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.link(kind, window, cx);
+                        })),
+                ),
+        )
+    }
+
+    fn render_comments(&self, detail: &IssueDetail, cx: &Context<Self>) -> impl IntoElement {
+        self.section("评论", cx)
+            .children(detail.comments.iter().map(|comment| {
+                let comment_id = comment.id;
+                v_flex()
+                    .w_full()
+                    .p_2()
+                    .gap_1()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded_md()
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .items_center()
+                            .justify_between()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format!(
+                                "{} · {}",
+                                comment.author,
+                                format_datetime(comment.created_at)
+                            ))
+                            .when(comment.author == self.user, |this| {
+                                this.child(
+                                    Button::new(SharedString::from(format!(
+                                        "delete-comment-{}",
+                                        comment_id
+                                    )))
+                                    .ghost()
+                                    .xsmall()
+                                    .icon(Icon::new(IconName::Trash))
+                                    .on_click(cx.listener(
+                                        move |this, _, window, cx| {
+                                            this.delete_comment(comment_id, window, cx);
+                                        },
+                                    )),
+                                )
+                            }),
+                    )
+                    .child(div().text_sm().child(comment.body.clone()))
+            }))
+            .child(Input::new(&self.comment_input))
+            .child(h_flex().justify_end().child(
+                Button::new("add-comment-btn").primary().small().label("发表评论").on_click(
+                    cx.listener(|this, _, window, cx| {
+                        this.add_comment(window, cx);
+                    }),
+                ),
+            ))
+    }
+}
+
+fn push_error(message: String, window: &mut Window, cx: &mut App) {
+    struct IssueDetailError;
+    window.push_notification(Notification::new().message(message).id::<IssueDetailError>(), cx);
+}
@@ -0,0 +1,296 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, Window, div, prelude::FluentBuilder, rgb,
+};
+use gpui_component::{
+    WindowExt,
+    button::*,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+use tools::{
+    Coded as _, EndpointConfig, Issue, IssueDraft, IssueFormError, IssueStatus, RequestError,
+    RequestTool, Severity, Store,
+};
+
+use crate::{
+    AppState,
+    view::{FormSaved, PersonInput, RecordForm, field_error, form_field, status_select},
+};
+
+/// Dialog body for creating an issue, or editing one when `existing` is set.
+pub struct IssueForm {
+    store: Store,
+    request: RequestTool,
+    user: String,
+    existing: Option<Issue>,
+    /// The DTS endpoint, only when it accepts new issues.
+    endpoint: Option<EndpointConfig>,
+    id_input: Entity<InputState>,
+    title_input: Entity<InputState>,
+    assignee_input: Entity<PersonInput>,
+    severity_select: Entity<SelectState<Vec<SharedString>>>,
+    status_select: Entity<SelectState<Vec<SharedString>>>,
+    draft: IssueDraft,
+    push: bool,
+    errors: Vec<IssueFormError>,
+    push_error: Option<String>,
+    push_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<FormSaved<Issue>> for IssueForm {}
+
+impl RecordForm for IssueForm {
+    type Record = Issue;
+
+    const NOUN: &'static str = "问题单";
+    const WIDTH: f32 = 560.;
+
+    fn new(existing: Option<Issue>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let draft = existing.as_ref().map_or_else(IssueDraft::new, IssueDraft::edit);
+        let id_input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("留空则生成本地编号");
+            input.set_value(draft.id.clone(), window, cx);
+            input
+        });
+        let title_input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("问题描述");
+            input.set_value(draft.title.clone(), window, cx);
+            input
+        });
+        let assignee_input = cx.new(|cx| {
+            PersonInput::new("输入姓名或从建议中选择", draft.assignee.clone(), window, cx)
+        });
+        let severity_select = status_select(Severity::ALL, draft.severity, window, cx);
+        let statuses = IssueDraft::statuses(existing.as_ref());
+        let status_select = status_select(&statuses, draft.status, window, cx);
+
+        let subscriptions = vec![
+            cx.subscribe_in(&id_input, window, Self::on_input_event),
+            cx.subscribe_in(&title_input, window, Self::on_input_event),
+            cx.subscribe_in(
+                &severity_select,
+                window,
+                |this, _, event: &SelectEvent<Vec<SharedString>>, _, cx| {
+                    let SelectEvent::Confirm(value) = event;
+                    this.draft.severity = value.as_deref().and_then(Severity::parse);
+                    cx.notify();
+                },
+            ),
+            cx.subscribe_in(
+                &status_select,
+                window,
+                |this, _, event: &SelectEvent<Vec<SharedString>>, _, cx| {
+                    let SelectEvent::Confirm(value) = event;
+                    this.draft.status = value.as_deref().and_then(IssueStatus::parse);
+                    cx.notify();
+                },
+            ),
+        ];
+
+        let state = AppState::global(cx);
+        let endpoint = state.sync_config.dts.clone().filter(|dts| dts.create_url.is_some());
+
+        Self {
+            store: state.store.clone(),
+            request: state.request.clone(),
+            user: state.user.clone(),
+            existing,
+            endpoint,
+            id_input,
+            title_input,
+            assignee_input,
+            severity_select,
+            status_select,
+            draft,
+            push: false,
+            errors: Vec::new(),
+            push_error: None,
+            push_task: None,
+            _subscriptions: subscriptions,
+        }
+    }
+}
+
+impl IssueForm {
+    fn on_input_event(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value().to_string();
+            if state == &self.id_input {
+                self.draft.id = value;
+            } else if state == &self.title_input {
+                self.draft.title = value;
+            }
+            cx.notify();
+        }
+    }
+
+    fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.push_task.is_some() {
+            return;
+        }
+        self.draft.assignee = self.assignee_input.read(cx).value(cx);
+        let issue = match self.draft.validate(self.existing.as_ref(), |id| self.issue_exists(id)) {
+            Ok(issue) => issue,
+            Err(errors) => {
+                self.errors = errors;
+                cx.notify();
+                return;
+            },
+        };
+        self.errors.clear();
+        self.push_error = None;
+
+        let endpoint = self.endpoint.clone().filter(|_| self.push && self.existing.is_none());
+        let Some(endpoint) = endpoint else {
+            self.save(issue, window, cx);
+            return;
+        };
+
+        // 先提交到 DTS，成功后再以 DTS 分配的编号保存到本地
+        let request = self.request.clone();
+        self.push_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = request.create(&endpoint, &issue).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.push_task = None;
+                match result {
+                    Ok(remote_id) if this.issue_exists(&remote_id) => {
+                        tracing::error!("Remote issue id {} already exists locally", remote_id);
+                        this.push_error = Some(format!(
+                            "已提交到 DTS，但返回的编号 {} 在本地已存在，未保存到本地",
+                            remote_id
+                        ));
+                        cx.notify();
+                    },
+                    Ok(remote_id) => {
+                        let mut issue = issue;
+                        issue.id = remote_id;
+                        this.save(issue, window, cx);
+                    },
+                    Err(err @ RequestError::Schema { .. }) => {
+                        // 已在 DTS 创建，本地不保存，下次同步时拉取，避免重复
+                        tracing::error!("Pushed issue has no usable remote id: {}", err);
+                        this.push_error = Some(format!(
+                            "已提交到 DTS，但未能读取编号，未保存到本地，同步后可见: {}",
+                            err
+                        ));
+                        cx.notify();
+                    },
+                    Err(err) => {
+                        tracing::error!("Failed to push issue: {}", err);
+                        this.push_error = Some(format!("提交到 DTS 失败，未保存: {}", err));
+                        cx.notify();
+                    },
+                }
+            });
+        }));
+        cx.notify();
+    }
+
+    fn issue_exists(&self, id: &str) -> bool {
+        self.store.issues().get(id).map(|issue| issue.is_some()).unwrap_or_else(|err| {
+            tracing::error!("Failed to look up issue {}: {:?}", id, err);
+            false
+        })
+    }
+
+    fn save(&mut self, issue: Issue, window: &mut Window, cx: &mut Context<Self>) {
+        struct IssueSaved;
+        let message = match self.store.save_issue(&issue, self.existing.as_ref(), &self.user) {
+            Ok(()) => {
+                let message = format!("已保存问题单 #{}", issue.id);
+                cx.emit(FormSaved(issue));
+                window.close_dialog(cx);
+                message
+            },
+            Err(err) => {
+                tracing::error!("Failed to save issue {}: {:?}", issue.id, err);
+                format!("保存问题单失败: {}", err)
+            },
+        };
+        window.push_notification(Notification::new().message(message).id::<IssueSaved>(), cx);
+    }
+}
+
+impl IssueForm {
+    fn field(
+        &self,
+        label: &'static str,
+        required: bool,
+        input: impl IntoElement,
+        cx: &App,
+    ) -> impl IntoElement {
+        form_field(label, required, field_error(&self.errors, label), input, cx)
+    }
+}
+
+impl Render for IssueForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let creating = self.existing.is_none();
+        let pushing = self.push_task.is_some();
+
+        v_flex()
+            .w_full()
+            .gap_3()
+            .child(self.field("ID", false, Input::new(&self.id_input).disabled(!creating), cx))
+            .child(self.field("标题", true, Input::new(&self.title_input), cx))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "严重程度",
+                        true,
+                        Select::new(&self.severity_select).placeholder("选择严重程度"),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "状态",
+                        true,
+                        Select::new(&self.status_select).placeholder("选择状态"),
+                        cx,
+                    ))),
+            )
+            .child(self.field("责任人", true, self.assignee_input.clone(), cx))
+            .when(creating, |this| {
+                let label = if self.endpoint.is_some() {
+                    "同时提交到 DTS"
+                } else {
+                    "同时提交到 DTS（未配置创建地址）"
+                };
+                this.child(
+                    Checkbox::new("push-issue")
+                        .label(label)
+                        .checked(self.push)
+                        .disabled(self.endpoint.is_none())
+                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                            this.push = *checked;
+                            cx.notify();
+                        })),
+                )
+            })
+            .when_some(self.push_error.clone(), |this, error| {
+                this.child(div().text_sm().text_color(rgb(0xef4444)).child(error))
+            })
+            .child(
+                h_flex().w_full().justify_end().gap_2().child(
+                    Button::new("save-issue-btn")
+                        .primary()
+                        .label(if pushing { "正在提交..." } else { "保存" })
+                        .disabled(pushing)
+                        .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
+                ),
+            )
+    }
+}
@@ -0,0 +1,312 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, Window, div, prelude::FluentBuilder, px, rgb,
+};
+use gpui_component::{
+    IndexPath, WindowExt,
+    button::*,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputState},
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+use tools::{
+    Job, JobDraft, JobEndpoints, JobFormError, JobParams, JobTemplate, RequestTool, Store,
+};
+
+use crate::{AppState, view::form_field};
+
+pub enum JobFormEvent {
+    Launched(Job),
+}
+
+/// Dialog body for launching a job, optionally prefilled from a template.
+pub struct JobForm {
+    store: Store,
+    request: RequestTool,
+    user: String,
+    endpoints: Option<JobEndpoints>,
+    templates: Vec<JobTemplate>,
+    template_select: Entity<SelectState<Vec<SharedString>>>,
+    /// Ids of the virtual environments a job can run on.
+    env_ids: Vec<SharedString>,
+    env_select: Entity<SelectState<Vec<SharedString>>>,
+    env_id: Option<String>,
+    name_input: Entity<InputState>,
+    branch_input: Entity<InputState>,
+    suite_input: Entity<InputState>,
+    tags_input: Entity<InputState>,
+    save_template: bool,
+    errors: Vec<JobFormError>,
+    launch_error: Option<String>,
+    launch_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<JobFormEvent> for JobForm {}
+
+impl JobForm {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let state = AppState::global(cx);
+        let store = state.store.clone();
+        let request = state.request.clone();
+        let user = state.user.clone();
+        let endpoints = state.sync_config.jobs.clone();
+        let templates = store.job_templates(&user).unwrap_or_else(|err| {
+            tracing::error!("Failed to load job templates: {:?}", err);
+            Vec::new()
+        });
+
+        let env_ids: Vec<SharedString> = store
+            .virtual_envs()
+            .all()
+            .unwrap_or_else(|err| {
+                tracing::error!("Failed to load virtual environments: {:?}", err);
+                Vec::new()
+            })
+            .into_iter()
+            .map(|env| SharedString::from(env.id))
+            .collect();
+
+        let names = templates.iter().map(|template| SharedString::from(template.name.clone()));
+        let template_select = cx.new(|cx| SelectState::new(names.collect(), None, window, cx));
+        let env_select = cx.new(|cx| SelectState::new(env_ids.clone(), None, window, cx));
+        let env_subscription = cx.subscribe_in(
+            &env_select,
+            window,
+            |this, _, event: &SelectEvent<Vec<SharedString>>, _, cx| {
+                let SelectEvent::Confirm(value) = event;
+                this.env_id = value.as_ref().map(ToString::to_string);
+                cx.notify();
+            },
+        );
+        let template_subscription = cx.subscribe_in(
+            &template_select,
+            window,
+            |this, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                let SelectEvent::Confirm(value) = event;
+                let template = value.as_deref().and_then(|name| {
+                    this.templates.iter().find(|template| template.name == name).cloned()
+                });
+                if let Some(template) = template {
+                    this.apply_template(&template, window, cx);
+                }
+            },
+        );
+        let input = |placeholder: &'static str, window: &mut Window, cx: &mut Context<Self>| {
+            cx.new(|cx| InputState::new(window, cx).placeholder(placeholder))
+        };
+
+        Self {
+            store,
+            request,
+            user,
+            endpoints,
+            templates,
+            template_select,
+            env_ids,
+            env_select,
+            env_id: None,
+            name_input: input("例如：主干夜间回归", window, cx),
+            branch_input: input("例如：master", window, cx),
+            suite_input: input("例如：smoke", window, cx),
+            tags_input: input("多个标签用逗号或空格分隔", window, cx),
+            save_template: false,
+            errors: Vec::new(),
+            launch_error: None,
+            launch_task: None,
+            _subscriptions: vec![template_subscription, env_subscription],
+        }
+    }
+
+    fn apply_template(
+        &mut self,
+        template: &JobTemplate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let draft = JobDraft::from_template(template);
+        // 模板里的环境已不存在时清空，需重新选择
+        let env = self.env_ids.iter().position(|id| *id == draft.params.env_id);
+        self.env_id = env.map(|_| draft.params.env_id.clone());
+        self.env_select.update(cx, |select, cx| {
+            select.set_selected_index(env.map(IndexPath::new), window, cx)
+        });
+        for (input, value) in [
+            (&self.name_input, draft.name),
+            (&self.branch_input, draft.params.branch),
+            (&self.suite_input, draft.params.suite),
+            (&self.tags_input, draft.params.tags.join(", ")),
+        ] {
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+        self.errors.clear();
+        cx.notify();
+    }
+
+    fn draft(&self, cx: &App) -> JobDraft {
+        let value = |input: &Entity<InputState>| input.read(cx).value().to_string();
+        JobDraft {
+            name: value(&self.name_input),
+            params: JobParams {
+                branch: value(&self.branch_input),
+                env_id: self.env_id.clone().unwrap_or_default(),
+                suite: value(&self.suite_input),
+                tags: JobParams::parse_tags(&value(&self.tags_input)),
+            },
+        }
+    }
+
+    fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (name, params) = match self.draft(cx).validate() {
+            Ok(valid) => valid,
+            Err(errors) => {
+                self.errors = errors;
+                cx.notify();
+                return;
+            },
+        };
+        self.errors.clear();
+        self.launch_error = None;
+
+        // 模板在拉起前保存，拉起失败也不必重填参数
+        if self.save_template
+            && let Err(err) = self.store.save_job_template(&self.user, &name, &params)
+        {
+            tracing::error!("Failed to save job template {}: {:?}", name, err);
+        }
+        let Some(endpoints) = self.endpoints.clone() else {
+            self.launch_error = Some("未配置 Job 服务地址，无法拉起".to_string());
+            cx.notify();
+            return;
+        };
+
+        let request = self.request.clone();
+        self.launch_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = request.launch_job(&endpoints, &name, &params).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.launch_task = None;
+                match result {
+                    Ok(job) => this.save(job, window, cx),
+                    Err(err) => {
+                        tracing::error!("Failed to launch job: {}", err);
+                        this.launch_error = Some(format!("拉起 Job 失败: {}", err));
+                        cx.notify();
+                    },
+                }
+            });
+        }));
+        cx.notify();
+    }
+
+    fn save(&mut self, job: Job, window: &mut Window, cx: &mut Context<Self>) {
+        struct JobLaunched;
+        if let Err(err) = self.store.insert_job(&job) {
+            tracing::error!("Failed to save job {}: {}", job.id, err);
+            self.launch_error = Some(format!("Job 已拉起，但保存到本地失败: {}", err));
+            cx.notify();
+            return;
+        }
+        let message = format!("已拉起 Job {}", job.name);
+        cx.emit(JobFormEvent::Launched(job));
+        window.close_dialog(cx);
+        window.push_notification(Notification::new().message(message).id::<JobLaunched>(), cx);
+    }
+
+    fn field(
+        &self,
+        label: &'static str,
+        required: bool,
+        input: impl IntoElement,
+        cx: &App,
+    ) -> impl IntoElement {
+        let error = self.errors.iter().find(|error| error.field() == label);
+        form_field(label, required, error.map(ToString::to_string), input, cx)
+    }
+}
+
+impl Render for JobForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let launching = self.launch_task.is_some();
+
+        v_flex()
+            .w_full()
+            .gap_3()
+            .child(self.field(
+                "模板",
+                false,
+                Select::new(&self.template_select).placeholder(if self.templates.is_empty() {
+                    "暂无保存的模板"
+                } else {
+                    "选择模板填入参数"
+                }),
+                cx,
+            ))
+            .child(self.field("名称", true, Input::new(&self.name_input), cx))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "分支",
+                        true,
+                        Input::new(&self.branch_input),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "环境",
+                        true,
+                        Select::new(&self.env_select).placeholder(if self.env_ids.is_empty() {
+                            "暂无虚拟环境"
+                        } else {
+                            "选择虚拟环境"
+                        }),
+                        cx,
+                    ))),
+            )
+            .child(self.field("测试套", true, Input::new(&self.suite_input), cx))
+            .child(self.field("标签", false, Input::new(&self.tags_input), cx))
+            .child(
+                Checkbox::new("save-job-template")
+                    .label("以名称保存为模板")
+                    .checked(self.save_template)
+                    .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                        this.save_template = *checked;
+                        cx.notify();
+                    })),
+            )
+            .when_some(self.launch_error.clone(), |this, error| {
+                this.child(div().text_sm().text_color(rgb(0xef4444)).child(error))
+            })
+            .child(
+                h_flex().w_full().justify_end().child(
+                    Button::new("launch-job-btn")
+                        .primary()
+                        .label(if launching { "正在拉起..." } else { "拉起" })
+                        .disabled(launching)
+                        .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
+                ),
+            )
+    }
+}
+
+/// Open the launch form in a dialog; `on_launched` runs on the opening view
+/// once the job is launched.
+pub fn open_job_form<V: 'static>(
+    on_launched: fn(&mut V, &Job, &mut Context<V>),
+    window: &mut Window,
+    cx: &mut Context<V>,
+) {
+    let form = cx.new(|cx| JobForm::new(window, cx));
+    cx.subscribe(&form, move |this, _, event: &JobFormEvent, cx| {
+        let JobFormEvent::Launched(job) = event;
+        on_launched(this, job, cx);
+        cx.notify();
+    })
+    .detach();
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog.title("拉起 Job").width(px(560.)).child(form.clone())
+    });
+}
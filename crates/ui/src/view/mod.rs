@@ -1,11 +1,34 @@
+mod chart;
 mod codehub;
 mod dts;
 mod excel;
+mod export;
+mod form;
 mod hive;
-mod request;
+mod import;
+mod issue_detail;
+mod issue_form;
+mod job_form;
+mod release_form;
+mod report;
 mod requirement;
+mod requirement_form;
+mod reservation_form;
+mod sync;
+pub use chart::*;
 pub use codehub::*;
 pub use dts::*;
 pub use excel::*;
+pub use export::*;
+pub use form::*;
 pub use hive::*;
+pub use import::*;
+pub use issue_detail::*;
+pub use issue_form::*;
+pub use job_form::*;
+pub use release_form::*;
+pub use report::*;
 pub use requirement::*;
+pub use requirement_form::*;
+pub use reservation_form::*;
+pub use sync::*;
@@ -0,0 +1,207 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled,
+    Subscription, Window, div, px,
+};
+use gpui_component::{
+    WindowExt,
+    button::*,
+    calendar::Date,
+    date_picker::{DatePicker, DatePickerEvent, DatePickerState},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    notification::Notification,
+    v_flex,
+};
+use tools::{Release, ReleaseDraft, ReleaseFormError, Store};
+
+use crate::{AppState, utils::picked_date, view::form_field};
+
+pub enum ReleaseFormEvent {
+    Saved(Release),
+}
+
+/// Dialog body for creating a release, or editing one when `existing` is
+/// set. The version of an existing release can't change since requirements
+/// are grouped by it.
+pub struct ReleaseForm {
+    store: Store,
+    existing: Option<Release>,
+    version_input: Entity<InputState>,
+    name_input: Entity<InputState>,
+    description_input: Entity<InputState>,
+    date_picker: Entity<DatePickerState>,
+    draft: ReleaseDraft,
+    errors: Vec<ReleaseFormError>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<ReleaseFormEvent> for ReleaseForm {}
+
+impl ReleaseForm {
+    pub fn new(existing: Option<Release>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let draft = existing.as_ref().map(ReleaseDraft::edit).unwrap_or_default();
+        let mut input = |placeholder: &'static str, value: &str| {
+            cx.new(|cx| {
+                let mut input = InputState::new(window, cx).placeholder(placeholder);
+                input.set_value(value.to_string(), window, cx);
+                input
+            })
+        };
+        let version_input = input("例如 v1.2.0", &draft.version);
+        let name_input = input("版本名称，可不填", &draft.name);
+        let description_input = input("版本说明，可不填", &draft.description);
+        let date_picker = cx.new(|cx| {
+            let mut picker = DatePickerState::new(window, cx);
+            picker.set_date(Date::Single(draft.release_date), window, cx);
+            picker
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(&version_input, window, Self::on_input_event),
+            cx.subscribe_in(&name_input, window, Self::on_input_event),
+            cx.subscribe_in(&description_input, window, Self::on_input_event),
+            cx.subscribe(&date_picker, |this, _, ev, cx| match ev {
+                DatePickerEvent::Change(date) => {
+                    this.draft.release_date = picked_date(date);
+                    cx.notify();
+                },
+            }),
+        ];
+
+        Self {
+            store: AppState::global(cx).store.clone(),
+            existing,
+            version_input,
+            name_input,
+            description_input,
+            date_picker,
+            draft,
+            errors: Vec::new(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn on_input_event(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value().to_string();
+            if state == &self.version_input {
+                self.draft.version = value;
+            } else if state == &self.name_input {
+                self.draft.name = value;
+            } else if state == &self.description_input {
+                self.draft.description = value;
+            }
+            cx.notify();
+        }
+    }
+
+    fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct ReleaseSaved;
+        let releases = self.store.releases();
+        let existing = releases.all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load releases: {:?}", err);
+            Vec::new()
+        });
+        let release = match self.draft.validate(self.existing.as_ref(), &existing) {
+            Ok(release) => release,
+            Err(errors) => {
+                self.errors = errors;
+                cx.notify();
+                return;
+            },
+        };
+        self.errors.clear();
+
+        let message = match releases.upsert(&release) {
+            Ok(()) => {
+                let message = format!("已保存版本 {}", release.id);
+                cx.emit(ReleaseFormEvent::Saved(release));
+                window.close_dialog(cx);
+                message
+            },
+            Err(err) => {
+                tracing::error!("Failed to save release {}: {:?}", release.id, err);
+                format!("保存版本失败: {}", err)
+            },
+        };
+        window.push_notification(Notification::new().message(message).id::<ReleaseSaved>(), cx);
+    }
+
+    fn field(
+        &self,
+        label: &'static str,
+        required: bool,
+        input: impl IntoElement,
+        cx: &App,
+    ) -> impl IntoElement {
+        let error = self.errors.iter().find(|error| error.field() == label);
+        form_field(label, required, error.map(ToString::to_string), input, cx)
+    }
+}
+
+impl Render for ReleaseForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let creating = self.existing.is_none();
+
+        v_flex()
+            .w_full()
+            .gap_3()
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "版本号",
+                        true,
+                        Input::new(&self.version_input).disabled(!creating),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "发布日期",
+                        false,
+                        DatePicker::new(&self.date_picker).cleanable(true),
+                        cx,
+                    ))),
+            )
+            .child(self.field("名称", false, Input::new(&self.name_input), cx))
+            .child(self.field("说明", false, Input::new(&self.description_input), cx))
+            .child(
+                h_flex().w_full().justify_end().child(
+                    Button::new("save-release-btn")
+                        .primary()
+                        .label("保存")
+                        .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
+                ),
+            )
+    }
+}
+
+/// Open the release form in a dialog; `on_saved` runs on the opening view
+/// once the release is saved.
+pub fn open_release_form<V: 'static>(
+    existing: Option<Release>,
+    on_saved: fn(&mut V, &Release, &mut Context<V>),
+    window: &mut Window,
+    cx: &mut Context<V>,
+) {
+    let title = match &existing {
+        Some(release) => format!("编辑版本 {}", release.id),
+        None => "添加版本".to_string(),
+    };
+    let form = cx.new(|cx| ReleaseForm::new(existing, window, cx));
+    cx.subscribe(&form, move |this, _, event: &ReleaseFormEvent, cx| {
+        let ReleaseFormEvent::Saved(release) = event;
+        on_saved(this, release, cx);
+        cx.notify();
+    })
+    .detach();
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog.title(title.clone()).width(px(520.)).child(form.clone())
+    });
+}
@@ -0,0 +1,140 @@
+use chrono::{Days, Local, NaiveDate};
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement, Styled, Subscription, Window, div,
+};
+use gpui_component::{
+    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    button::*,
+    date_picker::{DatePicker, DatePickerEvent, DatePickerState},
+    h_flex, v_flex,
+};
+use tools::{Chart, CodeHubTool, DtsTool, RequirementTool, Store};
+
+use crate::{AppState, utils::picked_range, view::ChartCard};
+
+/// How many weeks the report covers when the view opens.
+const DEFAULT_PERIOD_WEEKS: u64 = 8;
+
+/// Weekly trends of MRs, issues and requirements over a picked date range.
+pub struct ReportView {
+    store: Store,
+    date_range: (NaiveDate, NaiveDate),
+    date_range_picker: Entity<DatePickerState>,
+    charts: Vec<Chart>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ReportView {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let today = Local::now().date_naive();
+        let date_range = (today - Days::new(DEFAULT_PERIOD_WEEKS * 7 - 1), today);
+        let date_range_picker = cx.new(|cx| {
+            let mut picker = DatePickerState::new(window, cx);
+            picker.set_date(date_range, window, cx);
+            picker
+        });
+
+        let subscriptions = vec![cx.subscribe(&date_range_picker, |this, _, ev, cx| match ev {
+            DatePickerEvent::Change(date) => {
+                if let Some(range) = picked_range(date) {
+                    this.date_range = range;
+                    this.reload();
+                    cx.notify();
+                }
+            },
+        })];
+
+        let mut this = Self {
+            store: AppState::global(cx).store.clone(),
+            date_range,
+            date_range_picker,
+            charts: Vec::new(),
+            _subscriptions: subscriptions,
+        };
+        this.reload();
+        this
+    }
+
+    fn reload(&mut self) {
+        let (start, end) = self.date_range;
+        let mrs = self.store.merge_requests().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load merge requests: {:?}", err);
+            Vec::new()
+        });
+        let issues = self.store.issues().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load issues: {:?}", err);
+            Vec::new()
+        });
+        let requirements = self.store.requirements().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load requirements: {:?}", err);
+            Vec::new()
+        });
+
+        self.charts = vec![
+            CodeHubTool::workload(&mrs.iter().collect::<Vec<_>>(), start, end).lines_chart(),
+            DtsTool::weekly_trend(&issues.iter().collect::<Vec<_>>(), start, end),
+            RequirementTool::burndown(&requirements.iter().collect::<Vec<_>>(), start, end),
+        ];
+    }
+}
+
+impl Render for ReportView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                // 标题区
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(Icon::new(IconName::ChartPie).size_6())
+                            .child(
+                                div()
+                                    .text_2xl()
+                                    .font_bold()
+                                    .text_color(cx.theme().foreground)
+                                    .child("趋势报表"),
+                            ),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(DatePicker::new(&self.date_range_picker))
+                            .child(
+                                Button::new("refresh-report-btn")
+                                    .ghost()
+                                    .icon(Icon::new(IconName::RefreshCw))
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.reload();
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                // 图表区
+                v_flex()
+                    .id("report-charts")
+                    .flex_1()
+                    .w_full()
+                    .gap_4()
+                    .overflow_y_scroll()
+                    .children(self.charts.iter().enumerate().map(|(index, chart)| {
+                        ChartCard::new(format!("chart-{}", index), chart.clone())
+                    })),
+            )
+    }
+}
@@ -1,33 +1,63 @@
-use chrono::{DateTime, Utc};
+use chrono::{Datelike as _, Local, NaiveDate};
 use gpui::{
-    App, AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
-    Styled, Subscription, Window, div, rgb,
+    App, AppContext, Context, CursorStyle, Entity, InteractiveElement, IntoElement, MouseButton,
+    MouseDownEvent, MouseMoveEvent, ParentElement, Pixels, Render, Rgba, SharedString,
+    StatefulInteractiveElement, Styled, Subscription, Window, div, prelude::FluentBuilder, px,
+    relative, rgb,
 };
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Sizable, StyledExt,
+    ActiveTheme, Icon, IconName, IndexPath, Sizable, StyledExt, WindowExt,
     button::*,
     h_flex,
     input::{Input, InputEvent, InputState},
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
     v_flex,
 };
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Requirement {
-    pub id: String,
-    pub title: String,
-    pub version: String,
-    pub test_cycle: String,
-    pub start_date: DateTime<Utc>,
-    pub end_date: DateTime<Utc>,
-    pub status: String,
-    pub owner: String,
+use tools::{
+    Coded as _, Exportable as _, GanttEdge, Record as _, Release, ReleaseProgress, Requirement,
+    RequirementStatus, RequirementTool, Store, SyncSource, Version,
+};
+
+use crate::{
+    AppState,
+    view::{
+        RequirementForm, SyncEvent, SyncPanel, export_to_excel, import_from_excel, open_form,
+        open_release_form,
+    },
+};
+
+/// Look-ahead windows offered for the "due soon" section, in days.
+const DUE_WINDOWS: [u64; 4] = [3, 7, 14, 30];
+
+/// Gantt chart geometry.
+const GANTT_DAY_WIDTH: f32 = 28.;
+const GANTT_ROW_HEIGHT: f32 = 36.;
+const GANTT_LABEL_WIDTH: f32 = 200.;
+const GANTT_HANDLE_WIDTH: f32 = 6.;
+
+/// A Gantt bar being dragged: which requirement, which part of the bar, where
+/// the drag started and how many whole days it has moved so far.
+struct GanttDrag {
+    id: String,
+    edge: GanttEdge,
+    origin: Pixels,
+    days: i64,
 }
 
 pub struct RequirementView {
+    store: Store,
     requirements: Vec<Requirement>,
+    releases: Vec<Release>,
+    /// 只显示该版本的需求
+    selected_version: Option<String>,
+    show_gantt: bool,
+    gantt_drag: Option<GanttDrag>,
+    sync_panel: Entity<SyncPanel>,
     search_input: Entity<InputState>,
     search_value: Option<String>,
+    due_select: Entity<SelectState<Vec<SharedString>>>,
+    due_days: u64,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -39,53 +69,97 @@ impl RequirementView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("搜索需求..."));
 
-        // 添加测试数据
-        let requirements = vec![
-            Requirement {
-                id: "REQ001".to_string(),
-                title: "用户认证系统".to_string(),
-                version: "v1.0".to_string(),
-                test_cycle: "2周".to_string(),
-                start_date: Utc::now(),
-                end_date: Utc::now(),
-                status: "进行中".to_string(),
-                owner: "张三".to_string(),
-            },
-            Requirement {
-                id: "REQ002".to_string(),
-                title: "支付功能模块".to_string(),
-                version: "v2.0".to_string(),
-                test_cycle: "3周".to_string(),
-                start_date: Utc::now(),
-                end_date: Utc::now(),
-                status: "进行中".to_string(),
-                owner: "李四".to_string(),
-            },
-            Requirement {
-                id: "REQ003".to_string(),
-                title: "数据分析平台".to_string(),
-                version: "v1.5".to_string(),
-                test_cycle: "4周".to_string(),
-                start_date: Utc::now(),
-                end_date: Utc::now(),
-                status: "已完成".to_string(),
-                owner: "王五".to_string(),
-            },
-            Requirement {
-                id: "REQ004".to_string(),
-                title: "移动端适配".to_string(),
-                version: "v1.0".to_string(),
-                test_cycle: "2周".to_string(),
-                start_date: Utc::now(),
-                end_date: Utc::now(),
-                status: "延期".to_string(),
-                owner: "赵六".to_string(),
-            },
+        let sync_panel = SyncPanel::view(SyncSource::Requirement, cx);
+        let due_days = 7;
+        let due_select = cx.new(|cx| {
+            let labels = DUE_WINDOWS.into_iter().map(Self::due_label).collect();
+            let selected = DUE_WINDOWS.iter().position(|days| *days == due_days);
+            SelectState::new(labels, selected.map(IndexPath::new), window, cx)
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(&search_input, window, Self::on_input_event),
+            cx.subscribe_in(
+                &due_select,
+                window,
+                |this, _, event: &SelectEvent<Vec<SharedString>>, _, cx| {
+                    let SelectEvent::Confirm(value) = event;
+                    if let Some(days) = DUE_WINDOWS
+                        .into_iter()
+                        .find(|days| value.as_deref() == Some(&*Self::due_label(*days)))
+                    {
+                        this.due_days = days;
+                    }
+                    cx.notify();
+                },
+            ),
+            cx.subscribe(&sync_panel, |this, _, _: &SyncEvent, cx| {
+                this.reload();
+                cx.notify();
+            }),
         ];
 
-        let subscriptions = vec![cx.subscribe_in(&search_input, window, Self::on_input_event)];
+        let mut this = Self {
+            store: AppState::global(cx).store.clone(),
+            requirements: Vec::new(),
+            releases: Vec::new(),
+            selected_version: None,
+            show_gantt: false,
+            gantt_drag: None,
+            sync_panel,
+            search_input,
+            search_value: None,
+            due_select,
+            due_days,
+            _subscriptions: subscriptions,
+        };
+        this.reload();
+        this
+    }
+
+    fn due_label(days: u64) -> SharedString {
+        format!("{} 天内", days).into()
+    }
+
+    fn today() -> NaiveDate {
+        Local::now().date_naive()
+    }
 
-        Self { requirements, search_input, search_value: None, _subscriptions: subscriptions }
+    /// 重新加载需求，未同步的需求按今天的日期显示派生状态
+    fn reload(&mut self) {
+        self.requirements = self.store.requirements_on(Self::today()).unwrap_or_else(|err| {
+            tracing::error!("Failed to load requirements: {:?}", err);
+            Vec::new()
+        });
+        self.releases = self.store.releases().all().unwrap_or_else(|err| {
+            tracing::error!("Failed to load releases: {:?}", err);
+            Vec::new()
+        });
+    }
+
+    fn toggle_version(&mut self, version: String, cx: &mut Context<Self>) {
+        self.selected_version =
+            if self.selected_version.as_ref() == Some(&version) { None } else { Some(version) };
+        cx.notify();
+    }
+
+    fn confirm_delete_release(&mut self, id: String, window: &mut Window, cx: &mut Context<Self>) {
+        let message = format!("确定删除版本 {}？其下的需求不会被删除。", id);
+        let view = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _, _| {
+            let view = view.clone();
+            let id = id.clone();
+            dialog.title("删除版本").confirm().child(message.clone()).on_ok(move |_, _, cx| {
+                _ = view.update(cx, |this, cx| {
+                    if let Err(err) = this.store.releases().delete(&id) {
+                        tracing::error!("Failed to delete release {}: {:?}", id, err);
+                    }
+                    this.reload();
+                    cx.notify();
+                });
+                true
+            })
+        });
     }
 
     fn on_input_event(
@@ -105,24 +179,37 @@ impl RequirementView {
             _ => {},
         };
     }
-}
 
-impl Render for RequirementView {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    /// 根据搜索查询过滤需求
+    fn searched_requirements(&self, cx: &App) -> Vec<&Requirement> {
         let search_query = self.search_input.read(cx).value().trim().to_lowercase();
-
-        // 根据搜索查询过滤需求
-        let filtered_requirements: Vec<&Requirement> = self
-            .requirements
+        self.requirements
             .iter()
             .filter(|req: &&Requirement| {
-                search_query.is_empty()
-                    || req.title.to_lowercase().contains(&search_query)
-                    || req.owner.to_lowercase().contains(&search_query)
-                    || req.id.to_lowercase().contains(&search_query)
-                    || req.version.to_lowercase().contains(&search_query)
+                req.matches(&search_query) || req.version.to_lowercase().contains(&search_query)
             })
-            .collect();
+            .collect()
+    }
+
+    /// 在搜索结果上再按所选版本过滤
+    fn filtered_requirements(&self, cx: &App) -> Vec<&Requirement> {
+        let mut requirements = self.searched_requirements(cx);
+        if let Some(version) = &self.selected_version {
+            requirements.retain(|req| RequirementTool::same_version(&req.version, version));
+        }
+        requirements
+    }
+
+    fn handle_export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let requirements = self.filtered_requirements(cx).into_iter().cloned().collect();
+        export_to_excel::<Requirement>(requirements, "需求导出.xlsx", window, cx);
+    }
+}
+
+impl Render for RequirementView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let search_query = self.search_input.read(cx).value().trim().to_lowercase();
+        let filtered_requirements = self.filtered_requirements(cx);
 
         v_flex()
             .size_full()
@@ -144,45 +231,14 @@ impl Render for RequirementView {
                         ),
                 ),
             )
+            .child(self.sync_panel.clone())
             .child(
                 // 统计卡片区
-                h_flex()
-                    .w_full()
-                    .gap_4()
-                    .child(self.stat_card("总需求", &filtered_requirements.len().to_string(), cx))
-                    .child(
-                        self.stat_card(
-                            "进行中",
-                            &filtered_requirements
-                                .iter()
-                                .filter(|r| r.status == "进行中")
-                                .count()
-                                .to_string(),
-                            cx,
-                        ),
-                    )
-                    .child(
-                        self.stat_card(
-                            "已完成",
-                            &filtered_requirements
-                                .iter()
-                                .filter(|r| r.status == "已完成")
-                                .count()
-                                .to_string(),
-                            cx,
-                        ),
-                    )
-                    .child(
-                        self.stat_card(
-                            "延期",
-                            &filtered_requirements
-                                .iter()
-                                .filter(|r| r.status == "延期")
-                                .count()
-                                .to_string(),
-                            cx,
-                        ),
-                    ),
+                h_flex().w_full().gap_4().children(
+                    Requirement::summary(&filtered_requirements)
+                        .into_iter()
+                        .map(|(label, value)| self.stat_card(label, &value.to_string(), cx)),
+                ),
             )
             .child(
                 // 操作区
@@ -201,22 +257,89 @@ impl Render for RequirementView {
                         Button::new("add-req-btn")
                             .primary()
                             .label("添加需求")
-                            .icon(Icon::new(IconName::Plus)),
+                            .icon(Icon::new(IconName::Plus))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                open_form::<RequirementForm, _>(
+                                    None,
+                                    |this: &mut Self, _, _| this.reload(),
+                                    window,
+                                    cx,
+                                );
+                            })),
                     )
                     .child(
                         Button::new("import-req-btn")
                             .ghost()
                             .label("导入需求")
-                            .icon(Icon::new(IconName::Upload)),
+                            .icon(Icon::new(IconName::Upload))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                import_from_excel::<Requirement, _>(
+                                    "导入需求",
+                                    |this: &mut Self, _| this.reload(),
+                                    window,
+                                    cx,
+                                );
+                            })),
+                    )
+                    .child(
+                        Button::new("export-req-btn")
+                            .ghost()
+                            .label("导出")
+                            .icon(Icon::new(IconName::Download))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.handle_export(window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("refresh-req-btn")
+                            .ghost()
+                            .icon(Icon::new(IconName::RefreshCw))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.sync_panel.update(cx, |panel, cx| panel.sync_now(cx));
+                            })),
                     ),
             )
+            .child(self.render_releases(cx))
+            .child(self.render_due_soon(&filtered_requirements, cx))
             .child(
                 // 需求列表
                 v_flex()
                     .flex_1()
                     .w_full()
                     .gap_2()
-                    .child(div().text_lg().font_semibold().child("需求列表"))
+                    .child(
+                        h_flex()
+                            .w_full()
+                            .items_center()
+                            .justify_between()
+                            .child(div().text_lg().font_semibold().child("需求列表"))
+                            .child(
+                                h_flex()
+                                    .gap_1()
+                                    .child(
+                                        Button::new("req-list-mode")
+                                            .ghost()
+                                            .small()
+                                            .label("列表")
+                                            .selected(!self.show_gantt)
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.show_gantt = false;
+                                                cx.notify();
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new("req-gantt-mode")
+                                            .ghost()
+                                            .small()
+                                            .label("甘特图")
+                                            .selected(self.show_gantt)
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.show_gantt = true;
+                                                cx.notify();
+                                            })),
+                                    ),
+                            ),
+                    )
                     .child(
                         div()
                             .w_full()
@@ -243,6 +366,8 @@ impl Render for RequirementView {
                                             "没有找到匹配的需求"
                                         },
                                     ))
+                            } else if self.show_gantt {
+                                div().child(self.render_gantt(&filtered_requirements, cx))
                             } else {
                                 div().child(self.render_requirement_list(filtered_requirements, cx))
                             }),
@@ -267,6 +392,267 @@ impl RequirementView {
             .child(div().text_2xl().font_bold().text_color(cx.theme().foreground).child(value))
     }
 
+    fn status_color(status: RequirementStatus) -> Rgba {
+        match status {
+            RequirementStatus::InProgress => rgb(0x3b82f6),
+            RequirementStatus::Done => rgb(0x22c55e),
+            RequirementStatus::Delayed => rgb(0xef4444),
+            RequirementStatus::NotStarted => rgb(0x6b7280),
+        }
+    }
+
+    /// 各版本的需求完成进度，按主版本号分组；点击版本只看该版本的需求
+    fn render_releases(&self, cx: &Context<Self>) -> impl IntoElement {
+        let groups = RequirementTool::group_by_release(
+            &self.searched_requirements(cx),
+            &self.releases,
+            Self::today(),
+        );
+        let mut lines: Vec<(String, Vec<ReleaseProgress>)> = Vec::new();
+        for group in groups {
+            let line = Version::parse(&group.version)
+                .map_or_else(|| "其他".to_string(), |version| format!("v{}.x", version.major));
+            match lines.last_mut() {
+                Some((last, groups)) if *last == line => groups.push(group),
+                _ => lines.push((line, vec![group])),
+            }
+        }
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(Icon::new(IconName::Rocket).size_4())
+                            .child(div().text_lg().font_semibold().child("版本"))
+                            .when_some(self.selected_version.clone(), |this, version| {
+                                this.child(
+                                    Button::new("clear-version-btn")
+                                        .ghost()
+                                        .xsmall()
+                                        .label(format!("仅显示 {} · 清除", version))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.selected_version = None;
+                                            cx.notify();
+                                        })),
+                                )
+                            }),
+                    )
+                    .child(
+                        Button::new("add-release-btn")
+                            .ghost()
+                            .small()
+                            .label("添加版本")
+                            .icon(Icon::new(IconName::Plus))
+                            .on_click(cx.listener(|_, _, window, cx| {
+                                open_release_form(
+                                    None,
+                                    |this: &mut Self, _, _| this.reload(),
+                                    window,
+                                    cx,
+                                );
+                            })),
+                    ),
+            )
+            .child(if lines.is_empty() {
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("暂无版本，添加版本或为需求填写版本号后显示")
+            } else {
+                div().child(v_flex().w_full().gap_2().children(lines.into_iter().map(
+                    |(line, groups)| {
+                        v_flex()
+                            .w_full()
+                            .gap_1()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_semibold()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(line),
+                            )
+                            .child(h_flex().w_full().gap_2().flex_wrap().children(
+                                groups.iter().map(|group| self.render_release_card(group, cx)),
+                            ))
+                    },
+                )))
+            })
+    }
+
+    fn render_release_card(&self, group: &ReleaseProgress, cx: &Context<Self>) -> impl IntoElement {
+        let selected = self.selected_version.as_ref() == Some(&group.version);
+        let version = group.version.clone();
+        let subtitle = group.release.as_ref().map(|release| {
+            let date = release.release_date.map_or("未定发布日期".to_string(), |date| {
+                date.with_timezone(&Local).format("%Y-%m-%d").to_string()
+            });
+            match release.name.as_str() {
+                "" => date,
+                name => format!("{} · {}", name, date),
+            }
+        });
+
+        v_flex()
+            .id(SharedString::from(format!("release-{}", group.version)))
+            .w(px(220.))
+            .p_3()
+            .gap_2()
+            .border_1()
+            .border_color(if selected { cx.theme().primary } else { cx.theme().border })
+            .rounded_md()
+            .cursor_pointer()
+            .on_click(cx.listener(move |this, _, _, cx| this.toggle_version(version.clone(), cx)))
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .child(div().font_semibold().child(group.version.clone()))
+                    .when_some(group.release.clone(), |this, release| {
+                        this.child(
+                            h_flex()
+                                .child(
+                                    Button::new(SharedString::from(format!(
+                                        "edit-release-{}",
+                                        release.id
+                                    )))
+                                    .ghost()
+                                    .xsmall()
+                                    .icon(Icon::new(IconName::PenTool))
+                                    .on_click(cx.listener({
+                                        let release = release.clone();
+                                        move |_, _, window, cx| {
+                                            cx.stop_propagation();
+                                            open_release_form(
+                                                Some(release.clone()),
+                                                |this: &mut Self, _, _| this.reload(),
+                                                window,
+                                                cx,
+                                            );
+                                        }
+                                    })),
+                                )
+                                .child(
+                                    Button::new(SharedString::from(format!(
+                                        "delete-release-{}",
+                                        release.id
+                                    )))
+                                    .ghost()
+                                    .xsmall()
+                                    .icon(Icon::new(IconName::Trash))
+                                    .on_click(cx.listener(
+                                        move |this, _, window, cx| {
+                                            cx.stop_propagation();
+                                            this.confirm_delete_release(
+                                                release.id.clone(),
+                                                window,
+                                                cx,
+                                            );
+                                        },
+                                    )),
+                                ),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(subtitle.unwrap_or_else(|| "未创建版本".to_string())),
+            )
+            .child(div().w_full().h(px(6.)).rounded_full().bg(cx.theme().border).child(
+                div().h_full().w(relative(group.ratio() as f32)).rounded_full().bg(rgb(0x22c55e)),
+            ))
+            .child(
+                h_flex()
+                    .w_full()
+                    .justify_between()
+                    .text_sm()
+                    .child(format!("完成 {}/{}", group.done, group.total()))
+                    .when(group.overdue > 0, |this| {
+                        this.child(
+                            div()
+                                .text_color(rgb(0xef4444))
+                                .child(format!("逾期 {}", group.overdue)),
+                        )
+                    }),
+            )
+    }
+
+    /// 即将到期、尚未完成的需求，越临近越醒目
+    fn render_due_soon(
+        &self,
+        requirements: &[&Requirement],
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let today = Self::today();
+        let due = RequirementTool::due_within(requirements, today, self.due_days);
+
+        v_flex()
+            .w_full()
+            .gap_2()
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(Icon::new(IconName::TriangleAlert).size_4())
+                            .child(div().text_lg().font_semibold().child("即将到期"))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(format!("{} 项", due.len())),
+                            ),
+                    )
+                    .child(div().w(px(128.)).child(Select::new(&self.due_select).small())),
+            )
+            .child(if due.is_empty() {
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!("{} 天内没有到期的未完成需求", self.due_days))
+            } else {
+                div().child(h_flex().w_full().gap_2().flex_wrap().children(due.into_iter().map(
+                    |req| {
+                        let days_left = req.days_left(today);
+                        // 两天内到期标红，其余标橙
+                        let color = if days_left <= 2 { rgb(0xef4444) } else { rgb(0xf59e0b) };
+                        v_flex()
+                            .min_w(px(192.))
+                            .p_3()
+                            .gap_1()
+                            .border_1()
+                            .border_color(color)
+                            .rounded_md()
+                            .child(div().font_semibold().child(req.title.clone()))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(format!("{} · {}", req.version, req.owner)),
+                            )
+                            .child(div().text_sm().text_color(color).child(match days_left {
+                                0 => "今天到期".to_string(),
+                                days => format!("{} 天后到期（{}）", days, req.end_day()),
+                            }))
+                    },
+                )))
+            })
+    }
+
     fn render_requirement_list(
         &self,
         requirements: Vec<&Requirement>,
@@ -320,20 +706,10 @@ impl RequirementView {
                             .px_3()
                             .py_1()
                             .rounded_md()
-                            .bg(match req.status.as_str() {
-                                "进行中" => rgb(0x3b82f6),
-                                "已完成" => rgb(0x22c55e),
-                                "延期" => rgb(0xef4444),
-                                _ => rgb(0x6b7280),
-                            })
-                            .text_color(match req.status.as_str() {
-                                "进行中" => rgb(0x3b82f6),
-                                "已完成" => rgb(0x22c55e),
-                                "延期" => rgb(0xef4444),
-                                _ => rgb(0x6b7280),
-                            })
+                            .bg(Self::status_color(req.status))
+                            .text_color(Self::status_color(req.status))
                             .text_sm()
-                            .child(req.status.clone()),
+                            .child(req.status.label()),
                     ),
             )
             .child(
@@ -354,7 +730,7 @@ impl RequirementView {
                             .gap_1()
                             .items_center()
                             .child(Icon::new(IconName::RotateCw).size_4())
-                            .child(format!("测试周期: {}", req.test_cycle)),
+                            .child(format!("测试周期: {} 天", req.test_cycle_days)),
                     )
                     .child(
                         h_flex()
@@ -389,7 +765,18 @@ impl RequirementView {
                                 Button::new(format!("edit-req-{}", req.id))
                                     .small()
                                     .ghost()
-                                    .icon(Icon::new(IconName::PenTool)),
+                                    .icon(Icon::new(IconName::PenTool))
+                                    .on_click(cx.listener({
+                                        let req = req.clone();
+                                        move |_, _, window, cx| {
+                                            open_form::<RequirementForm, _>(
+                                                Some(req.clone()),
+                                                |this: &mut Self, _, _| this.reload(),
+                                                window,
+                                                cx,
+                                            );
+                                        }
+                                    })),
                             )
                             .child(
                                 Button::new(format!("view-req-{}", req.id))
@@ -407,3 +794,216 @@ impl RequirementView {
             )
     }
 }
+
+impl RequirementView {
+    fn start_gantt_drag(
+        &mut self,
+        id: String,
+        edge: GanttEdge,
+        event: &MouseDownEvent,
+        cx: &mut Context<Self>,
+    ) {
+        cx.stop_propagation();
+        self.gantt_drag = Some(GanttDrag { id, edge, origin: event.position.x, days: 0 });
+        cx.notify();
+    }
+
+    fn on_gantt_mouse_move(&mut self, event: &MouseMoveEvent, cx: &mut Context<Self>) {
+        let Some(drag) = &mut self.gantt_drag else {
+            return;
+        };
+        let days = (f32::from(event.position.x - drag.origin) / GANTT_DAY_WIDTH).round() as i64;
+        if days != drag.days {
+            drag.days = days;
+            cx.notify();
+        }
+    }
+
+    /// 松开鼠标时按拖动的天数保存新的起止日期
+    fn finish_gantt_drag(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct RequirementRescheduled;
+        let Some(drag) = self.gantt_drag.take() else {
+            return;
+        };
+        cx.notify();
+        let Some(req) = self.requirements.iter().find(|req| req.id == drag.id) else {
+            return;
+        };
+        if drag.days == 0 {
+            return;
+        }
+
+        let req = req.rescheduled(drag.edge, drag.days, Self::today());
+        let message = match self.store.requirements().upsert(&req) {
+            Ok(()) => {
+                format!("已将需求 #{} 调整为 {} ~ {}", req.id, req.start_day(), req.end_day())
+            },
+            Err(err) => {
+                tracing::error!("Failed to reschedule requirement {}: {:?}", req.id, err);
+                format!("调整需求日期失败: {}", err)
+            },
+        };
+        self.reload();
+        window.push_notification(
+            Notification::new().message(message).id::<RequirementRescheduled>(),
+            cx,
+        );
+    }
+
+    /// 甘特图：左侧为需求名称，右侧时间轴可横向滚动；拖动色条整体平移，拖动两端调整起止日期
+    fn render_gantt(&self, requirements: &[&Requirement], cx: &Context<Self>) -> impl IntoElement {
+        let today = Self::today();
+        let gantt = RequirementTool::gantt(requirements, today);
+        let day_width = px(GANTT_DAY_WIDTH);
+        let row_height = px(GANTT_ROW_HEIGHT);
+
+        let labels = v_flex()
+            .w(px(GANTT_LABEL_WIDTH))
+            .flex_none()
+            .border_r_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .h(row_height)
+                    .px_2()
+                    .flex()
+                    .items_center()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("需求"),
+            )
+            .children(gantt.bars.iter().map(|bar| {
+                let req = bar.requirement;
+                v_flex()
+                    .h(row_height)
+                    .px_2()
+                    .justify_center()
+                    .overflow_hidden()
+                    .child(div().text_sm().truncate().child(req.title.clone()))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .truncate()
+                            .child(format!("{} · {}", req.version, req.owner)),
+                    )
+            }));
+
+        let header = h_flex().h(row_height).children((0..gantt.days).map(|offset| {
+            let day = gantt.day(offset);
+            let label = if offset == 0 || day.day() == 1 {
+                day.format("%m-%d").to_string()
+            } else {
+                day.format("%d").to_string()
+            };
+            div()
+                .w(day_width)
+                .h_full()
+                .flex_none()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .when(day.weekday().number_from_monday() >= 6, |this| this.bg(cx.theme().muted))
+                .child(label)
+        }));
+
+        let rows = gantt.bars.iter().map(|bar| {
+            let req = bar.requirement;
+            // 拖动中的色条按预览位置绘制，松开后才保存
+            let (offset, span, overdue) = match &self.gantt_drag {
+                Some(drag) if drag.id == req.id => {
+                    let preview = req.rescheduled(drag.edge, drag.days, today);
+                    (
+                        (preview.start_day() - gantt.start).num_days(),
+                        preview.cycle_days(),
+                        preview.status == RequirementStatus::Delayed,
+                    )
+                },
+                _ => (bar.offset, bar.span, bar.overdue),
+            };
+            let color = if overdue { rgb(0xef4444) } else { Self::status_color(req.status) };
+            let handle = |edge: GanttEdge| {
+                let id = req.id.clone();
+                div()
+                    .absolute()
+                    .top_0()
+                    .bottom_0()
+                    .w(px(GANTT_HANDLE_WIDTH))
+                    .when(edge == GanttEdge::Start, |this| this.left_0())
+                    .when(edge == GanttEdge::End, |this| this.right_0())
+                    .cursor(CursorStyle::ResizeLeftRight)
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                            this.start_gantt_drag(id.clone(), edge, event, cx);
+                        }),
+                    )
+            };
+            let id = req.id.clone();
+
+            div().relative().w_full().h(row_height).child(
+                div()
+                    .absolute()
+                    .top(px(6.))
+                    .h(px(GANTT_ROW_HEIGHT - 12.))
+                    .left(px(offset as f32 * GANTT_DAY_WIDTH))
+                    .w(px(span as f32 * GANTT_DAY_WIDTH))
+                    .rounded_md()
+                    .bg(color)
+                    .px_2()
+                    .flex()
+                    .items_center()
+                    .overflow_hidden()
+                    .text_xs()
+                    .text_color(gpui::white())
+                    .cursor(CursorStyle::OpenHand)
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                            this.start_gantt_drag(id.clone(), GanttEdge::Both, event, cx);
+                        }),
+                    )
+                    .child(format!("{} 天", span))
+                    .child(handle(GanttEdge::Start))
+                    .child(handle(GanttEdge::End)),
+            )
+        });
+
+        let timeline = div()
+            .id("req-gantt-timeline")
+            .flex_1()
+            .overflow_x_scroll()
+            .child(
+                v_flex()
+                    .relative()
+                    .w(px(gantt.days as f32 * GANTT_DAY_WIDTH))
+                    .child(header)
+                    .children(rows)
+                    .child(
+                        // 今天
+                        div()
+                            .absolute()
+                            .top_0()
+                            .bottom_0()
+                            .left(px((gantt.today as f32 + 0.5) * GANTT_DAY_WIDTH - 1.))
+                            .w(px(2.))
+                            .bg(rgb(0xf59e0b)),
+                    ),
+            )
+            .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _, cx| {
+                this.on_gantt_mouse_move(event, cx);
+            }))
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|this, _, window, cx| this.finish_gantt_drag(window, cx)),
+            )
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|this, _, window, cx| this.finish_gantt_drag(window, cx)),
+            );
+
+        h_flex().w_full().items_start().child(labels).child(timeline)
+    }
+}
@@ -0,0 +1,252 @@
+use chrono::{Local, NaiveDate};
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled,
+    Subscription, Window, div,
+};
+use gpui_component::{
+    ActiveTheme, WindowExt,
+    button::*,
+    calendar::Date,
+    checkbox::Checkbox,
+    date_picker::{DatePicker, DatePickerEvent, DatePickerState},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    notification::Notification,
+    v_flex,
+};
+use tools::{Coded as _, Requirement, RequirementDraft, RequirementFormError, Store};
+
+use crate::{
+    AppState,
+    utils::picked_date,
+    view::{FormSaved, PersonInput, RecordForm, field_error, form_field},
+};
+
+/// Dialog body for creating a requirement, or editing one when `existing`
+/// is set.
+pub struct RequirementForm {
+    store: Store,
+    existing: Option<Requirement>,
+    id_input: Entity<InputState>,
+    title_input: Entity<InputState>,
+    version_input: Entity<InputState>,
+    owner_input: Entity<PersonInput>,
+    start_picker: Entity<DatePickerState>,
+    end_picker: Entity<DatePickerState>,
+    draft: RequirementDraft,
+    errors: Vec<RequirementFormError>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<FormSaved<Requirement>> for RequirementForm {}
+
+impl RecordForm for RequirementForm {
+    type Record = Requirement;
+
+    const NOUN: &'static str = "需求";
+    const WIDTH: f32 = 600.;
+
+    fn new(existing: Option<Requirement>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let draft = existing.as_ref().map_or_else(RequirementDraft::new, RequirementDraft::edit);
+        let mut input = |placeholder: &'static str, value: &str| {
+            cx.new(|cx| {
+                let mut input = InputState::new(window, cx).placeholder(placeholder);
+                input.set_value(value.to_string(), window, cx);
+                input
+            })
+        };
+        let id_input = input("留空则生成本地编号", &draft.id);
+        let title_input = input("需求标题", &draft.title);
+        let version_input = input("例如 1.2.0", &draft.version);
+        let owner_input = cx
+            .new(|cx| PersonInput::new("输入姓名或从建议中选择", draft.owner.clone(), window, cx));
+        let mut picker = |date: Option<NaiveDate>| {
+            cx.new(|cx| {
+                let mut picker = DatePickerState::new(window, cx);
+                picker.set_date(Date::Single(date), window, cx);
+                picker
+            })
+        };
+        let start_picker = picker(draft.start);
+        let end_picker = picker(draft.end);
+
+        let subscriptions = vec![
+            cx.subscribe_in(&id_input, window, Self::on_input_event),
+            cx.subscribe_in(&title_input, window, Self::on_input_event),
+            cx.subscribe_in(&version_input, window, Self::on_input_event),
+            cx.subscribe(&start_picker, |this, _, ev, cx| match ev {
+                DatePickerEvent::Change(date) => {
+                    this.draft.start = picked_date(date);
+                    cx.notify();
+                },
+            }),
+            cx.subscribe(&end_picker, |this, _, ev, cx| match ev {
+                DatePickerEvent::Change(date) => {
+                    this.draft.end = picked_date(date);
+                    cx.notify();
+                },
+            }),
+        ];
+
+        Self {
+            store: AppState::global(cx).store.clone(),
+            existing,
+            id_input,
+            title_input,
+            version_input,
+            owner_input,
+            start_picker,
+            end_picker,
+            draft,
+            errors: Vec::new(),
+            _subscriptions: subscriptions,
+        }
+    }
+}
+
+impl RequirementForm {
+    fn on_input_event(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value().to_string();
+            if state == &self.id_input {
+                self.draft.id = value;
+            } else if state == &self.title_input {
+                self.draft.title = value;
+            } else if state == &self.version_input {
+                self.draft.version = value;
+            }
+            cx.notify();
+        }
+    }
+
+    fn today() -> NaiveDate {
+        Local::now().date_naive()
+    }
+
+    fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct RequirementSaved;
+        self.draft.owner = self.owner_input.read(cx).value(cx);
+        let requirements = self.store.requirements();
+        let req = match self.draft.validate(
+            self.existing.as_ref(),
+            |id| {
+                requirements.get(id).map(|req| req.is_some()).unwrap_or_else(|err| {
+                    tracing::error!("Failed to look up requirement {}: {:?}", id, err);
+                    false
+                })
+            },
+            Self::today(),
+        ) {
+            Ok(req) => req,
+            Err(errors) => {
+                self.errors = errors;
+                cx.notify();
+                return;
+            },
+        };
+        self.errors.clear();
+
+        let message = match requirements.upsert(&req) {
+            Ok(()) => {
+                let message = format!("已保存需求 #{}", req.id);
+                cx.emit(FormSaved(req));
+                window.close_dialog(cx);
+                message
+            },
+            Err(err) => {
+                tracing::error!("Failed to save requirement {}: {:?}", req.id, err);
+                format!("保存需求失败: {}", err)
+            },
+        };
+        window.push_notification(Notification::new().message(message).id::<RequirementSaved>(), cx);
+    }
+
+    fn field(
+        &self,
+        label: &'static str,
+        required: bool,
+        input: impl IntoElement,
+        cx: &App,
+    ) -> impl IntoElement {
+        form_field(label, required, field_error(&self.errors, label), input, cx)
+    }
+
+    /// What the current dates and completion make of the requirement, so the
+    /// derived status is visible before saving.
+    fn preview(&self) -> Option<String> {
+        let req = self.draft.validate(self.existing.as_ref(), |_| false, Self::today()).ok()?;
+        Some(format!("测试周期 {} 天，状态将为「{}」", req.test_cycle_days, req.status.label()))
+    }
+}
+
+impl Render for RequirementForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let creating = self.existing.is_none();
+
+        v_flex()
+            .w_full()
+            .gap_3()
+            .child(self.field("ID", false, Input::new(&self.id_input).disabled(!creating), cx))
+            .child(self.field("标题", true, Input::new(&self.title_input), cx))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "版本",
+                        true,
+                        Input::new(&self.version_input),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "负责人",
+                        true,
+                        self.owner_input.clone(),
+                        cx,
+                    ))),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_3()
+                    .child(div().flex_1().child(self.field(
+                        "开始日期",
+                        true,
+                        DatePicker::new(&self.start_picker),
+                        cx,
+                    )))
+                    .child(div().flex_1().child(self.field(
+                        "结束日期",
+                        true,
+                        DatePicker::new(&self.end_picker),
+                        cx,
+                    ))),
+            )
+            .child(
+                Checkbox::new("req-completed")
+                    .label("已完成")
+                    .checked(self.draft.completed)
+                    .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                        this.draft.completed = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(
+                self.preview().unwrap_or_else(|| "状态将根据日期和完成情况自动计算".to_string()),
+            ))
+            .child(
+                h_flex().w_full().justify_end().child(
+                    Button::new("save-req-btn")
+                        .primary()
+                        .label("保存")
+                        .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
+                ),
+            )
+    }
+}
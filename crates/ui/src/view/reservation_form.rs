@@ -0,0 +1,155 @@
+use chrono::{TimeDelta, Utc};
+use gpui::{
+    AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render, SharedString,
+    Styled, Subscription, Task, Window, div, prelude::FluentBuilder, px, rgb,
+};
+use gpui_component::{
+    IndexPath, WindowExt,
+    button::*,
+    h_flex,
+    input::{Input, InputState},
+    notification::Notification,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+use tools::{LeaseTool, VirtualEnv};
+
+use crate::{AppState, view::form_field};
+
+/// Lease lengths offered when reserving an environment.
+const LEASES: [(&str, i64); 6] =
+    [("1 小时", 1), ("2 小时", 2), ("4 小时", 4), ("8 小时", 8), ("1 天", 24), ("3 天", 72)];
+
+pub enum ReservationFormEvent {
+    Reserved(VirtualEnv),
+}
+
+/// Dialog body for reserving an environment, or extending one's own lease.
+pub struct ReservationForm {
+    leases: LeaseTool,
+    user: String,
+    env: VirtualEnv,
+    lease_select: Entity<SelectState<Vec<SharedString>>>,
+    reason_input: Entity<InputState>,
+    hours: Option<i64>,
+    error: Option<String>,
+    reserve_task: Option<Task<()>>,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<ReservationFormEvent> for ReservationForm {}
+
+impl ReservationForm {
+    pub fn new(env: VirtualEnv, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let labels = LEASES.iter().map(|(label, _)| SharedString::from(*label)).collect();
+        let default = 1;
+        let lease_select =
+            cx.new(|cx| SelectState::new(labels, Some(IndexPath::new(default)), window, cx));
+        let subscription = cx.subscribe_in(
+            &lease_select,
+            window,
+            |this, _, event: &SelectEvent<Vec<SharedString>>, _, cx| {
+                let SelectEvent::Confirm(value) = event;
+                this.hours = value.as_deref().and_then(|value| {
+                    LEASES.iter().find(|(label, _)| *label == value).map(|(_, hours)| *hours)
+                });
+                cx.notify();
+            },
+        );
+        let reason_input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx).placeholder("例如：版本回归、问题复现");
+            input.set_value(env.reason.clone().unwrap_or_default(), window, cx);
+            input
+        });
+        let state = AppState::global(cx);
+
+        Self {
+            leases: state.leases.clone(),
+            user: state.user.clone(),
+            env,
+            lease_select,
+            reason_input,
+            hours: Some(LEASES[default].1),
+            error: None,
+            reserve_task: None,
+            _subscription: subscription,
+        }
+    }
+
+    fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        struct EnvReserved;
+        let hours = self.hours.unwrap_or_default();
+        let reason = self.reason_input.read(cx).value().to_string();
+        let (leases, env_id, user) = (self.leases.clone(), self.env.id.clone(), self.user.clone());
+        self.error = None;
+
+        self.reserve_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result =
+                leases.reserve(&env_id, &user, &reason, TimeDelta::hours(hours), Utc::now()).await;
+            _ = this.update_in(cx, |this, window, cx| {
+                this.reserve_task = None;
+                match result {
+                    Ok(env) => {
+                        let message = format!("已占用环境 {}", env.name);
+                        cx.emit(ReservationFormEvent::Reserved(env));
+                        window.close_dialog(cx);
+                        window.push_notification(
+                            Notification::new().message(message).id::<EnvReserved>(),
+                            cx,
+                        );
+                    },
+                    Err(err) => {
+                        tracing::error!("Failed to reserve {}: {}", env_id, err);
+                        this.error = Some(err.to_string());
+                        cx.notify();
+                    },
+                }
+            });
+        }));
+        cx.notify();
+    }
+}
+
+impl Render for ReservationForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let reserving = self.reserve_task.is_some();
+        v_flex()
+            .w_full()
+            .gap_3()
+            .child(form_field("占用时长", true, None, Select::new(&self.lease_select), cx))
+            .child(form_field("占用原因", true, None, Input::new(&self.reason_input), cx))
+            .when_some(self.error.clone(), |this, error| {
+                this.child(div().text_sm().text_color(rgb(0xef4444)).child(error))
+            })
+            .child(
+                h_flex().w_full().justify_end().child(
+                    Button::new("reserve-env-btn")
+                        .primary()
+                        .label(if reserving { "正在占用..." } else { "占用" })
+                        .disabled(reserving)
+                        .on_click(cx.listener(|this, _, window, cx| this.submit(window, cx))),
+                ),
+            )
+    }
+}
+
+/// Open the reservation form for `env` in a dialog; `on_reserved` runs on
+/// the opening view once the environment is reserved.
+pub fn open_reservation_form<V: 'static>(
+    env: VirtualEnv,
+    on_reserved: fn(&mut V, &VirtualEnv, &mut Context<V>),
+    window: &mut Window,
+    cx: &mut Context<V>,
+) {
+    let title = format!("占用环境 {}", env.name);
+    let form = cx.new(|cx| ReservationForm::new(env, window, cx));
+    cx.subscribe(&form, move |this, _, event: &ReservationFormEvent, cx| {
+        let ReservationFormEvent::Reserved(env) = event;
+        on_reserved(this, env, cx);
+        cx.notify();
+    })
+    .detach();
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog.title(title.clone()).width(px(420.)).child(form.clone())
+    });
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, ParentElement, Render, Styled,
+    Task, Window, div, rgb,
+};
+use gpui_component::{ActiveTheme, Icon, IconName, Sizable, h_flex, v_flex};
+use tools::{ChangeKind, SyncConfig, SyncReport, SyncSource, SyncTool};
+
+use crate::AppState;
+
+/// How many changes of the last sync are listed before collapsing the rest.
+const MAX_CHANGES_SHOWN: usize = 5;
+
+pub enum SyncEvent {
+    Synced,
+}
+
+/// Shows when a source was last synced and what that sync changed, and
+/// keeps syncing it on the configured interval.
+pub struct SyncPanel {
+    source: SyncSource,
+    sync: SyncTool,
+    config: Arc<SyncConfig>,
+    last: Option<SyncReport>,
+    error: Option<String>,
+    syncing: bool,
+    _sync_task: Option<Task<()>>,
+    _schedule_task: Task<()>,
+}
+
+impl EventEmitter<SyncEvent> for SyncPanel {}
+
+impl SyncPanel {
+    pub fn view(source: SyncSource, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(source, cx))
+    }
+
+    fn new(source: SyncSource, cx: &mut Context<Self>) -> Self {
+        let state = AppState::global(cx);
+        let sync = state.sync.clone();
+        let config = state.sync_config.clone();
+        let last = state.store.last_sync(source).unwrap_or_else(|err| {
+            tracing::error!("Failed to load last {} sync: {:?}", source.code(), err);
+            None
+        });
+
+        let interval = config.interval();
+        let schedule_task = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(interval).await;
+                if this.update(cx, |this, cx| this.sync_now(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            source,
+            sync,
+            config,
+            last,
+            error: None,
+            syncing: false,
+            _sync_task: None,
+            _schedule_task: schedule_task,
+        }
+    }
+
+    /// Pull the source now. Listeners get [`SyncEvent::Synced`] afterwards
+    /// even if nothing is configured, so a refresh always reloads the view.
+    pub fn sync_now(&mut self, cx: &mut Context<Self>) {
+        if self.syncing {
+            return;
+        }
+        self.syncing = true;
+        cx.notify();
+
+        let sync = self.sync.clone();
+        let config = self.config.clone();
+        let source = self.source;
+        self._sync_task = Some(cx.spawn(async move |this, cx| {
+            let result = sync.sync_source(source, &config).await;
+            _ = this.update(cx, |this, cx| {
+                this.syncing = false;
+                match result {
+                    Some(Ok(report)) => {
+                        this.last = Some(report);
+                        this.error = None;
+                    },
+                    Some(Err(err)) => {
+                        tracing::error!("Failed to sync {}: {}", source.code(), err);
+                        this.error = Some(err.to_string());
+                    },
+                    None => this.error = None,
+                }
+                cx.emit(SyncEvent::Synced);
+                cx.notify();
+            });
+        }));
+    }
+}
+
+impl Render for SyncPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = if self.syncing {
+            "正在同步...".to_string()
+        } else if let Some(last) = &self.last {
+            format!(
+                "上次同步：{}（拉取 {} 条，新增 {}，修改 {}）",
+                last.synced_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                last.fetched,
+                last.created(),
+                last.updated()
+            )
+        } else {
+            "尚未同步".to_string()
+        };
+
+        let changes = self.last.as_ref().map(|last| last.changes.as_slice()).unwrap_or_default();
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .text_sm()
+            .text_color(cx.theme().muted_foreground)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(Icon::new(IconName::RefreshCw).small())
+                    .child(status)
+                    .children(
+                        self.error.clone().map(|err| div().text_color(rgb(0xef4444)).child(err)),
+                    ),
+            )
+            .children(changes.iter().take(MAX_CHANGES_SHOWN).map(|change| {
+                h_flex()
+                    .gap_2()
+                    .pl_6()
+                    .child(
+                        div()
+                            .text_color(match change.kind {
+                                ChangeKind::Created => rgb(0x22c55e),
+                                ChangeKind::Updated => rgb(0x3b82f6),
+                            })
+                            .child(change.kind.label()),
+                    )
+                    .child(format!("#{}", change.record_id))
+                    .child(change.title.clone())
+            }))
+            .children(
+                (changes.len() > MAX_CHANGES_SHOWN)
+                    .then(|| div().pl_6().child(format!("等 {} 条变更", changes.len()))),
+            )
+    }
+}